@@ -98,7 +98,9 @@ where
         }
     }
 
-    let mut git = {
+    // Keep the snapshot of the namespace, if any, alive until the subprocess
+    // has exited
+    let (mut git, _snapshot) = {
         let storage = pool.get().await.map_err(|e| {
             tracing::error!(err=?e, "error opening storage pool");
             Error::Unexpected(Box::new(e))
@@ -43,15 +43,35 @@ pub enum Error {
 // basically the same logic it doesn't seem ideal to expose this logic as a part
 // of librads public API and it doesn't seem like enough code to warrant a new
 // crate.
+//
+// Fetches are served from a `Snapshot` of the namespace, so that a concurrent
+// replication can't be observed half-way through. The snapshot must be kept
+// alive until the command has exited.
 pub(super) fn create_command(
     storage: &storage::Storage,
     service: ssh_service::SshService,
-) -> Result<tokio::process::Command, Error> {
+) -> Result<(tokio::process::Command, Option<storage::Snapshot>), Error> {
+    let is_upload = service.is_upload();
     let urn = service.path.into();
     guard_has_urn(storage, &urn)?;
 
+    let snapshot = if is_upload {
+        let snapshot = storage
+            .read_only()
+            .snapshot(Some(&urn))
+            .map_err(|e| Error::Other(Box::new(e)))?;
+        Some(snapshot)
+    } else {
+        None
+    };
+
     let mut git = tokio::process::Command::new("git");
-    git.current_dir(&storage.path()).args(&[
+    git.current_dir(
+        snapshot
+            .as_ref()
+            .map_or_else(|| storage.path(), |snapshot| snapshot.path()),
+    )
+    .args(&[
         &format!("--namespace={}", Namespace::from(&urn)),
         "-c",
         "transfer.hiderefs=refs/remotes",
@@ -64,7 +84,8 @@ pub(super) fn create_command(
     match service.service.0 {
         GitService::UploadPack | GitService::UploadPackLs => {
             // Fetching remotes is ok, pushing is not
-            visible_remotes(storage, &urn)?.for_each(|remote_ref| {
+            let view = snapshot.as_deref().unwrap_or_else(|| storage.read_only());
+            visible_remotes(view, &urn)?.for_each(|remote_ref| {
                 git.arg("-c")
                     .arg(format!("uploadpack.hiderefs=!^{}", remote_ref));
             });
@@ -82,7 +103,7 @@ pub(super) fn create_command(
     ) {
         git.arg("--advertise-refs");
     }
-    Ok(git)
+    Ok((git, snapshot))
 }
 
 fn guard_has_urn<S>(storage: S, urn: &Urn) -> Result<(), Error>
@@ -17,11 +17,14 @@ pub use sockets::Sockets;
 
 pub mod announce;
 pub mod client;
+mod events;
 pub mod io;
 pub mod messages;
+pub mod purge;
 pub mod request_pull;
 mod rpc;
 pub mod sockets;
+pub mod stats;
 pub mod wire_types;
 
 #[instrument(name = "api subroutine", skip(spawner, peer, sockets))]
@@ -36,7 +39,15 @@ where
     S: Signer + Clone,
     G: RequestPullGuard,
 {
-    let tasks = Box::pin(rpc::tasks(spawner, peer, sockets.rpc(), announce_wait_time));
+    let tasks = Box::pin(futures::stream::select(
+        rpc::tasks(
+            spawner.clone(),
+            peer.clone(),
+            sockets.rpc(),
+            announce_wait_time,
+        ),
+        events::tasks(spawner, peer, sockets.events()),
+    ));
     if let Some(timeout) = linger_timeout {
         link_async::tasks::run_until_idle(tasks, timeout).await
     } else {
@@ -20,7 +20,7 @@ use tokio::net::UnixStream;
 
 use librad::{git::Urn, PeerId};
 
-use super::{announce, io, messages, request_pull};
+use super::{announce, io, messages, purge, request_pull, stats};
 
 pub struct Connection<T> {
     socket: T,
@@ -273,3 +273,21 @@ impl Command<request_pull::Request, request_pull::Response> {
         }
     }
 }
+
+impl Command<purge::Request, purge::Response> {
+    pub fn purge(urn: Urn) -> Self {
+        Self {
+            payload: purge::Request { urn },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Command<stats::Request, stats::Response> {
+    pub fn stats(urn: Option<Urn>) -> Self {
+        Self {
+            payload: stats::Request { urn },
+            _marker: PhantomData,
+        }
+    }
+}
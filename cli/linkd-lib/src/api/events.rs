@@ -0,0 +1,96 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::sync::Arc;
+
+use async_compat::CompatExt as _;
+use futures::{future::FutureExt as _, pin_mut, stream::StreamExt as _};
+use tokio::net::{UnixListener, UnixStream};
+
+use librad::{
+    net::{peer::Peer, protocol::RequestPullGuard},
+    Signer,
+};
+use link_async::{incoming::UnixListenerExt, Spawner};
+
+use super::{
+    io::{MessageReader, MessageWriter},
+    wire_types::events::{Event, EventMessage, Subscribe},
+};
+
+pub fn tasks<S, G>(
+    spawner: Arc<Spawner>,
+    peer: Peer<S, G>,
+    socket: &UnixListener,
+) -> impl futures::stream::Stream<Item = link_async::Task<()>> + Send + '_
+where
+    S: Signer + Clone,
+    G: RequestPullGuard,
+{
+    socket
+        .incoming()
+        .map(move |stream| match stream {
+            Ok(stream) => {
+                tracing::debug!("new events connection");
+                Some(spawner.spawn(events(peer.clone(), stream)))
+            },
+            Err(e) => {
+                tracing::error!(err=?e, "error accepting connection");
+                None
+            },
+        })
+        .take_while(|e| futures::future::ready(e.is_some()))
+        .filter_map(futures::future::ready)
+}
+
+async fn events<S, G>(peer: Peer<S, G>, stream: UnixStream)
+where
+    S: Signer + Clone,
+    G: RequestPullGuard,
+{
+    let (rx, sx) = stream.into_split();
+    let mut reader = MessageReader::new(rx.compat());
+    let mut writer = MessageWriter::new(sx.compat());
+
+    let subscribe = match reader.read_message::<Subscribe>().await {
+        Ok(Some(msg)) => msg.headers,
+        Ok(None) => {
+            tracing::debug!("connection closed before subscribing");
+            return;
+        },
+        Err(e) => {
+            tracing::error!(err=?e, "error reading subscription, closing");
+            return;
+        },
+    };
+    tracing::info!(?subscribe, "new subscription");
+
+    let events = peer
+        .subscribe_filtered(subscribe.into())
+        .filter_map(|event| futures::future::ready(Event::from_subscription(event)))
+        .fuse();
+    pin_mut!(events);
+    loop {
+        futures::select! {
+            event = events.next() => match event {
+                Some(event) => {
+                    let msg = EventMessage::from(event);
+                    if let Err(e) = writer.write_message(&msg).await {
+                        tracing::debug!(err=?e, "error sending event, closing");
+                        break;
+                    }
+                },
+                None => {
+                    tracing::info!("network shut down, closing subscription");
+                    break;
+                },
+            },
+            // Clients are not expected to send anything after subscribing,
+            // but we need to notice when they hang up
+            _ = reader.read_message::<Subscribe>().fuse() => {
+                tracing::debug!("subscriber went away");
+                break;
+            },
+        }
+    }
+}
@@ -5,7 +5,7 @@
 
 use rand::Rng;
 
-use super::{announce, request_pull};
+use super::{announce, purge, request_pull, stats};
 
 #[derive(
     Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, minicbor::Decode, minicbor::Encode,
@@ -78,6 +78,8 @@ pub struct Request {
 pub enum RequestPayload {
     Announce(announce::Request),
     RequestPull(request_pull::Request),
+    Purge(purge::Request),
+    Stats(stats::Request),
 }
 
 impl From<announce::Request> for RequestPayload {
@@ -92,6 +94,18 @@ impl From<request_pull::Request> for RequestPayload {
     }
 }
 
+impl From<purge::Request> for RequestPayload {
+    fn from(x: purge::Request) -> Self {
+        Self::Purge(x)
+    }
+}
+
+impl From<stats::Request> for RequestPayload {
+    fn from(x: stats::Request) -> Self {
+        Self::Stats(x)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Response<P> {
     pub request_id: RequestId,
@@ -110,6 +124,8 @@ pub enum ResponsePayload<P> {
 pub enum SomeSuccess {
     Announce(announce::Response),
     RequestPull(request_pull::Response),
+    Purge(purge::Response),
+    Stats(stats::Response),
 }
 
 impl From<announce::Response> for SomeSuccess {
@@ -124,6 +140,18 @@ impl From<request_pull::Response> for SomeSuccess {
     }
 }
 
+impl From<purge::Response> for SomeSuccess {
+    fn from(x: purge::Response) -> Self {
+        Self::Purge(x)
+    }
+}
+
+impl From<stats::Response> for SomeSuccess {
+    fn from(x: stats::Response) -> Self {
+        Self::Stats(x)
+    }
+}
+
 impl minicbor::Encode for SomeSuccess {
     fn encode<W: minicbor::encode::Write>(
         &self,
@@ -132,6 +160,8 @@ impl minicbor::Encode for SomeSuccess {
         match self {
             SomeSuccess::Announce(x) => e.encode(x)?.ok(),
             SomeSuccess::RequestPull(x) => e.encode(x)?.ok(),
+            SomeSuccess::Purge(x) => e.encode(x)?.ok(),
+            SomeSuccess::Stats(x) => e.encode(x)?.ok(),
         }
     }
 }
@@ -0,0 +1,40 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use librad::git::{purge::Purged, Urn};
+
+#[derive(Clone, Debug, PartialEq, Eq, minicbor::Decode, minicbor::Encode)]
+pub struct Request {
+    #[n(0)]
+    pub urn: Urn,
+}
+
+/// Summary of what was removed from the storage.
+///
+/// See [`librad::git::purge::purge`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, minicbor::Decode, minicbor::Encode)]
+pub struct Response {
+    /// Number of refs deleted.
+    #[n(0)]
+    pub refs: u64,
+    /// Number of tracking entries removed.
+    #[n(1)]
+    pub tracking: u64,
+    /// Number of collaborative object cache entries removed.
+    #[n(2)]
+    pub cob_cache: u64,
+    /// Whether an include file was removed.
+    #[n(3)]
+    pub include: bool,
+}
+
+impl From<Purged> for Response {
+    fn from(p: Purged) -> Self {
+        Self {
+            refs: p.refs.len() as u64,
+            tracking: p.tracking as u64,
+            cob_cache: p.cob_cache.len() as u64,
+            include: p.include.is_some(),
+        }
+    }
+}
@@ -22,7 +22,9 @@ use super::{
     announce,
     io::{self, SocketTransportError, Transport},
     messages,
+    purge,
     request_pull,
+    stats,
 };
 
 pub fn tasks<S, G>(
@@ -98,7 +100,19 @@ async fn rpc<S, G>(
                                     tracing::info!(?p, "dispatching request");
                                     listener.ack().await;
                                     listener.handle(peer, p).boxed()
-                                }
+                                },
+                                messages::RequestPayload::Purge(p) => {
+                                    let mut listener = Listener::purge(next.mode, sx.clone());
+                                    tracing::info!(?p, "dispatching request");
+                                    listener.ack().await;
+                                    listener.handle(peer, p).boxed()
+                                },
+                                messages::RequestPayload::Stats(p) => {
+                                    let mut listener = Listener::stats(next.mode, sx.clone());
+                                    tracing::info!(?p, "dispatching request");
+                                    listener.ack().await;
+                                    listener.handle(peer, p).boxed()
+                                },
                             })
                         };
                         running_handlers.push(handler);
@@ -346,3 +360,97 @@ impl Listener<request_pull::Response> {
         }
     }
 }
+
+impl Listener<purge::Response> {
+    fn purge(
+        mode: messages::RequestMode,
+        send: Sender<messages::Response<messages::SomeSuccess>>,
+    ) -> Self {
+        Self {
+            request_id: Default::default(),
+            send,
+            interest: mode.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[tracing::instrument(skip(self, peer))]
+    async fn handle<S, G>(mut self, peer: Peer<S, G>, purge::Request { urn }: purge::Request)
+    where
+        S: Signer + Clone,
+        G: RequestPullGuard,
+    {
+        tracing::info!(urn = %urn, "received purge");
+        let paths = peer.protocol_config().paths.clone();
+        let res = peer
+            .using_storage({
+                let urn = urn.clone();
+                move |storage| librad::git::purge::purge(storage, &paths, &urn)
+            })
+            .await;
+        match res {
+            Ok(Ok(purged)) => {
+                tracing::info!(urn = %urn, refs = purged.refs.len(), "purged");
+                self.success(purge::Response::from(purged).into()).await
+            },
+            Ok(Err(err)) => {
+                tracing::error!(err = %err, "failed to purge");
+                self.error(format!("unable to purge `{}`: {}", urn, err))
+                    .await
+            },
+            Err(err) => {
+                tracing::error!(err = %err, "failed to obtain storage");
+                self.error("purge failed due to internal storage error".to_string())
+                    .await
+            },
+        }
+    }
+}
+
+impl Listener<stats::Response> {
+    fn stats(
+        mode: messages::RequestMode,
+        send: Sender<messages::Response<messages::SomeSuccess>>,
+    ) -> Self {
+        Self {
+            request_id: Default::default(),
+            send,
+            interest: mode.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[tracing::instrument(skip(self, peer))]
+    async fn handle<S, G>(mut self, peer: Peer<S, G>, stats::Request { urn }: stats::Request)
+    where
+        S: Signer + Clone,
+        G: RequestPullGuard,
+    {
+        use librad::git::storage::stats;
+
+        tracing::info!(urn = ?urn, "received stats");
+        let res = peer
+            .using_storage(move |storage| match urn {
+                Some(urn) => stats::for_urn(storage, &urn).map(|stats| vec![stats]),
+                None => storage.stats(),
+            })
+            .await;
+        match res {
+            Ok(Ok(stats)) => {
+                tracing::info!(urns = stats.len(), "computed stats");
+                self.success(super::stats::Response::from(stats).into())
+                    .await
+            },
+            Ok(Err(err)) => {
+                tracing::error!(err = %err, "failed to compute stats");
+                self.error(format!("unable to compute stats: {}", err))
+                    .await
+            },
+            Err(err) => {
+                tracing::error!(err = %err, "failed to obtain storage");
+                self.error("stats failed due to internal storage error".to_string())
+                    .await
+            },
+        }
+    }
+}
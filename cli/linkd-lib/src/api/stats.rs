@@ -0,0 +1,68 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use librad::git::{storage::stats, Urn};
+
+#[derive(Clone, Debug, PartialEq, Eq, minicbor::Decode, minicbor::Encode)]
+pub struct Request {
+    /// Only compute the statistics of this URN, instead of all identities in
+    /// the storage.
+    #[n(0)]
+    pub urn: Option<Urn>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, minicbor::Decode, minicbor::Encode)]
+pub struct Response {
+    #[n(0)]
+    pub urns: Vec<UrnStats>,
+}
+
+/// See [`librad::git::storage::stats::UrnStats`].
+#[derive(Clone, Debug, PartialEq, Eq, minicbor::Decode, minicbor::Encode)]
+#[cbor(map)]
+pub struct UrnStats {
+    #[n(0)]
+    pub urn: Urn,
+    #[n(1)]
+    pub refs: u64,
+    #[n(2)]
+    pub tracked: u64,
+    #[n(3)]
+    pub objects: u64,
+    #[n(4)]
+    pub size: u64,
+    /// Seconds since the epoch.
+    #[n(5)]
+    pub updated: Option<i64>,
+    /// Number of collaborative objects by type name.
+    #[n(6)]
+    pub cobs: BTreeMap<String, u64>,
+}
+
+impl From<stats::UrnStats> for UrnStats {
+    fn from(s: stats::UrnStats) -> Self {
+        Self {
+            urn: s.urn,
+            refs: s.refs as u64,
+            tracked: s.tracked as u64,
+            objects: s.objects as u64,
+            size: s.size,
+            updated: s.updated,
+            cobs: s
+                .cobs
+                .into_iter()
+                .map(|(typename, count)| (typename.to_string(), count as u64))
+                .collect(),
+        }
+    }
+}
+
+impl From<Vec<stats::UrnStats>> for Response {
+    fn from(stats: Vec<stats::UrnStats>) -> Self {
+        Self {
+            urns: stats.into_iter().map(UrnStats::from).collect(),
+        }
+    }
+}
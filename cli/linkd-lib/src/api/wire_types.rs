@@ -31,6 +31,7 @@
 
 use super::messages;
 
+pub mod events;
 pub mod request;
 pub use request::Request;
 pub mod response;
@@ -0,0 +1,218 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Messages exchanged over the events socket.
+//!
+//! The framing is the same as for the RPC protocol, except that messages never
+//! carry a payload. A client sends a single [`Subscribe`] message, after which
+//! the node streams [`Event`] messages matching the subscription until either
+//! side closes the connection.
+
+use librad::{
+    git::Urn,
+    net::{
+        peer::{subscription, ProtocolEvent},
+        protocol::{
+            cache,
+            event::upstream::{Caches, Endpoint, Gossip},
+            gossip,
+            membership::Transition,
+        },
+    },
+    PeerId,
+};
+
+use super::{messages, Message};
+
+pub type SubscribeMessage = Message<Subscribe>;
+pub type EventMessage = Message<Event>;
+
+/// Subscription request.
+///
+/// Fields which are `None` do not constrain the subscription. See
+/// [`subscription::Filter`] for the semantics of the constraints.
+#[derive(Clone, Debug, Default, PartialEq, Eq, minicbor::Encode, minicbor::Decode)]
+#[cbor(map)]
+pub struct Subscribe {
+    #[n(0)]
+    pub user_agent: Option<messages::UserAgent>,
+    #[n(1)]
+    pub urns: Option<Vec<Urn>>,
+    #[n(2)]
+    pub kinds: Option<Vec<Kind>>,
+    #[n(3)]
+    pub peers: Option<Vec<PeerId>>,
+}
+
+impl From<Subscribe> for subscription::Filter {
+    fn from(sub: Subscribe) -> Self {
+        let mut filter = subscription::Filter::default();
+        for urn in sub.urns.into_iter().flatten() {
+            filter = filter.urn(urn);
+        }
+        if let Some(kinds) = sub.kinds {
+            // Unknown kinds match nothing, but a set of kinds was still asked
+            // for: if it consists only of unknown kinds, nothing matches.
+            filter = filter.kinds(kinds.into_iter().filter_map(|kind| match kind {
+                Kind::Endpoint => Some(subscription::Kind::Endpoint),
+                Kind::Gossip => Some(subscription::Kind::Gossip),
+                Kind::Membership => Some(subscription::Kind::Membership),
+                Kind::Caches => Some(subscription::Kind::Caches),
+                Kind::Unknown(other) => {
+                    tracing::warn!(kind = other, "unknown event kind");
+                    None
+                },
+            }))
+        }
+        for peer in sub.peers.into_iter().flatten() {
+            filter = filter.peer(peer);
+        }
+        filter
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    // CBOR encode and decode maps to 1
+    Endpoint,
+    // CBOR encode and decode maps to 2
+    Gossip,
+    // CBOR encode and decode maps to 3
+    Membership,
+    // CBOR encode and decode maps to 4
+    Caches,
+    Unknown(u8),
+}
+
+impl minicbor::Encode for Kind {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        let val = match self {
+            Self::Endpoint => 1,
+            Self::Gossip => 2,
+            Self::Membership => 3,
+            Self::Caches => 4,
+            Self::Unknown(other) => *other,
+        };
+        e.u8(val)?;
+        Ok(())
+    }
+}
+
+impl<'b> minicbor::Decode<'b> for Kind {
+    fn decode(d: &mut minicbor::Decoder<'b>) -> Result<Self, minicbor::decode::Error> {
+        Ok(match d.u8()? {
+            1 => Self::Endpoint,
+            2 => Self::Gossip,
+            3 => Self::Membership,
+            4 => Self::Caches,
+            other => Self::Unknown(other),
+        })
+    }
+}
+
+/// An event streamed to subscribers.
+#[derive(Clone, Debug, PartialEq, Eq, minicbor::Encode, minicbor::Decode)]
+pub enum Event {
+    /// The subscriber fell behind, and `dropped` events were lost.
+    #[n(0)]
+    #[cbor(array)]
+    Lagged {
+        #[n(0)]
+        dropped: u64,
+    },
+
+    #[n(1)]
+    EndpointUp,
+
+    #[n(2)]
+    EndpointDown,
+
+    /// A `Have` announced by `provider` was applied to local storage.
+    #[n(3)]
+    #[cbor(array)]
+    Gossip {
+        #[n(0)]
+        provider: PeerId,
+        #[n(1)]
+        payload: gossip::Payload,
+    },
+
+    #[n(4)]
+    #[cbor(array)]
+    Promoted {
+        #[n(0)]
+        peer: PeerId,
+    },
+
+    #[n(5)]
+    #[cbor(array)]
+    Demoted {
+        #[n(0)]
+        peer: PeerId,
+    },
+
+    #[n(6)]
+    #[cbor(array)]
+    Evicted {
+        #[n(0)]
+        peer: PeerId,
+    },
+
+    #[n(7)]
+    #[cbor(array)]
+    UrnCacheRebuilt {
+        #[n(0)]
+        len: u64,
+    },
+}
+
+impl Event {
+    /// Convert a [`subscription::Event`] to its wire representation.
+    ///
+    /// Returns `None` for events which have no wire representation.
+    pub fn from_subscription(event: subscription::Event) -> Option<Self> {
+        match event {
+            subscription::Event::Lagged { dropped } => Some(Self::Lagged { dropped }),
+            subscription::Event::Protocol(event) => match event {
+                ProtocolEvent::Endpoint(Endpoint::Up { .. }) => Some(Self::EndpointUp),
+                ProtocolEvent::Endpoint(Endpoint::Down) => Some(Self::EndpointDown),
+                ProtocolEvent::Gossip(gossip) => match *gossip {
+                    Gossip::Put {
+                        provider, payload, ..
+                    } => Some(Self::Gossip {
+                        provider: provider.peer_id,
+                        payload,
+                    }),
+                },
+                ProtocolEvent::Membership(Transition::Promoted(info)) => {
+                    Some(Self::Promoted { peer: info.peer_id })
+                },
+                ProtocolEvent::Membership(Transition::Demoted(info)) => {
+                    Some(Self::Demoted { peer: info.peer_id })
+                },
+                ProtocolEvent::Membership(Transition::Evicted(info)) => {
+                    Some(Self::Evicted { peer: info.peer_id })
+                },
+                ProtocolEvent::Caches(Caches::Urns(cache::urns::Event::Rebuilt {
+                    len_new,
+                    ..
+                })) => Some(Self::UrnCacheRebuilt {
+                    len: len_new as u64,
+                }),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl From<Event> for EventMessage {
+    fn from(event: Event) -> Self {
+        Message {
+            headers: event,
+            payload: None,
+        }
+    }
+}
@@ -44,6 +44,12 @@ impl From<messages::Request> for Request {
             messages::RequestPayload::RequestPull(request_pull) => {
                 (minicbor::to_vec(request_pull).unwrap(), Kind::RequestPull)
             },
+            messages::RequestPayload::Purge(purge) => {
+                (minicbor::to_vec(purge).unwrap(), Kind::Purge)
+            },
+            messages::RequestPayload::Stats(stats) => {
+                (minicbor::to_vec(stats).unwrap(), Kind::Stats)
+            },
         };
         Request {
             headers: Headers {
@@ -78,6 +84,8 @@ impl TryFrom<Request> for messages::Request {
             Kind::RequestPull => {
                 messages::RequestPayload::RequestPull(minicbor::decode(&payload_bytes)?)
             },
+            Kind::Purge => messages::RequestPayload::Purge(minicbor::decode(&payload_bytes)?),
+            Kind::Stats => messages::RequestPayload::Stats(minicbor::decode(&payload_bytes)?),
             Kind::Unknown(other) => return Err(DecodeError::UnknownRequestKind(other)),
         };
         Ok(messages::Request {
@@ -96,6 +104,10 @@ pub enum Kind {
     Announce,
     // CBOR encode and decode maps to 5
     RequestPull,
+    // CBOR encode and decode maps to 6
+    Purge,
+    // CBOR encode and decode maps to 7
+    Stats,
     Unknown(u8),
 }
 
@@ -118,6 +130,8 @@ impl minicbor::Encode for Kind {
         let val = match self {
             Self::Announce => 1,
             Self::RequestPull => 5,
+            Self::Purge => 6,
+            Self::Stats => 7,
             Self::Unknown(other) => *other,
         };
         e.u8(val)?;
@@ -130,6 +144,8 @@ impl<'b> minicbor::Decode<'b> for Kind {
         Ok(match d.u8()? {
             1 => Self::Announce,
             5 => Self::RequestPull,
+            6 => Self::Purge,
+            7 => Self::Stats,
             other => Self::Unknown(other),
         })
     }
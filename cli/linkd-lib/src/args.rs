@@ -63,6 +63,9 @@ pub struct Args {
     #[clap(flatten)]
     pub protocol: ProtocolArgs,
 
+    #[clap(flatten)]
+    pub bandwidth: BandwidthArgs,
+
     /// Forces the creation of a temporary root for the local state, should be
     /// used for debug and testing only.
     #[clap(long)]
@@ -74,6 +77,12 @@ pub struct Args {
     #[clap(flatten)]
     pub request_pull: RequestPullStorage,
 
+    #[clap(flatten)]
+    pub maintenance: MaintenanceArgs,
+
+    #[clap(flatten)]
+    pub storage_quota: StorageQuotaArgs,
+
     /// The number of milliseconds to wait after losing all connections before
     /// shutting down the node. If not specified the node will never
     /// shutdown.
@@ -237,6 +246,11 @@ pub struct MetricsArgs {
         required_if_eq("metrics-provider", "graphite")
     )]
     pub graphite_addr: String,
+
+    /// Where to serve Prometheus metrics from. Either a socket address, or a
+    /// path to a unix domain socket prefixed with `unix:`.
+    #[clap(long, default_value = "127.0.0.1:9435")]
+    pub prometheus_listen: String,
 }
 
 impl Default for MetricsArgs {
@@ -244,6 +258,7 @@ impl Default for MetricsArgs {
         Self {
             provider: None,
             graphite_addr: "localhost:2003".to_string(),
+            prometheus_listen: "127.0.0.1:9435".to_string(),
         }
     }
 }
@@ -251,6 +266,7 @@ impl Default for MetricsArgs {
 #[derive(Debug, Eq, PartialEq, Parser)]
 pub enum MetricsProvider {
     Graphite,
+    Prometheus,
 }
 
 impl FromStr for MetricsProvider {
@@ -259,6 +275,7 @@ impl FromStr for MetricsProvider {
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         match input {
             "graphite" => Ok(Self::Graphite),
+            "prometheus" => Ok(Self::Prometheus),
             _ => Err(format!("unsupported key source `{}`", input)),
         }
     }
@@ -281,9 +298,86 @@ pub struct ProtocolArgs {
         parse(try_from_str = parse_protocol_network))
     ]
     pub network: Network,
+
+    /// The number of seconds to wait for in-flight replications and
+    /// request-pulls to complete when shutting down.
+    #[clap(
+        long = "protocol-drain-timeout",
+        name = "protocol-drain-timeout",
+        default_value_t
+    )]
+    pub drain_timeout: DrainTimeout,
     // TODO(xla): Expose protocol args (membership, replication, etc.).
 }
 
+/// Limits on the bytes transferred when serving git fetches and
+/// request-pulls. A peer, URN or the node as a whole exceeding a quota is
+/// refused further transfers until the respective window has elapsed.
+#[derive(Debug, Default, Eq, PartialEq, Parser)]
+pub struct BandwidthArgs {
+    /// Maximum number of bytes per day across all peers.
+    #[clap(long = "bandwidth-global-daily", name = "bandwidth-global-daily")]
+    pub global_daily: Option<u64>,
+
+    /// Maximum number of bytes per minute across all peers.
+    #[clap(long = "bandwidth-global-burst", name = "bandwidth-global-burst")]
+    pub global_burst: Option<u64>,
+
+    /// Maximum number of bytes per day for any single peer.
+    #[clap(long = "bandwidth-peer-daily", name = "bandwidth-peer-daily")]
+    pub peer_daily: Option<u64>,
+
+    /// Maximum number of bytes per minute for any single peer.
+    #[clap(long = "bandwidth-peer-burst", name = "bandwidth-peer-burst")]
+    pub peer_burst: Option<u64>,
+
+    /// Maximum number of bytes per day for any single URN.
+    #[clap(long = "bandwidth-urn-daily", name = "bandwidth-urn-daily")]
+    pub urn_daily: Option<u64>,
+
+    /// Maximum number of bytes per minute for any single URN.
+    #[clap(long = "bandwidth-urn-burst", name = "bandwidth-urn-burst")]
+    pub urn_burst: Option<u64>,
+}
+
+/// Periodic housekeeping of the storage, cf.
+/// [`librad::git::storage::maintenance`]. Replication is suspended while
+/// maintenance is running.
+#[derive(Debug, Default, Eq, PartialEq, Parser)]
+pub struct MaintenanceArgs {
+    /// The number of seconds between runs of the storage maintenance tasks. If
+    /// not specified, no maintenance is scheduled.
+    #[clap(long = "maintenance-interval", name = "maintenance-interval")]
+    pub interval: Option<u64>,
+
+    /// Fully repack the storage, in addition to running `git gc`.
+    #[clap(long = "maintenance-repack")]
+    pub repack: bool,
+
+    /// Do not configure delta islands per namespace when writing packfiles.
+    #[clap(long = "maintenance-no-delta-islands")]
+    pub no_delta_islands: bool,
+}
+
+/// Limits on the storage space occupied by replicated data. Fetches are
+/// refused for a URN exceeding its quota, while remotes exceeding their quota
+/// are skipped. Archived projects can be untracked after a grace period.
+#[derive(Debug, Default, Eq, PartialEq, Parser)]
+pub struct StorageQuotaArgs {
+    /// Maximum number of bytes stored for any single URN.
+    #[clap(long = "storage-quota-urn", name = "storage-quota-urn")]
+    pub urn: Option<u64>,
+
+    /// Maximum number of bytes stored for any single remote peer of a URN.
+    #[clap(long = "storage-quota-remote", name = "storage-quota-remote")]
+    pub remote: Option<u64>,
+
+    /// Number of seconds after which an archived project is no longer
+    /// tracked. If not specified archived projects remain tracked.
+    #[clap(long)]
+    pub archived_grace_period: Option<u64>,
+}
+
 #[derive(Debug, Eq, PartialEq, Parser)]
 pub enum ProtocolListen {
     Any,
@@ -312,6 +406,39 @@ impl FromStr for ProtocolListen {
     }
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct DrainTimeout(Duration);
+
+impl Default for DrainTimeout {
+    fn default() -> Self {
+        Self(Duration::from_secs(30))
+    }
+}
+
+impl From<&DrainTimeout> for Duration {
+    fn from(d: &DrainTimeout) -> Self {
+        d.0
+    }
+}
+
+impl fmt::Display for DrainTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.as_secs())
+    }
+}
+
+impl FromStr for DrainTimeout {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let integer: Result<u64, _> = s.parse();
+        match integer {
+            Ok(i) => Ok(DrainTimeout(Duration::from_secs(i))),
+            Err(_) => Err("expected a positive integer"),
+        }
+    }
+}
+
 fn parse_protocol_network(src: &str) -> Result<Network, String> {
     match src {
         _main if src.to_lowercase() == "main" => Ok(Network::Main),
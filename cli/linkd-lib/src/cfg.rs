@@ -7,6 +7,7 @@ use std::{
     convert::TryFrom,
     io,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs as _},
+    path::PathBuf,
     time::Duration,
 };
 
@@ -29,7 +30,7 @@ use librad::{
 };
 use lnk_clib::keys;
 
-use crate::{args, request_pull, tracking::Tracker};
+use crate::{args, metrics::prometheus, request_pull, tracking::Tracker};
 
 use lnk_clib::seed::{self, store::FileStore, Seeds};
 
@@ -95,7 +96,9 @@ pub struct Cfg<Disco, Signer, Auth> {
     pub metrics: Option<Metrics>,
     pub peer: PeerConfig<Signer, Auth>,
     pub tracker: Option<Tracker>,
+    pub maintenance: Option<Maintenance>,
     pub run_mode: RunMode,
+    pub drain_timeout: Duration,
     pub profile: Profile,
 }
 
@@ -150,6 +153,18 @@ impl Cfg<discovery::Static, BoxedSigner, request_pull::State> {
                     .next()
                     .unwrap(),
             )),
+            Some(args::MetricsProvider::Prometheus) => Some(Metrics::Prometheus(
+                match args.metrics.prometheus_listen.strip_prefix("unix:") {
+                    Some(path) => prometheus::Listen::Unix(PathBuf::from(path)),
+                    None => prometheus::Listen::Tcp(
+                        args.metrics
+                            .prometheus_listen
+                            .to_socket_addrs()?
+                            .next()
+                            .unwrap(),
+                    ),
+                },
+            )),
             None => None,
         };
 
@@ -167,6 +182,11 @@ impl Cfg<discovery::Static, BoxedSigner, request_pull::State> {
             ),
         });
 
+        let maintenance = args.maintenance.interval.map(|secs| Maintenance {
+            interval: Duration::from_secs(secs),
+            config: maintenance_config(&args.maintenance),
+        });
+
         let storage_lock = storage::pool::Initialised::no();
         let request_pull = request_pull::State::new(
             storage::Pool::new(
@@ -191,21 +211,81 @@ impl Cfg<discovery::Static, BoxedSigner, request_pull::State> {
                     advertised_addrs: None,
                     membership,
                     network: args.protocol.network.clone(),
-                    replication: Default::default(),
-                    rate_limits: Default::default(),
+                    replication: replication_config(&args.storage_quota),
+                    rate_limits: net::protocol::Quota {
+                        bandwidth: bandwidth_quota(&args.bandwidth),
+                        ..Default::default()
+                    },
                     request_pull,
                 },
                 storage: Default::default(),
             },
             tracker,
+            maintenance,
             profile,
             run_mode,
+            drain_timeout: (&args.protocol.drain_timeout).into(),
         })
     }
 }
 
+fn bandwidth_quota(args: &args::BandwidthArgs) -> net::protocol::bandwidth::Quota {
+    use librad::net::protocol::bandwidth::{Limit, Quota};
+
+    Quota {
+        global: Limit {
+            daily: args.global_daily,
+            burst: args.global_burst,
+        },
+        per_peer: Limit {
+            daily: args.peer_daily,
+            burst: args.peer_burst,
+        },
+        per_urn: Limit {
+            daily: args.urn_daily,
+            burst: args.urn_burst,
+        },
+        ..Quota::default()
+    }
+}
+
+fn replication_config(args: &args::StorageQuotaArgs) -> net::replication::Config {
+    use librad::net::replication::{Config, FetchLimit, Quota};
+
+    Config {
+        limit: FetchLimit {
+            quota: Quota {
+                urn: args.urn,
+                remote: args.remote,
+            },
+            ..FetchLimit::default()
+        },
+        archived_grace_period: args.archived_grace_period.map(Duration::from_secs),
+        ..Config::default()
+    }
+}
+
+fn maintenance_config(args: &args::MaintenanceArgs) -> storage::maintenance::Config {
+    use librad::git::storage::maintenance::{Config, Task};
+
+    let mut config = Config {
+        delta_islands: !args.no_delta_islands,
+        ..Config::default()
+    };
+    if args.repack {
+        config.tasks.insert(1, Task::Repack);
+    }
+    config
+}
+
+pub struct Maintenance {
+    pub interval: Duration,
+    pub config: storage::maintenance::Config,
+}
+
 pub enum Metrics {
     Graphite(SocketAddr),
+    Prometheus(prometheus::Listen),
 }
 
 impl TryFrom<&args::Args> for Profile {
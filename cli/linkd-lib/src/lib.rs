@@ -9,9 +9,10 @@ mod cfg;
 
 pub mod api;
 mod logging;
+mod maintenance;
 mod metrics;
 pub mod node;
-mod protocol;
+pub mod protocol;
 pub mod request_pull;
 mod signals;
 pub mod tracking;
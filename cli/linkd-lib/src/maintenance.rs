@@ -0,0 +1,40 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::time::Duration;
+
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use tracing::{error, info, instrument};
+
+use librad::{git::storage::maintenance::Config, net::peer::Peer, Signer};
+
+use crate::request_pull;
+
+/// Run storage maintenance every `interval`, starting one `interval` after
+/// the node was started.
+///
+/// Failures are logged, and maintenance is re-attempted on the next tick.
+#[instrument(name = "maintenance subroutine", skip(peer, config))]
+pub async fn routine<S>(
+    peer: Peer<S, request_pull::State>,
+    interval: Duration,
+    config: Config,
+) -> anyhow::Result<()>
+where
+    S: Signer + Clone,
+{
+    let mut ticks = interval_at(Instant::now() + interval, interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        let request_pull = peer.protocol_config().request_pull.storage();
+        match peer.maintain(config.clone(), &[request_pull]).await {
+            Ok(report) => {
+                for (task, elapsed) in report.tasks {
+                    info!(%task, ?elapsed, "storage maintenance task completed")
+                }
+            },
+            Err(e) => error!(err = %e, "storage maintenance failed"),
+        }
+    }
+}
@@ -20,6 +20,16 @@ const CONNECTIONS_TOTAL: &str = "connections_total";
 const CONNECTED_PEERS: &str = "connected_peers";
 const MEMBERSHIP_ACTIVE: &str = "membership_active";
 const MEMBERSHIP_PASSIVE: &str = "membership_passive";
+const BANDWIDTH_TOTAL: &str = "bandwidth_total_bytes";
+const BANDWIDTH_DAILY: &str = "bandwidth_daily_bytes";
+const BANDWIDTH_BURST: &str = "bandwidth_burst_bytes";
+const REPLICATION_PHASE_COUNT: &str = "replication_phase_count";
+const REPLICATION_PHASE_FAILURES: &str = "replication_phase_failures";
+const REPLICATION_PHASE_SECONDS: &str = "replication_phase_seconds";
+const REPLICATION_PHASE_BYTES: &str = "replication_phase_bytes";
+const REPLICATION_REFS_UPDATED: &str = "replication_refs_updated";
+const REPLICATION_REFS_REJECTED: &str = "replication_refs_rejected";
+const REPLICATION_REJECTIONS: &str = "replication_rejections";
 
 #[instrument(name = "graphite subroutine", skip(peer))]
 pub async fn routine<S, G>(peer: Peer<S, G>, graphite_addr: SocketAddr) -> anyhow::Result<()>
@@ -50,6 +60,82 @@ where
             sock.send(line(peer_id.clone(), metric, *value as f32, now).as_bytes())
                 .await?;
         }
+
+        let bandwidth = &stats.bandwidth;
+        for (metric, value) in &[
+            (BANDWIDTH_TOTAL, bandwidth.global.total),
+            (BANDWIDTH_DAILY, bandwidth.global.daily),
+            (BANDWIDTH_BURST, bandwidth.global.burst),
+        ] {
+            sock.send(line(peer_id.clone(), metric, *value as f32, now).as_bytes())
+                .await?;
+        }
+        for (remote, usage) in &bandwidth.peers {
+            let remote = remote.to_string();
+            for (metric, value) in &[
+                (BANDWIDTH_TOTAL, usage.total),
+                (BANDWIDTH_DAILY, usage.daily),
+                (BANDWIDTH_BURST, usage.burst),
+            ] {
+                sock.send(
+                    tagged_line(&peer_id, ("remote", &remote), metric, *value as f32, now)
+                        .as_bytes(),
+                )
+                .await?;
+            }
+        }
+        for (urn, usage) in &bandwidth.urns {
+            let urn = urn.to_string();
+            for (metric, value) in &[
+                (BANDWIDTH_TOTAL, usage.total),
+                (BANDWIDTH_DAILY, usage.daily),
+                (BANDWIDTH_BURST, usage.burst),
+            ] {
+                sock.send(
+                    tagged_line(&peer_id, ("urn", &urn), metric, *value as f32, now).as_bytes(),
+                )
+                .await?;
+            }
+        }
+
+        let repl = peer.replication_stats();
+        for (metric, value) in &[
+            (REPLICATION_REFS_UPDATED, repl.refs_updated),
+            (REPLICATION_REFS_REJECTED, repl.refs_rejected),
+        ] {
+            sock.send(line(peer_id.clone(), metric, *value as f32, now).as_bytes())
+                .await?;
+        }
+        for (phase, phase_stats) in &repl.phases {
+            for (metric, value) in &[
+                (REPLICATION_PHASE_COUNT, phase_stats.count as f32),
+                (REPLICATION_PHASE_FAILURES, phase_stats.failures as f32),
+                (
+                    REPLICATION_PHASE_SECONDS,
+                    phase_stats.duration_sum.as_secs_f32(),
+                ),
+                (REPLICATION_PHASE_BYTES, phase_stats.bytes_received as f32),
+            ] {
+                sock.send(
+                    tagged_line(&peer_id, ("phase", phase.as_str()), metric, *value, now)
+                        .as_bytes(),
+                )
+                .await?;
+            }
+        }
+        for (reason, count) in &repl.rejections {
+            sock.send(
+                tagged_line(
+                    &peer_id,
+                    ("reason", reason.as_str()),
+                    REPLICATION_REJECTIONS,
+                    *count as f32,
+                    now,
+                )
+                .as_bytes(),
+            )
+            .await?;
+        }
     }
 }
 
@@ -62,3 +148,21 @@ fn line(peer_id: String, metric: &str, value: f32, time: Duration) -> String {
         time.as_secs()
     )
 }
+
+fn tagged_line(
+    peer_id: &str,
+    (tag, tag_value): (&str, &str),
+    metric: &str,
+    value: f32,
+    time: Duration,
+) -> String {
+    format!(
+        "linkd_{};peer={};{}={} {:?} {}",
+        metric,
+        peer_id,
+        tag,
+        tag_value,
+        value,
+        time.as_secs()
+    )
+}
@@ -0,0 +1,440 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Serve metrics in the [Prometheus text format][fmt].
+//!
+//! Only the bare minimum of HTTP is spoken: any `GET /metrics` request is
+//! answered with the current metrics, anything else with a 404.
+//!
+//! [fmt]: https://prometheus.io/docs/instrumenting/exposition_formats/
+
+use std::{
+    fmt::{self, Write as _},
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncRead, AsyncWrite, AsyncWriteExt as _, BufReader},
+    net::{TcpListener, UnixListener},
+    time,
+};
+use tracing::{info, instrument, warn};
+
+use librad::{
+    net::{
+        peer::{event::downstream::Stats, Peer},
+        protocol::RequestPullGuard,
+        replication,
+    },
+    Signer,
+};
+
+/// Time allotted to serving a single scrape.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum Listen {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+#[instrument(name = "prometheus subroutine", skip(peer))]
+pub async fn routine<S, G>(peer: Peer<S, G>, listen: Listen) -> anyhow::Result<()>
+where
+    S: Signer + Clone,
+    G: RequestPullGuard,
+{
+    info!("starting prometheus exporter");
+
+    match listen {
+        Listen::Tcp(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            info!(addr = %listener.local_addr()?, "serving metrics");
+            loop {
+                let (stream, _) = listener.accept().await?;
+                scrape(&peer, stream).await
+            }
+        },
+        Listen::Unix(path) => {
+            remove_stale_socket(&path)?;
+            let listener = UnixListener::bind(&path)?;
+            info!(path = %path.display(), "serving metrics");
+            loop {
+                let (stream, _) = listener.accept().await?;
+                scrape(&peer, stream).await
+            }
+        },
+    }
+}
+
+/// Remove a socket left behind at `path` by a previous run, which would
+/// otherwise make binding fail with `EADDRINUSE`.
+///
+/// Anything other than a socket is left alone.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt as _;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => {
+            info!(path = %path.display(), "removing stale socket");
+            std::fs::remove_file(path)
+        },
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+async fn scrape<S, G, T>(peer: &Peer<S, G>, stream: T)
+where
+    S: Signer + Clone,
+    G: RequestPullGuard,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    match time::timeout(SCRAPE_TIMEOUT, respond(peer, stream)).await {
+        Err(_) => warn!("timed out serving metrics"),
+        Ok(Err(e)) => warn!(err = ?e, "error serving metrics"),
+        Ok(Ok(())) => {},
+    }
+}
+
+async fn respond<S, G, T>(peer: &Peer<S, G>, stream: T) -> anyhow::Result<()>
+where
+    S: Signer + Clone,
+    G: RequestPullGuard,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut stream = BufReader::new(stream);
+
+    let mut request = String::new();
+    stream.read_line(&mut request).await?;
+    // Skip the headers, we don't care
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let response = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+        ["GET", "/metrics"] => {
+            let body = render(&peer.stats().await, &peer.replication_stats())?;
+            format!(
+                "HTTP/1.1 200 OK\r\n\
+                 Content-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        },
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_owned(),
+    };
+
+    let stream = stream.get_mut();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+fn render(stats: &Stats, repl: &replication::Stats) -> Result<String, fmt::Error> {
+    let mut out = Exposition::default();
+
+    out.metric("connections", "gauge", "Number of open connections.")?;
+    out.sample("connections", &[], stats.connections_total)?;
+    out.metric("connected_peers", "gauge", "Number of connected peers.")?;
+    out.sample("connected_peers", &[], stats.connected_peers.len())?;
+    out.metric("membership", "gauge", "Size of the membership views.")?;
+    out.sample("membership", &[("view", "active")], stats.membership_active)?;
+    out.sample(
+        "membership",
+        &[("view", "passive")],
+        stats.membership_passive,
+    )?;
+
+    out.metric(
+        "urn_cache_elements",
+        "gauge",
+        "Number of URNs in the cache.",
+    )?;
+    out.sample("urn_cache_elements", &[], stats.caches.urns.elements)?;
+
+    let broadcast = &stats.broadcast;
+    out.metric(
+        "broadcast_messages_total",
+        "counter",
+        "Gossip messages received.",
+    )?;
+    out.sample("broadcast_messages_total", &[], broadcast.messages)?;
+    out.metric(
+        "broadcast_seen_total",
+        "counter",
+        "Gossip messages received which had been seen before.",
+    )?;
+    out.sample("broadcast_seen_total", &[], broadcast.seen)?;
+    out.metric(
+        "broadcast_legacy_total",
+        "counter",
+        "Gossip messages received without a hop count.",
+    )?;
+    out.sample("broadcast_legacy_total", &[], broadcast.legacy)?;
+    out.metric(
+        "broadcast_hops_total",
+        "counter",
+        "Sum of the hop counts of gossip messages received.",
+    )?;
+    out.sample("broadcast_hops_total", &[], broadcast.hops)?;
+
+    let bandwidth = &stats.bandwidth;
+    out.metric(
+        "bandwidth_global_bytes_total",
+        "counter",
+        "Bytes transferred serving git and request-pull streams.",
+    )?;
+    out.sample("bandwidth_global_bytes_total", &[], bandwidth.global.total)?;
+    out.metric(
+        "bandwidth_bytes_total",
+        "counter",
+        "Bytes transferred serving git and request-pull streams, by remote.",
+    )?;
+    for (remote, usage) in &bandwidth.peers {
+        out.sample(
+            "bandwidth_bytes_total",
+            &[("remote", &remote.to_string())],
+            usage.total,
+        )?;
+    }
+    out.metric(
+        "bandwidth_urn_bytes_total",
+        "counter",
+        "Bytes transferred serving git and request-pull streams, by URN.",
+    )?;
+    for (urn, usage) in &bandwidth.urns {
+        out.sample(
+            "bandwidth_urn_bytes_total",
+            &[("urn", &urn.to_string())],
+            usage.total,
+        )?;
+    }
+    out.metric(
+        "bandwidth_global_daily_bytes",
+        "gauge",
+        "Bytes transferred in the current daily quota window.",
+    )?;
+    out.sample("bandwidth_global_daily_bytes", &[], bandwidth.global.daily)?;
+    out.metric(
+        "bandwidth_daily_bytes",
+        "gauge",
+        "Bytes transferred in the current daily quota window, by remote.",
+    )?;
+    for (remote, usage) in &bandwidth.peers {
+        out.sample(
+            "bandwidth_daily_bytes",
+            &[("remote", &remote.to_string())],
+            usage.daily,
+        )?;
+    }
+    out.metric(
+        "bandwidth_urn_daily_bytes",
+        "gauge",
+        "Bytes transferred in the current daily quota window, by URN.",
+    )?;
+    for (urn, usage) in &bandwidth.urns {
+        out.sample(
+            "bandwidth_urn_daily_bytes",
+            &[("urn", &urn.to_string())],
+            usage.daily,
+        )?;
+    }
+
+    out.metric("replications_total", "counter", "Replications by outcome.")?;
+    out.sample(
+        "replications_total",
+        &[("outcome", "success")],
+        repl.succeeded,
+    )?;
+    out.sample("replications_total", &[("outcome", "failure")], repl.failed)?;
+    out.sample(
+        "replications_total",
+        &[("outcome", "slot_timeout")],
+        repl.slot_timeouts,
+    )?;
+    out.metric(
+        "replication_duration_seconds",
+        "histogram",
+        "Time spent replicating.",
+    )?;
+    for (bound, count) in replication::DURATION_BUCKETS
+        .iter()
+        .zip(&repl.duration_buckets)
+    {
+        out.sample(
+            "replication_duration_seconds_bucket",
+            &[("le", &bound.to_string())],
+            count,
+        )?;
+    }
+    let count = repl.succeeded + repl.failed;
+    out.sample(
+        "replication_duration_seconds_bucket",
+        &[("le", "+Inf")],
+        count,
+    )?;
+    out.sample(
+        "replication_duration_seconds_sum",
+        &[],
+        repl.duration_sum.as_secs_f64(),
+    )?;
+    out.sample("replication_duration_seconds_count", &[], count)?;
+
+    out.metric(
+        "replication_phase_duration_seconds",
+        "summary",
+        "Time spent in the phases of replications.",
+    )?;
+    for (phase, stats) in &repl.phases {
+        out.sample(
+            "replication_phase_duration_seconds_sum",
+            &[("phase", phase.as_str())],
+            stats.duration_sum.as_secs_f64(),
+        )?;
+        out.sample(
+            "replication_phase_duration_seconds_count",
+            &[("phase", phase.as_str())],
+            stats.count,
+        )?;
+    }
+    out.metric(
+        "replication_phase_failures_total",
+        "counter",
+        "Replications aborted, by the phase which failed.",
+    )?;
+    for (phase, stats) in &repl.phases {
+        out.sample(
+            "replication_phase_failures_total",
+            &[("phase", phase.as_str())],
+            stats.failures,
+        )?;
+    }
+    out.metric(
+        "replication_phase_received_bytes_total",
+        "counter",
+        "Bytes received during the phases of replications.",
+    )?;
+    for (phase, stats) in &repl.phases {
+        out.sample(
+            "replication_phase_received_bytes_total",
+            &[("phase", phase.as_str())],
+            stats.bytes_received,
+        )?;
+    }
+    out.metric(
+        "replication_refs_total",
+        "counter",
+        "Refs touched by successful replications, by result.",
+    )?;
+    out.sample(
+        "replication_refs_total",
+        &[("result", "updated")],
+        repl.refs_updated,
+    )?;
+    out.sample(
+        "replication_refs_total",
+        &[("result", "rejected")],
+        repl.refs_rejected,
+    )?;
+    out.metric(
+        "replication_rejections_total",
+        "counter",
+        "Refs which did not end up in the desired state, by reason.",
+    )?;
+    for (reason, count) in &repl.rejections {
+        out.sample(
+            "replication_rejections_total",
+            &[("reason", reason.as_str())],
+            count,
+        )?;
+    }
+
+    let index = &repl.odb.index;
+    out.metric(
+        "odb_index_lookups_total",
+        "counter",
+        "Pack index lookups by result.",
+    )?;
+    out.sample("odb_index_lookups_total", &[("result", "hit")], index.hits)?;
+    out.sample(
+        "odb_index_lookups_total",
+        &[("result", "miss")],
+        index.misses,
+    )?;
+    out.metric(
+        "odb_index_pushes_total",
+        "counter",
+        "Pack indices added explicitly.",
+    )?;
+    out.sample("odb_index_pushes_total", &[], index.pushes)?;
+    out.metric("odb_index_reloads_total", "counter", "Pack index reloads.")?;
+    out.sample("odb_index_reloads_total", &[], index.reloads)?;
+    out.metric("odb_indices", "gauge", "Pack indices currently held.")?;
+    out.sample("odb_indices", &[], index.indices)?;
+
+    let window = &repl.odb.window;
+    out.metric(
+        "odb_pack_cache_lookups_total",
+        "counter",
+        "Pack data cache lookups by result.",
+    )?;
+    out.sample(
+        "odb_pack_cache_lookups_total",
+        &[("result", "hit")],
+        window.cache_hits,
+    )?;
+    out.sample(
+        "odb_pack_cache_lookups_total",
+        &[("result", "miss")],
+        window.cache_misses,
+    )?;
+    out.metric(
+        "odb_pack_loads_total",
+        "counter",
+        "Attempts to load a pack file from disk.",
+    )?;
+    out.sample("odb_pack_loads_total", &[], window.file_loads)?;
+    out.metric("odb_open_packs", "gauge", "Pack files currently held open.")?;
+    out.sample("odb_open_packs", &[], window.open_files)?;
+
+    Ok(out.0)
+}
+
+#[derive(Default)]
+struct Exposition(String);
+
+impl Exposition {
+    fn metric(&mut self, name: &str, kind: &str, help: &str) -> fmt::Result {
+        writeln!(self.0, "# HELP linkd_{} {}", name, help)?;
+        writeln!(self.0, "# TYPE linkd_{} {}", name, kind)
+    }
+
+    fn sample<V>(&mut self, name: &str, labels: &[(&str, &str)], value: V) -> fmt::Result
+    where
+        V: fmt::Display,
+    {
+        write!(self.0, "linkd_{}", name)?;
+        if !labels.is_empty() {
+            self.0.push('{');
+            for (i, (k, v)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.0.push(',');
+                }
+                write!(self.0, "{}=\"{}\"", k, v)?;
+            }
+            self.0.push('}');
+        }
+        writeln!(self.0, " {}", value)
+    }
+}
@@ -20,7 +20,8 @@ use crate::{
     args::Args,
     cfg::{self, Cfg, RunMode},
     logging,
-    metrics::graphite,
+    maintenance,
+    metrics::{graphite, prometheus},
     protocol,
     request_pull,
     signals,
@@ -43,14 +44,27 @@ pub async fn run() -> anyhow::Result<()> {
 
     let mut coalesced = FuturesUnordered::new();
     let peer = Peer::new(cfg.peer)?;
-    let peer_task = spawner
-        .spawn(protocol::routine(peer.clone(), cfg.disco, shutdown_rx))
+    let mut peer_task = spawner
+        .spawn(protocol::routine(
+            peer.clone(),
+            cfg.disco,
+            shutdown_rx,
+            cfg.drain_timeout,
+        ))
         .fuse();
-    coalesced.push(peer_task);
 
-    if let Some(cfg::Metrics::Graphite(addr)) = cfg.metrics {
-        let graphite_task = spawner.spawn(graphite::routine(peer.clone(), addr)).fuse();
-        coalesced.push(graphite_task);
+    match cfg.metrics {
+        Some(cfg::Metrics::Graphite(addr)) => {
+            let graphite_task = spawner.spawn(graphite::routine(peer.clone(), addr)).fuse();
+            coalesced.push(graphite_task);
+        },
+        Some(cfg::Metrics::Prometheus(listen)) => {
+            let prometheus_task = spawner
+                .spawn(prometheus::routine(peer.clone(), listen))
+                .fuse();
+            coalesced.push(prometheus_task);
+        },
+        None => {},
     }
 
     if let Some(tracker) = cfg.tracker {
@@ -60,6 +74,13 @@ pub async fn run() -> anyhow::Result<()> {
         coalesced.push(tracking_task);
     }
 
+    if let Some(cfg::Maintenance { interval, config }) = cfg.maintenance {
+        let maintenance_task = spawner
+            .spawn(maintenance::routine(peer.clone(), interval, config))
+            .fuse();
+        coalesced.push(maintenance_task);
+    }
+
     let timeout = match cfg.run_mode {
         RunMode::Mortal(t) => Some(t),
         RunMode::Immortal => None,
@@ -77,18 +98,38 @@ pub async fn run() -> anyhow::Result<()> {
     futures::pin_mut!(api_routine);
 
     info!("starting node");
-    futures::select! {
+    let drain = futures::select! {
         _ = api_routine => {
             tracing::info!("event loop shutdown");
+            false
+        },
+        res = peer_task => {
+            if let Err(e) = res {
+                if e.is_panic() {
+                    panic::resume_unwind(e.into_panic());
+                }
+            }
+            false
         },
-        res = coalesced.next() => {
-            if let Some(Err(e)) = res {
+        res = coalesced.select_next_some() => {
+            if let Err(e) = res {
                 if e.is_panic() {
                     panic::resume_unwind(e.into_panic());
                 }
             }
+            false
         },
-        _ = signals_task => {
+        _ = signals_task => true,
+    };
+    // The signal handler only initiates the shutdown, the protocol task drains
+    // in-flight work before it stops. Returning earlier would drop the runtime,
+    // and with it whatever is still in flight.
+    if drain {
+        info!("waiting for the protocol to drain");
+        if let Err(e) = peer_task.await {
+            if e.is_panic() {
+                panic::resume_unwind(e.into_panic());
+            }
         }
     }
 
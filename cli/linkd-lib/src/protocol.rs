@@ -7,7 +7,7 @@ use std::{net::SocketAddr, panic, time::Duration};
 
 use futures::{future::FutureExt as _, pin_mut, select};
 use tokio::{sync::mpsc, time::sleep};
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 
 use librad::{
     net::{self, discovery::Discovery, peer::Peer, protocol::RequestPullGuard},
@@ -19,6 +19,7 @@ pub async fn routine<D, S, G>(
     peer: Peer<S, G>,
     disco: D,
     mut shutdown_rx: mpsc::Receiver<()>,
+    drain_timeout: Duration,
 ) -> anyhow::Result<()>
 where
     D: Discovery<Addr = SocketAddr> + Clone + 'static,
@@ -37,6 +38,10 @@ where
 
                 let res = select! {
                     _ = shutdown => {
+                        info!(timeout = ?drain_timeout, "draining");
+                        if let Err(err) = peer.drain(drain_timeout).await {
+                            warn!(%err, "shutting down with in-flight tasks");
+                        }
                         stop();
                         run.await
                     }
@@ -29,6 +29,11 @@ impl State {
             tracker: tracker.into(),
         }
     }
+
+    /// The storage pool used for tracking.
+    pub fn storage(&self) -> &storage::Pool<storage::Storage> {
+        &self.storage
+    }
 }
 
 #[derive(Debug, Error)]
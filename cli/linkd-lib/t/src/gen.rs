@@ -8,7 +8,7 @@ use std::{fmt::Debug, net::SocketAddr};
 use librad_test::gen::protocol::gen_request_pull_success;
 use link_crypto_test::gen::gen_peer_id;
 use link_identities_test::gen::urn::{gen_oid, gen_urn};
-use linkd_lib::api::{announce, messages, request_pull};
+use linkd_lib::api::{announce, messages, purge, request_pull, stats};
 use proptest::{collection, prelude::*};
 use test_helpers::gen::std_net::gen_socket_addr;
 
@@ -42,12 +42,22 @@ pub fn request_pull(addrs: Vec<SocketAddr>) -> impl Strategy<Value = request_pul
     })
 }
 
+pub fn purge() -> impl Strategy<Value = purge::Request> {
+    gen_urn().prop_map(|urn| purge::Request { urn })
+}
+
+pub fn stats() -> impl Strategy<Value = stats::Request> {
+    proptest::option::of(gen_urn()).prop_map(|urn| stats::Request { urn })
+}
+
 pub fn request_payload() -> impl Strategy<Value = messages::RequestPayload> {
     prop_oneof![
         announce().prop_map(messages::RequestPayload::from),
         collection::vec(gen_socket_addr(), 1..3)
             .prop_flat_map(request_pull)
-            .prop_map(messages::RequestPayload::from)
+            .prop_map(messages::RequestPayload::from),
+        purge().prop_map(messages::RequestPayload::from),
+        stats().prop_map(messages::RequestPayload::from),
     ]
 }
 
@@ -102,3 +112,67 @@ pub fn request_pull_response() -> impl Strategy<Value = messages::Response<reque
             })
     })
 }
+
+prop_compose! {
+    pub fn purge_success()
+        (refs in any::<u64>(),
+         tracking in any::<u64>(),
+         cob_cache in any::<u64>(),
+         include in any::<bool>())
+        -> purge::Response {
+        purge::Response {
+            refs,
+            tracking,
+            cob_cache,
+            include,
+        }
+    }
+}
+
+pub fn purge_response() -> impl Strategy<Value = messages::Response<purge::Response>> {
+    request_id().prop_flat_map(move |id| {
+        (Just(id), purge_success().prop_flat_map(response_payload)).prop_map(
+            move |(request_id, payload)| messages::Response {
+                payload,
+                request_id,
+            },
+        )
+    })
+}
+
+prop_compose! {
+    pub fn urn_stats()
+        (urn in gen_urn(),
+         refs in any::<u64>(),
+         tracked in any::<u64>(),
+         objects in any::<u64>(),
+         size in any::<u64>(),
+         updated in any::<Option<i64>>(),
+         cobs in collection::btree_map(any::<String>(), any::<u64>(), 0..3))
+        -> stats::UrnStats {
+        stats::UrnStats {
+            urn,
+            refs,
+            tracked,
+            objects,
+            size,
+            updated,
+            cobs,
+        }
+    }
+}
+
+pub fn stats_success() -> impl Strategy<Value = stats::Response> {
+    collection::vec(urn_stats(), 0..3).prop_map(|urns| stats::Response { urns })
+}
+
+pub fn stats_response() -> impl Strategy<Value = messages::Response<stats::Response>> {
+    request_id().prop_flat_map(move |id| {
+        (Just(id), stats_success().prop_flat_map(response_payload)).prop_map(
+            move |(request_id, payload)| messages::Response {
+                payload,
+                request_id,
+            },
+        )
+    })
+}
@@ -5,4 +5,5 @@
 
 mod api;
 mod args;
+mod protocol;
 mod tracking;
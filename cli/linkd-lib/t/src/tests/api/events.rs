@@ -0,0 +1,34 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use librad::net::{peer::ProtocolEvent, protocol::event::upstream::Endpoint};
+use linkd_lib::api::wire_types::events::{Kind, Subscribe};
+
+fn endpoint_down() -> ProtocolEvent {
+    ProtocolEvent::Endpoint(Endpoint::Down)
+}
+
+fn matches(kinds: Vec<Kind>) -> bool {
+    let filter: librad::net::peer::subscription::Filter = Subscribe {
+        kinds: Some(kinds),
+        ..Subscribe::default()
+    }
+    .into();
+    filter.matches(&endpoint_down())
+}
+
+#[test]
+fn known_kind() {
+    assert!(matches(vec![Kind::Endpoint]));
+    assert!(!matches(vec![Kind::Gossip]));
+}
+
+#[test]
+fn unknown_kind_matches_nothing() {
+    assert!(!matches(vec![Kind::Unknown(42)]));
+}
+
+#[test]
+fn unknown_kind_ignored_among_known() {
+    assert!(matches(vec![Kind::Unknown(42), Kind::Endpoint]));
+}
@@ -6,7 +6,13 @@
 use linkd_lib::api::{io, io::Transport as _, messages};
 use proptest::{array::uniform3, prelude::*};
 
-use crate::gen::{announce_response, request, request_pull_response};
+use crate::gen::{
+    announce_response,
+    purge_response,
+    request,
+    request_pull_response,
+    stats_response,
+};
 
 proptest! {
     #[test]
@@ -36,6 +42,14 @@ proptest! {
     fn test_response_round_trip_request_pull(responses in uniform3(request_pull_response())) {
         test_response_round_trip(&responses)
     }
+    #[test]
+    fn test_response_round_trip_purge(responses in uniform3(purge_response())) {
+        test_response_round_trip(&responses)
+    }
+    #[test]
+    fn test_response_round_trip_stats(responses in uniform3(stats_response())) {
+        test_response_round_trip(&responses)
+    }
 }
 
 fn with_async_transport<
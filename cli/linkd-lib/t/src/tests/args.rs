@@ -20,12 +20,16 @@ use librad::{
 use linkd_lib::args::{
     self,
     Args,
+    BandwidthArgs,
+    DrainTimeout,
     KeyArgs,
+    MaintenanceArgs,
     MetricsArgs,
     MetricsProvider,
     ProtocolArgs,
     ProtocolListen,
     Signer,
+    StorageQuotaArgs,
     TrackingArgs,
     TrackingMode,
 };
@@ -109,6 +113,33 @@ fn metrics_graphite() -> Result<()> {
             metrics: MetricsArgs {
                 provider: Some(MetricsProvider::Graphite),
                 graphite_addr: "graphite:9108".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn metrics_prometheus() -> Result<()> {
+    #[rustfmt::skip]
+    let iter = vec![
+        "linkd",
+            "--protocol-listen", "localhost",
+            "--metrics-provider", "prometheus",
+            "--prometheus-listen", "unix:/tmp/linkd-metrics.sock",
+    ];
+    let parsed = Args::try_parse_from(iter)?;
+
+    assert_eq!(
+        parsed,
+        Args {
+            metrics: MetricsArgs {
+                provider: Some(MetricsProvider::Prometheus),
+                prometheus_listen: "unix:/tmp/linkd-metrics.sock".to_string(),
+                ..Default::default()
             },
             ..Default::default()
         }
@@ -189,6 +220,132 @@ fn protocol_network() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn protocol_drain_timeout() -> Result<()> {
+    #[rustfmt::skip]
+    let iter = vec![
+        "linkd",
+            "--protocol-listen", "localhost",
+            "--protocol-drain-timeout", "60",
+    ];
+    let parsed = Args::try_parse_from(iter)?;
+
+    assert_eq!(
+        parsed,
+        Args {
+            protocol: ProtocolArgs {
+                drain_timeout: DrainTimeout::from_str("60").unwrap(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn bandwidth() -> Result<()> {
+    #[rustfmt::skip]
+    let iter = vec![
+        "linkd",
+            "--protocol-listen", "localhost",
+            "--bandwidth-peer-daily", "1073741824",
+            "--bandwidth-urn-burst", "10485760",
+    ];
+    let parsed = Args::try_parse_from(iter)?;
+
+    assert_eq!(
+        parsed,
+        Args {
+            bandwidth: BandwidthArgs {
+                peer_daily: Some(1073741824),
+                urn_burst: Some(10485760),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn maintenance() -> Result<()> {
+    #[rustfmt::skip]
+    let iter = vec![
+        "linkd",
+            "--protocol-listen", "localhost",
+            "--maintenance-interval", "86400",
+            "--maintenance-no-delta-islands",
+    ];
+    let parsed = Args::try_parse_from(iter)?;
+
+    assert_eq!(
+        parsed,
+        Args {
+            maintenance: MaintenanceArgs {
+                interval: Some(86400),
+                no_delta_islands: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn storage_quota() -> Result<()> {
+    #[rustfmt::skip]
+    let iter = vec![
+        "linkd",
+            "--protocol-listen", "localhost",
+            "--storage-quota-urn", "1073741824",
+            "--storage-quota-remote", "268435456",
+    ];
+    let parsed = Args::try_parse_from(iter)?;
+
+    assert_eq!(
+        parsed,
+        Args {
+            storage_quota: StorageQuotaArgs {
+                urn: Some(1073741824),
+                remote: Some(268435456),
+                archived_grace_period: None,
+            },
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn archived_grace_period() -> Result<()> {
+    #[rustfmt::skip]
+    let iter = vec![
+        "linkd",
+            "--protocol-listen", "localhost",
+            "--archived-grace-period", "604800",
+    ];
+    let parsed = Args::try_parse_from(iter)?;
+
+    assert_eq!(
+        parsed,
+        Args {
+            storage_quota: StorageQuotaArgs {
+                archived_grace_period: Some(604800),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    Ok(())
+}
+
 #[test]
 fn lnk_home() -> Result<()> {
     #[rustfmt::skip]
@@ -0,0 +1,77 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use librad::{
+    git::storage::Storage,
+    net::{
+        discovery,
+        peer::{self, config::DenyAll, Peer},
+        protocol,
+    },
+    paths::Paths,
+    SecretKey,
+};
+use linkd_lib::protocol::routine;
+use tokio::{sync::mpsc, time::sleep};
+
+#[test]
+fn shutdown_waits_for_inflight() {
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = Paths::from_root(tmp.path()).unwrap();
+        let key = SecretKey::new();
+        Storage::init(&paths, key.clone()).unwrap();
+
+        let peer = Peer::new(peer::Config {
+            signer: key,
+            protocol: protocol::Config {
+                paths,
+                listen_addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+                advertised_addrs: None,
+                membership: Default::default(),
+                network: Default::default(),
+                replication: Default::default(),
+                rate_limits: Default::default(),
+                request_pull: DenyAll,
+            },
+            storage: Default::default(),
+        })
+        .unwrap();
+
+        // Stands in for a replication which is still fetching
+        let inflight = peer.in_flight().unwrap();
+        let completed = Arc::new(AtomicBool::new(false));
+
+        let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let protocol = tokio::spawn(routine(
+            peer.clone(),
+            discovery::Static::from(vec![]),
+            shutdown_rx,
+            Duration::from_secs(10),
+        ));
+        shutdown_tx.send(()).await.unwrap();
+
+        tokio::spawn({
+            let completed = completed.clone();
+            async move {
+                sleep(Duration::from_millis(500)).await;
+                completed.store(true, Ordering::SeqCst);
+                drop(inflight)
+            }
+        });
+
+        protocol.await.unwrap().unwrap();
+        assert!(completed.load(Ordering::SeqCst));
+        // No new work is admitted once drained
+        assert!(peer.in_flight().is_none());
+    })
+}
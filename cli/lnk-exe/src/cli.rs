@@ -4,6 +4,8 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 pub mod args;
+pub mod doctor;
 pub mod main;
+pub mod stats;
 
 pub use main::main;
@@ -51,10 +51,14 @@ pub struct Global {
 
 #[derive(Debug, Parser)]
 pub enum Command {
+    /// Check the storage for inconsistencies
+    Doctor(super::doctor::Args),
     /// Manage Radicle Identities
     Identities(lnk_identities::cli::args::Args),
     /// Manage your Radicle profiles
     Profile(lnk_profile::cli::args::Args),
+    /// Show storage statistics per identity
+    Stats(super::stats::Args),
     /// Sync with your configured seeds
     #[clap(flatten)]
     Sync(lnk_sync::cli::args::Args),
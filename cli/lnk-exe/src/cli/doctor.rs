@@ -0,0 +1,64 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use clap::Parser;
+
+use librad::{
+    git::fsck,
+    profile::{LnkHome, Profile, ProfileId},
+};
+use lnk_clib::{keys::ssh::SshAuthSock, storage::ssh};
+
+/// Check the storage for inconsistencies: identities which do not verify,
+/// invalid or unmatched signed refs, malformed tracking entries, and dangling
+/// `rad/self` refs.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Prune everything found to be invalid. Remotes which were pruned will be
+    /// fetched again on the next sync.
+    #[clap(long)]
+    pub repair: bool,
+}
+
+pub fn main(
+    Args { repair }: Args,
+    profile: Option<ProfileId>,
+    sock: SshAuthSock,
+) -> anyhow::Result<()> {
+    let home = LnkHome::default();
+    let profile = Profile::from_home(&home, profile)?;
+    let (_, storage) = ssh::storage(&profile, sock)?;
+
+    let report = fsck::check(&storage)?;
+    println!(
+        "checked {} namespaces and {} tracking entries",
+        report.namespaces, report.tracking
+    );
+    if report.is_ok() {
+        println!("no problems found");
+        return Ok(());
+    }
+    for problem in &report.problems {
+        println!("{}", problem);
+    }
+
+    if !repair {
+        anyhow::bail!(
+            "found {} problems, re-run with `--repair` to prune them",
+            report.problems.len()
+        )
+    }
+
+    let repaired = fsck::repair(&storage, &report)?;
+    for urn in &repaired.unverified {
+        println!("deleted remotes of unverified `{}`", urn);
+    }
+    for name in &repaired.refs {
+        println!("deleted reference `{}`", name);
+    }
+    for urn in &repaired.resigned {
+        println!("re-signed refs of `{}`", urn);
+    }
+
+    Ok(())
+}
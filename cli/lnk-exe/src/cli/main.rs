@@ -5,7 +5,11 @@
 
 use clap::Parser;
 
-use super::args::{self, Args};
+use super::{
+    args::{self, Args},
+    doctor,
+    stats,
+};
 
 pub fn main() -> anyhow::Result<()> {
     let Args { global, command } = Args::parse();
@@ -20,10 +24,16 @@ pub fn main() -> anyhow::Result<()> {
         .unwrap();
 
     match command {
+        args::Command::Doctor(args) => {
+            doctor::main(args, global.lnk_profile, global.lnk_ssh_auth_sock)
+        },
         args::Command::Identities(args) => {
             lnk_identities::cli::main(args, global.lnk_profile, global.lnk_ssh_auth_sock)
         },
         args::Command::Profile(args) => lnk_profile::cli::main(args, global.lnk_ssh_auth_sock),
+        args::Command::Stats(args) => {
+            stats::main(args, global.lnk_profile, global.lnk_ssh_auth_sock)
+        },
         args::Command::Sync(args) => {
             lnk_sync::cli::main(args, global.lnk_profile, global.lnk_ssh_auth_sock, runtime)
         },
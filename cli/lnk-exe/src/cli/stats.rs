@@ -0,0 +1,50 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use clap::Parser;
+
+use librad::{
+    git::{storage::stats, Urn},
+    profile::{LnkHome, Profile, ProfileId},
+};
+use lnk_clib::{keys::ssh::SshAuthSock, storage::ssh};
+
+/// Show the number of refs, tracked remotes, reachable objects and their size,
+/// the time of the last update, and the collaborative objects of the
+/// identities in the storage.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Only show the statistics of this URN
+    #[clap(long)]
+    pub urn: Option<Urn>,
+}
+
+pub fn main(
+    Args { urn }: Args,
+    profile: Option<ProfileId>,
+    sock: SshAuthSock,
+) -> anyhow::Result<()> {
+    let home = LnkHome::default();
+    let profile = Profile::from_home(&home, profile)?;
+    let (_, storage) = ssh::storage(&profile, sock)?;
+
+    let all = match urn {
+        Some(urn) => vec![stats::for_urn(&storage, &urn)?],
+        None => storage.stats()?,
+    };
+    for stats in all {
+        println!("{}", stats.urn);
+        println!("  refs: {}", stats.refs);
+        println!("  tracked remotes: {}", stats.tracked);
+        println!("  objects: {}", stats.objects);
+        println!("  size: {} bytes", stats.size);
+        if let Some(updated) = stats.updated {
+            println!("  last updated: {}", updated);
+        }
+        for (typename, count) in &stats.cobs {
+            println!("  cobs {}: {}", typename, count);
+        }
+    }
+
+    Ok(())
+}
@@ -38,6 +38,7 @@ pub enum Command {
     Refs(Refs),
     Track(tracking::Track),
     Untrack(tracking::Untrack),
+    Purge(purge::Purge),
 }
 
 /// create, get, or modify a Radicle project
@@ -200,7 +201,8 @@ pub mod project {
         pub path: PathBuf,
     }
 
-    /// get a Radicle project
+    /// get a Radicle project, along with its status (active, deprecated,
+    /// archived, or moved)
     #[derive(Debug, Parser)]
     pub struct Get {
         /// the Radicle URN of the project
@@ -289,6 +291,9 @@ pub mod project {
         /// the peer to compare to
         #[clap(long)]
         pub peer: PeerId,
+        /// output the difference as JSON
+        #[clap(long)]
+        pub json: bool,
     }
 
     /// accept the proposed changes between the local Radicle project and a
@@ -339,6 +344,9 @@ pub mod person {
         Diff(Diff),
         Accept(Accept),
         Tracked(Tracked),
+        Vouch(Vouch),
+        Unvouch(Unvouch),
+        Vouches(Vouches),
     }
 
     /// create a new Radicle person, either with a fresh working copy or based
@@ -490,6 +498,9 @@ pub mod person {
         /// the peer to compare to, and accept from
         #[clap(long)]
         pub peer: PeerId,
+        /// output the difference as JSON
+        #[clap(long)]
+        pub json: bool,
     }
 
     /// accept the proposed changes between the local Radicle person and a
@@ -513,6 +524,44 @@ pub mod person {
         #[clap(long)]
         pub urn: Urn,
     }
+
+    /// vouch for a Radicle person, attesting that you know them
+    #[derive(Debug, Parser)]
+    pub struct Vouch {
+        /// the Radicle URN of the person to vouch for
+        #[clap(long)]
+        pub urn: Urn,
+
+        /// the Radicle URN pointing to the local identity to vouch as. If not
+        /// provided, the default identity is used.
+        #[clap(long)]
+        pub whoami: Option<Urn>,
+
+        /// a note describing the relationship to the person, eg. "colleague"
+        #[clap(long)]
+        pub note: Option<String>,
+    }
+
+    /// withdraw a previous vouch for a Radicle person
+    #[derive(Debug, Parser)]
+    pub struct Unvouch {
+        /// the Radicle URN of the person previously vouched for
+        #[clap(long)]
+        pub urn: Urn,
+
+        /// the Radicle URN pointing to the local identity which vouched. If
+        /// not provided, the default identity is used.
+        #[clap(long)]
+        pub whoami: Option<Urn>,
+    }
+
+    /// list the persons vouched for by a Radicle person
+    #[derive(Debug, Parser)]
+    pub struct Vouches {
+        /// the Radicle URN of the person
+        #[clap(long)]
+        pub urn: Urn,
+    }
 }
 
 pub mod any {
@@ -720,6 +769,11 @@ pub mod tracking {
         /// create the tracking relationship even if one already existed
         #[clap(long, short)]
         pub force: bool,
+
+        /// track all peers of the persons vouched for by the delegates of the
+        /// given `--urn`. Cannot be combined with `--peer`
+        #[clap(long)]
+        pub vouched: bool,
     }
 
     /// untrack a peer's gossip for a Radicle URN
@@ -740,6 +794,20 @@ pub mod tracking {
     }
 }
 
+pub mod purge {
+    use super::*;
+
+    /// remove a Radicle URN from the storage, including all references under
+    /// its namespace, its tracking entries, cached collaborative objects, and
+    /// its include file. The objects are left to be reclaimed by `git gc`
+    #[derive(Debug, Parser)]
+    pub struct Purge {
+        /// the Radicle URN to purge
+        #[clap(long)]
+        pub urn: Urn,
+    }
+}
+
 fn ext_payload(value: &str) -> Result<payload::Ext<serde_json::Value>, String> {
     serde_json::from_str(value).map_err(|err| err.to_string())
 }
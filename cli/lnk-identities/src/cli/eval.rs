@@ -7,6 +7,7 @@ pub mod any;
 pub mod local;
 pub mod person;
 pub mod project;
+pub mod purge;
 pub mod rad_refs;
 pub mod refs;
 pub mod tracking;
@@ -41,12 +41,17 @@ pub fn eval(profile: &Profile, sock: SshAuthSock, opts: Options) -> anyhow::Resu
         Options::Checkout(Checkout { urn, path, peer }) => {
             eval_checkout(profile, sock, urn, path, peer)?
         },
-        Options::Diff(Diff { urn, peer }) => eval_diff(profile, urn, peer)?,
+        Options::Diff(Diff { urn, peer, json }) => eval_diff(profile, urn, peer, json)?,
         Options::Accept(Accept { urn, peer, force }) => {
             eval_accept(profile, sock, urn, peer, force)?
         },
         Options::Tracked(Tracked { urn }) => eval_tracked(profile, urn)?,
         Options::Delegates(Delegates { urn, peer }) => eval_delegates(profile, urn, peer)?,
+        Options::Vouch(Vouch { urn, whoami, note }) => {
+            eval_vouch(profile, sock, urn, whoami, note)?
+        },
+        Options::Unvouch(Unvouch { urn, whoami }) => eval_unvouch(profile, sock, urn, whoami)?,
+        Options::Vouches(Vouches { urn }) => eval_vouches(profile, urn)?,
     }
 
     Ok(())
@@ -172,9 +177,44 @@ fn eval_delegates(profile: &Profile, urn: Urn, peer: Option<PeerId>) -> anyhow::
     Ok(())
 }
 
-fn eval_diff(profile: &Profile, urn: Urn, peer: PeerId) -> anyhow::Result<()> {
+fn eval_vouch(
+    profile: &Profile,
+    sock: SshAuthSock,
+    urn: Urn,
+    whoami: Option<Urn>,
+    note: Option<String>,
+) -> anyhow::Result<()> {
+    let (_, storage) = ssh::storage(profile, sock)?;
+    let attestation = person::vouch(&storage, &urn, whoami, note)?;
+    println!("{}", serde_json::to_string(&attestation)?);
+    Ok(())
+}
+
+fn eval_unvouch(
+    profile: &Profile,
+    sock: SshAuthSock,
+    urn: Urn,
+    whoami: Option<Urn>,
+) -> anyhow::Result<()> {
+    let (_, storage) = ssh::storage(profile, sock)?;
+    if person::unvouch(&storage, &urn, whoami)? {
+        println!("withdrew vouch for `{}`", urn);
+    } else {
+        println!("`{}` was not vouched for", urn);
+    }
+    Ok(())
+}
+
+fn eval_vouches(profile: &Profile, urn: Urn) -> anyhow::Result<()> {
     let storage = storage::read_only(profile)?;
-    diff(&storage, urn, peer)?;
+    let attestations = person::vouches(&storage, &urn)?;
+    println!("{}", serde_json::to_string(&attestations)?);
+    Ok(())
+}
+
+fn eval_diff(profile: &Profile, urn: Urn, peer: PeerId, json: bool) -> anyhow::Result<()> {
+    let storage = storage::read_only(profile)?;
+    diff(&storage, urn, peer, json)?;
     Ok(())
 }
 
@@ -187,7 +227,7 @@ fn eval_accept(
 ) -> anyhow::Result<()> {
     let (_, storage) = storage::ssh::storage(profile, sock)?;
 
-    diff(&storage, urn.clone(), peer)?;
+    diff(&storage, urn.clone(), peer, false)?;
 
     let accept = || -> anyhow::Result<()> {
         let person = identities::person::merge(&storage, &urn, peer)?;
@@ -242,30 +282,15 @@ fn eval_accept(
     Ok(())
 }
 
-fn diff<S>(storage: &S, urn: Urn, peer: PeerId) -> anyhow::Result<()>
+fn diff<S>(storage: &S, urn: Urn, peer: PeerId, json: bool) -> anyhow::Result<()>
 where
     S: AsRef<ReadOnly>,
 {
-    let storage = storage.as_ref();
-    let local = storage.peer_id();
-    let ours = identities::person::get(&storage, &urn)?
-        .ok_or_else(|| person::Error::Identities(identities::Error::NotFound(urn.clone())))?;
-    let theirs = {
-        let urn = Urn::try_from(Reference::rad_id(Namespace::from(&urn)).with_remote(peer))
-            .expect("namespace is set");
-        identities::person::get(&storage, &urn)?
-            .ok_or(person::Error::Identities(identities::Error::NotFound(urn)))?
-    };
-
-    let ours = &serde_json::to_string_pretty(&ours.payload()).unwrap();
-    let theirs = &serde_json::to_string_pretty(&theirs.payload()).unwrap();
-
-    println!(
-        "{}",
-        similar::TextDiff::from_lines(ours, theirs)
-            .unified_diff()
-            .context_radius(10)
-            .header(&format!("ours @ {}", local), &format!("theirs @ {}", peer))
-    );
+    let diff = identities::diff::person_with_peer(storage, &urn, peer)?;
+    if json {
+        println!("{}", serde_json::to_string(&diff)?);
+    } else {
+        print!("{}", diff);
+    }
     Ok(())
 }
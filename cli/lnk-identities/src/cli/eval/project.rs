@@ -48,7 +48,7 @@ pub fn eval(profile: &Profile, sock: SshAuthSock, opts: Options) -> anyhow::Resu
         Options::Checkout(Checkout { urn, path, peer }) => {
             eval_checkout(profile, sock, urn, path, peer)?
         },
-        Options::Diff(Diff { urn, peer }) => eval_diff(profile, urn, peer)?,
+        Options::Diff(Diff { urn, peer, json }) => eval_diff(profile, urn, peer, json)?,
         Options::Accept(Accept { urn, peer, force }) => {
             eval_accept(profile, sock, urn, peer, force)?
         },
@@ -111,7 +111,7 @@ fn eval_get(profile: &Profile, urn: Urn, peer: Option<PeerId>) -> anyhow::Result
         project::get(&storage, &urn)?.ok_or_else(|| identities::Error::NotFound(urn.clone()))?;
     println!(
         "{}",
-        serde_json::to_string(&project::Display::from(project))?
+        serde_json::to_string(&project::WithStatus::from(project))?
     );
     Ok(())
 }
@@ -182,9 +182,9 @@ fn eval_delegates(profile: &Profile, urn: Urn, peer: Option<PeerId>) -> anyhow::
     Ok(())
 }
 
-fn eval_diff(profile: &Profile, urn: Urn, peer: PeerId) -> anyhow::Result<()> {
+fn eval_diff(profile: &Profile, urn: Urn, peer: PeerId, json: bool) -> anyhow::Result<()> {
     let storage = storage::read_only(profile)?;
-    diff(&storage, urn, peer)?;
+    diff(&storage, urn, peer, json)?;
     Ok(())
 }
 
@@ -197,7 +197,7 @@ fn eval_accept(
 ) -> anyhow::Result<()> {
     let (_, storage) = storage::ssh::storage(profile, sock)?;
 
-    diff(&storage, urn.clone(), peer)?;
+    diff(&storage, urn.clone(), peer, false)?;
 
     let accept = || -> anyhow::Result<()> {
         let project = identities::project::merge(&storage, &urn, peer)?;
@@ -255,31 +255,15 @@ fn eval_accept(
     Ok(())
 }
 
-fn diff<S>(storage: &S, urn: Urn, peer: PeerId) -> anyhow::Result<()>
+fn diff<S>(storage: &S, urn: Urn, peer: PeerId, json: bool) -> anyhow::Result<()>
 where
     S: AsRef<ReadOnly>,
 {
-    let storage = storage.as_ref();
-    let local = storage.peer_id();
-    let ours = identities::project::get(&storage, &urn)?.ok_or_else(|| {
-        project::Error::Identities(Box::new(identities::Error::NotFound(urn.clone())))
-    })?;
-    let theirs = {
-        let urn = Urn::try_from(Reference::rad_id(Namespace::from(&urn)).with_remote(peer))
-            .expect("namespace is set");
-        identities::project::get(&storage, &urn)?
-            .ok_or_else(|| project::Error::Identities(Box::new(identities::Error::NotFound(urn))))?
-    };
-
-    let ours = &serde_json::to_string_pretty(&ours.payload()).unwrap();
-    let theirs = &serde_json::to_string_pretty(&theirs.payload()).unwrap();
-
-    println!(
-        "{}",
-        similar::TextDiff::from_lines(ours, theirs)
-            .unified_diff()
-            .context_radius(10)
-            .header(&format!("ours @ {}", local), &format!("theirs @ {}", peer))
-    );
+    let diff = identities::diff::project_with_peer(storage, &urn, peer)?;
+    if json {
+        println!("{}", serde_json::to_string(&diff)?);
+    } else {
+        print!("{}", diff);
+    }
     Ok(())
 }
@@ -0,0 +1,30 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use librad::{git::purge, profile::Profile};
+use lnk_clib::{keys::ssh::SshAuthSock, storage::ssh};
+
+use crate::cli::args::purge::*;
+
+pub fn eval(profile: &Profile, sock: SshAuthSock, Purge { urn }: Purge) -> anyhow::Result<()> {
+    let (_, storage) = ssh::storage(profile, sock)?;
+    let purged = purge::purge(&storage, profile.paths(), &urn)?;
+    if purged.is_empty() {
+        println!("nothing to purge for `{}`", urn);
+        return Ok(());
+    }
+
+    for name in &purged.refs {
+        println!("deleted reference `{}`", name);
+    }
+    println!("removed {} tracking entries", purged.tracking);
+    for path in &purged.cob_cache {
+        println!("removed cached object `{}`", path.display());
+    }
+    if let Some(path) = &purged.include {
+        println!("removed include file `{}`", path.display());
+    }
+    println!("purged `{}`", urn);
+
+    Ok(())
+}
@@ -19,8 +19,12 @@ pub fn eval_track(
         peer,
         config,
         force,
+        vouched,
     }: Track,
 ) -> anyhow::Result<()> {
+    if vouched && peer.is_some() {
+        anyhow::bail!("`--vouched` cannot be combined with `--peer`");
+    }
     let (_, storage) = ssh::storage(profile, sock)?;
     let paths = profile.paths();
     let policy = if force {
@@ -28,6 +32,18 @@ pub fn eval_track(
     } else {
         policy::Track::MustNotExist
     };
+    if vouched {
+        for (peer, tracked) in tracking::track_vouched(&storage, paths, &urn, config, policy)? {
+            match tracked {
+                Ok(r) => println!("created tracking relationship `{}`", r.name),
+                Err(err) => eprintln!(
+                    "could not create tracking relationship for `{}`: {}",
+                    peer, err
+                ),
+            }
+        }
+        return Ok(());
+    }
     match tracking::track(&storage, paths, &urn, peer, config, policy)? {
         Ok(r) => println!("created tracking relationship `{}`", r.name),
         Err(err) => eprintln!("could not create tracking relationship: {}", err),
@@ -8,7 +8,7 @@ use lnk_clib::keys::ssh;
 
 use super::{
     args::{Args, Command},
-    eval::{any, local, person, project, rad_refs, refs, tracking},
+    eval::{any, local, person, project, purge, rad_refs, refs, tracking},
 };
 
 pub fn main(
@@ -28,6 +28,7 @@ pub fn main(
         Command::Refs(opts) => refs::eval(&profile, opts.refs)?,
         Command::Track(track) => tracking::eval_track(&profile, sock, track)?,
         Command::Untrack(untrack) => tracking::eval_untrack(&profile, sock, untrack)?,
+        Command::Purge(opts) => purge::eval(&profile, sock, opts)?,
     }
 
     Ok(())
@@ -10,7 +10,7 @@ use thiserror::Error;
 use librad::{
     crypto::{BoxedSigner, PublicKey},
     git::{
-        identities::{self, person, relations, Person},
+        identities::{self, attestation, person, relations, Person},
         local::{transport, url::LocalUrl},
         storage::{ReadOnly, Storage},
         types::{Namespace, Reference},
@@ -34,6 +34,12 @@ pub type Display = display::Display<PersonPayload>;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("no local identity found, set a default identity or provide `--whoami`")]
+    NoLocalIdentity,
+
+    #[error(transparent)]
+    Attestation(#[from] attestation::Error),
+
     #[error(transparent)]
     Checkout(#[from] checkout::Error),
 
@@ -225,3 +231,47 @@ where
     let delegations = person.delegations();
     Ok(delegations.iter().copied().collect())
 }
+
+/// Vouch for the person `subject` as `whoami`, or as the default local
+/// identity if `whoami` is not provided.
+pub fn vouch(
+    storage: &Storage,
+    subject: &Urn,
+    whoami: Option<Urn>,
+    note: Option<String>,
+) -> Result<attestation::Signed, Error> {
+    // ensure that the URN exists and is indeed a person
+    let _guard =
+        get(storage, subject)?.ok_or_else(|| identities::Error::NotFound(subject.clone()))?;
+    let whoami = local_identity(storage, whoami)?;
+    Ok(attestation::vouch(storage, &whoami, subject, note)?)
+}
+
+/// Withdraw the vouch for the person `subject` made by `whoami`, or by the
+/// default local identity if `whoami` is not provided.
+///
+/// Returns `false` if `subject` was not vouched for.
+pub fn unvouch(storage: &Storage, subject: &Urn, whoami: Option<Urn>) -> Result<bool, Error> {
+    let whoami = local_identity(storage, whoami)?;
+    Ok(attestation::withdraw(storage, &whoami, subject)?)
+}
+
+/// List the valid attestations made by the person `urn`.
+pub fn vouches<S>(storage: &S, urn: &Urn) -> Result<Vec<attestation::Signed>, Error>
+where
+    S: AsRef<ReadOnly>,
+{
+    let _guard = get(storage, urn)?.ok_or_else(|| identities::Error::NotFound(urn.clone()))?;
+    Ok(attestation::list(storage, urn)?)
+}
+
+fn local_identity(
+    storage: &Storage,
+    whoami: Option<Urn>,
+) -> Result<identities::local::LocalIdentity, Error> {
+    let local = match whoami {
+        Some(me) => identities::local::load(storage, me)?,
+        None => identities::local::default(storage)?,
+    };
+    local.ok_or(Error::NoLocalIdentity)
+}
@@ -37,6 +37,23 @@ use crate::{
 
 pub type Display = display::Display<ProjectPayload>;
 
+/// A [`Display`] of a project, along with its lifecycle status.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct WithStatus {
+    #[serde(flatten)]
+    pub project: Display,
+    pub status: payload::ProjectStatus<Revision>,
+}
+
+impl From<Project> for WithStatus {
+    fn from(project: Project) -> Self {
+        Self {
+            status: project.status(),
+            project: project.into(),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
@@ -6,7 +6,12 @@
 use thiserror::Error;
 
 use librad::{
-    git::{identities, storage::Storage, tracking, Urn},
+    git::{
+        identities::{self, attestation},
+        storage::Storage,
+        tracking,
+        Urn,
+    },
     paths::Paths,
     PeerId,
 };
@@ -17,6 +22,9 @@ use crate::git::include;
 #[derive(Debug, Error)]
 #[allow(clippy::large_enum_variant)]
 pub enum Error {
+    #[error(transparent)]
+    Attestation(#[from] attestation::Error),
+
     #[error(transparent)]
     Include(#[from] include::Error),
 
@@ -51,6 +59,28 @@ pub fn track(
     Ok(tracked)
 }
 
+/// Track the peers of all persons vouched for by the delegates of `urn`, see
+/// [`attestation::vouched_peers`]. The local peer is skipped.
+///
+/// The result of [`track`] is returned for each of the peers.
+pub fn track_vouched(
+    storage: &Storage,
+    paths: &Paths,
+    urn: &Urn,
+    config: Option<tracking::Config>,
+    policy: tracking::policy::Track,
+) -> Result<Vec<(PeerId, Result<tracking::Ref, tracking::PreviousError>)>, Error> {
+    let local = storage.peer_id();
+    attestation::vouched_peers(storage, urn)?
+        .into_iter()
+        .filter(|peer| peer != local)
+        .map(|peer| {
+            track(storage, paths, urn, Some(peer), config.clone(), policy)
+                .map(|tracked| (peer, tracked))
+        })
+        .collect()
+}
+
 /// Track the given `urn` and `peer`. This will call
 /// [`include::update`] for modifying the include file for the given
 /// identity. If the `urn` does not exist in the storage, then no
@@ -0,0 +1,377 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Single-file archives of a [`librad::profile::Profile`], for moving it to
+//! another machine.
+//!
+//! An archive consists of a magic header, the length-prefixed JSON
+//! [`Manifest`], and the contents of the files listed in the manifest,
+//! concatenated in the same order. The monorepo is included as a `git bundle`
+//! of all its refs, the remaining files are copied verbatim: the encrypted
+//! keystore, the seeds file, the hooks and the cob caches.
+//!
+//! Every file is checked against the git blob hash recorded in the manifest
+//! when importing, and the keystore must belong to the [`PeerId`] the archive
+//! was created for.
+
+use std::{
+    fs,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use librad::{
+    crypto::PeerId,
+    git::{
+        storage::{read, ReadOnly},
+        Urn,
+    },
+    paths::Paths,
+};
+
+const MAGIC: &[u8] = b"lnk-profile-archive\n";
+
+/// The version of the archive format.
+pub const VERSION: u32 = 1;
+
+/// Upper bound of the length of the [`Manifest`], so a corrupt or malicious
+/// header can't make us allocate arbitrary amounts of memory.
+pub const MAX_MANIFEST_LEN: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("not a profile archive")]
+    NotAnArchive,
+
+    #[error("unsupported archive version {0}")]
+    Version(u32),
+
+    #[error("manifest length {0} exceeds the maximum of {max} bytes", max = MAX_MANIFEST_LEN)]
+    ManifestTooLarge(u64),
+
+    #[error("invalid path `{0}` in archive")]
+    InvalidPath(PathBuf),
+
+    #[error("integrity check of `{path}` failed: expected {expected}, found {actual}")]
+    Integrity {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("`git {cmd}` failed: {stderr}")]
+    Git { cmd: &'static str, stderr: String },
+
+    #[error(transparent)]
+    Storage(#[from] read::error::Init),
+
+    #[error("malformed manifest")]
+    Manifest(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Git2(#[from] git2::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub version: u32,
+    pub peer_id: PeerId,
+    /// The default identity of the profile, if set.
+    pub user: Option<Urn>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub kind: Kind,
+    /// Path relative to the directory of `kind`.
+    pub path: PathBuf,
+    pub size: u64,
+    /// The git blob hash of the contents.
+    pub oid: String,
+    #[serde(default)]
+    pub executable: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Kind {
+    /// A `git bundle` of the monorepo.
+    Bundle,
+    Keys,
+    Seeds,
+    Hooks,
+    CobCache,
+}
+
+const BUNDLE: &str = "monorepo.bundle";
+
+/// Write an archive of the profile at `paths` to `out`.
+///
+/// The storage should not be modified while the archive is written.
+pub fn export(paths: &Paths, out: &Path) -> Result<Manifest, Error> {
+    let storage = ReadOnly::open(paths)?;
+    let bundle = out.with_extension("bundle.tmp");
+    let res = export_with(paths, &storage, &bundle, out);
+    if bundle.exists() {
+        fs::remove_file(&bundle)?;
+    }
+    res
+}
+
+fn export_with(
+    paths: &Paths,
+    storage: &ReadOnly,
+    bundle: &Path,
+    out: &Path,
+) -> Result<Manifest, Error> {
+    let mut sources = Vec::new();
+
+    let repo = git2::Repository::open_bare(paths.git_dir())?;
+    if repo.references()?.next().is_some() {
+        git(
+            paths,
+            "bundle",
+            &[
+                "bundle".as_ref(),
+                "create".as_ref(),
+                bundle.as_os_str(),
+                "--all".as_ref(),
+            ],
+        )?;
+        sources.push((Kind::Bundle, PathBuf::from(BUNDLE), bundle.to_path_buf()));
+    }
+    for &(kind, dir) in &[
+        (Kind::Keys, paths.keys_dir()),
+        (Kind::Hooks, paths.hooks_dir()),
+        (Kind::CobCache, paths.cob_cache_dir()),
+    ] {
+        for path in files(dir)? {
+            sources.push((kind, path.clone(), dir.join(path)));
+        }
+    }
+    if paths.seeds_file().is_file() {
+        sources.push((
+            Kind::Seeds,
+            PathBuf::new(),
+            paths.seeds_file().to_path_buf(),
+        ));
+    }
+
+    let user = storage
+        .config()
+        .ok()
+        .and_then(|config| config.user().ok().flatten());
+    let mut manifest = Manifest {
+        version: VERSION,
+        peer_id: *storage.peer_id(),
+        user,
+        entries: Vec::with_capacity(sources.len()),
+    };
+    for (kind, path, source) in &sources {
+        let meta = fs::metadata(source)?;
+        manifest.entries.push(Entry {
+            kind: *kind,
+            path: path.clone(),
+            size: meta.len(),
+            oid: git2::Oid::hash_file(git2::ObjectType::Blob, source)?.to_string(),
+            executable: is_executable(&meta),
+        });
+    }
+
+    let mut w = BufWriter::new(fs::File::create(out)?);
+    let header = serde_json::to_vec(&manifest)?;
+    w.write_all(MAGIC)?;
+    w.write_all(&(header.len() as u64).to_be_bytes())?;
+    w.write_all(&header)?;
+    for (_, _, source) in &sources {
+        io::copy(&mut fs::File::open(source)?, &mut w)?;
+    }
+    w.flush()?;
+
+    Ok(manifest)
+}
+
+/// Unpack the archive at `archive` into the (empty) profile at `paths`.
+///
+/// The files are checked against the [`Manifest`], which is returned. The
+/// monorepo bundle is left at the returned path, to be fetched from once the
+/// storage was initialised with the key of the archive, see [`fetch_bundle`].
+pub fn unpack(paths: &Paths, archive: &Path) -> Result<(Manifest, Option<PathBuf>), Error> {
+    let mut r = BufReader::new(fs::File::open(archive)?);
+
+    let mut magic = [0; MAGIC.len()];
+    r.read_exact(&mut magic).map_err(|_| Error::NotAnArchive)?;
+    if magic != MAGIC {
+        return Err(Error::NotAnArchive);
+    }
+    let manifest: Manifest = {
+        let mut len = [0; 8];
+        r.read_exact(&mut len)?;
+        let len = u64::from_be_bytes(len);
+        if len > MAX_MANIFEST_LEN {
+            return Err(Error::ManifestTooLarge(len));
+        }
+        let mut header = vec![0; len as usize];
+        r.read_exact(&mut header)?;
+        serde_json::from_slice(&header)?
+    };
+    if manifest.version != VERSION {
+        return Err(Error::Version(manifest.version));
+    }
+
+    let mut bundle = None;
+    for entry in &manifest.entries {
+        let dest = match entry.kind {
+            Kind::Bundle => {
+                let dest = paths.git_dir().join(BUNDLE);
+                bundle = Some(dest.clone());
+                dest
+            },
+            Kind::Keys => paths.keys_dir().join(relative(&entry.path)?),
+            Kind::Hooks => paths.hooks_dir().join(relative(&entry.path)?),
+            Kind::CobCache => paths.cob_cache_dir().join(relative(&entry.path)?),
+            Kind::Seeds => paths.seeds_file().to_path_buf(),
+        };
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        {
+            let mut file = fs::File::create(&dest)?;
+            let copied = io::copy(&mut (&mut r).take(entry.size), &mut file)?;
+            if copied != entry.size {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+        }
+        let actual = git2::Oid::hash_file(git2::ObjectType::Blob, &dest)?.to_string();
+        if actual != entry.oid {
+            return Err(Error::Integrity {
+                path: entry.path.clone(),
+                expected: entry.oid.clone(),
+                actual,
+            });
+        }
+        if entry.executable {
+            set_executable(&dest)?;
+        }
+    }
+
+    Ok((manifest, bundle))
+}
+
+/// Fetch all refs from the monorepo `bundle` into the storage at `paths`, and
+/// remove the bundle.
+pub fn fetch_bundle(paths: &Paths, bundle: &Path) -> Result<(), Error> {
+    git(
+        paths,
+        "bundle verify",
+        &[
+            "bundle".as_ref(),
+            "verify".as_ref(),
+            "--quiet".as_ref(),
+            bundle.as_os_str(),
+        ],
+    )?;
+    git(
+        paths,
+        "fetch",
+        &[
+            "fetch".as_ref(),
+            "--quiet".as_ref(),
+            bundle.as_os_str(),
+            "+refs/*:refs/*".as_ref(),
+        ],
+    )?;
+    fs::remove_file(bundle)?;
+
+    Ok(())
+}
+
+fn git(paths: &Paths, cmd: &'static str, args: &[&std::ffi::OsStr]) -> Result<(), Error> {
+    let out = Command::new("git")
+        .arg("--git-dir")
+        .arg(paths.git_dir())
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(Error::Git {
+            cmd,
+            stderr: String::from_utf8_lossy(&out.stderr).into_owned(),
+        })
+    }
+}
+
+/// All regular files below `dir`, relative to `dir`.
+fn files(dir: &Path) -> Result<Vec<PathBuf>, io::Error> {
+    fn go(root: &Path, dir: &Path, acc: &mut Vec<PathBuf>) -> Result<(), io::Error> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let ty = entry.file_type()?;
+            if ty.is_dir() {
+                go(root, &entry.path(), acc)?;
+            } else if ty.is_file() {
+                acc.push(
+                    entry
+                        .path()
+                        .strip_prefix(root)
+                        .expect("entry is below root")
+                        .to_path_buf(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    let mut acc = Vec::new();
+    if dir.is_dir() {
+        go(dir, dir, &mut acc)?;
+    }
+    acc.sort();
+    Ok(acc)
+}
+
+/// Guard against archives writing outside of the profile.
+fn relative(path: &Path) -> Result<&Path, Error> {
+    if path.as_os_str().is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(Error::InvalidPath(path.to_path_buf()));
+    }
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<(), io::Error> {
+    use std::os::unix::fs::PermissionsExt as _;
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_mode(perms.mode() | 0o111);
+    fs::set_permissions(path, perms)
+}
+
+#[cfg(not(unix))]
+fn set_executable(_: &Path) -> Result<(), io::Error> {
+    Ok(())
+}
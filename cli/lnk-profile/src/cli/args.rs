@@ -3,6 +3,8 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::path::PathBuf;
+
 use clap::Parser;
 
 use librad::profile::ProfileId;
@@ -24,6 +26,8 @@ pub enum Command {
     Peer(GetPeerId),
     Paths(GetPaths),
     Ssh(Ssh),
+    Export(Export),
+    Import(Import),
 }
 
 /// Create a new profile, generating a new secret key and initialising
@@ -69,6 +73,29 @@ pub struct GetPaths {
     pub id: Option<ProfileId>,
 }
 
+/// Export a profile to a single archive file, containing the storage, the
+/// encrypted key, the seeds, hooks and cob caches. If no profile was provided,
+/// then the active one is used.
+#[derive(Debug, Parser)]
+pub struct Export {
+    /// the identifier of the profile to export
+    #[clap(long)]
+    pub id: Option<ProfileId>,
+    /// the file to write the archive to
+    #[clap(long)]
+    pub output: PathBuf,
+}
+
+/// Import a profile from an archive created by `lnk profile export`, verifying
+/// its integrity and decrypting the key it contains. The imported profile is
+/// not set as the active profile.
+#[derive(Debug, Parser)]
+pub struct Import {
+    /// the archive to import
+    #[clap(long)]
+    pub archive: PathBuf,
+}
+
 /// Manage the profile's key material on the ssh-agent
 #[derive(Debug, Parser)]
 pub struct Ssh {
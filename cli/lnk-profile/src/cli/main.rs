@@ -12,7 +12,9 @@ use lnk_clib::keys::{self, ssh::SshAuthSock};
 
 use crate::{
     create,
+    export,
     get,
+    import,
     list,
     paths,
     peer_id,
@@ -66,6 +68,20 @@ fn eval(sock: SshAuthSock, command: Command) -> anyhow::Result<()> {
             println!("git includes: {}", paths.git_includes_dir().display());
             println!("keys: {}", paths.keys_dir().display());
         },
+        Command::Export(Export { id, output }) => {
+            let (id, peer_id) = export(None, id, &output)?;
+            println!(
+                "exported profile id `{}` with peer id `{}` to {}",
+                id,
+                peer_id,
+                output.display()
+            );
+        },
+        Command::Import(Import { archive }) => {
+            let (profile, peer_id) = import(None, &archive, keys::prompt::new())?;
+            println!("profile id: {}", profile.id());
+            println!("peer id: {}", peer_id);
+        },
         Command::Ssh(Ssh { options }) => match options {
             ssh::Options::Add(ssh::Add { id, time }) => {
                 let constraints =
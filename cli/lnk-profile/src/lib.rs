@@ -3,7 +3,7 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{error, fmt};
+use std::{error, fmt, path::Path};
 
 use lnk_thrussh_agent::Constraint;
 use serde::{de::DeserializeOwned, Serialize};
@@ -17,13 +17,17 @@ use librad::{
         PublicKey,
         SecretKey,
     },
-    git::storage::{self, read, ReadOnly, Storage},
+    git::{
+        identities,
+        storage::{self, read, ReadOnly, Storage},
+    },
     paths::Paths,
     profile::{self, LnkHome, Profile, ProfileId},
     Signature,
 };
 use lnk_clib::keys::{self, ssh::SshAuthSock};
 
+pub mod archive;
 pub mod cli;
 
 #[derive(Debug, Error)]
@@ -32,6 +36,12 @@ pub enum Error {
     #[error(transparent)]
     AddKey(#[from] keys::ssh::Error),
     #[error(transparent)]
+    Archive(#[from] archive::Error),
+    #[error("the archive was created for {expected}, but its key belongs to {actual}")]
+    PeerMismatch { expected: PeerId, actual: PeerId },
+    #[error("failed to restore the default identity")]
+    RestoreUser(#[source] Box<dyn error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
     Keystore(Box<dyn error::Error + Send + Sync + 'static>),
     #[error("no active profile was found, perhaps you need to create one")]
     NoActiveProfile,
@@ -214,3 +224,59 @@ where
     let verified = keys::ssh::verify(&profile, payload.as_bytes(), &signature)?;
     Ok((profile.id().clone(), verified))
 }
+
+/// Export a [`Profile`] to a single archive file at `out`, see [`archive`].
+pub fn export<H, P>(home: H, id: P, out: &Path) -> Result<(ProfileId, PeerId), Error>
+where
+    H: Into<Option<LnkHome>>,
+    P: Into<Option<ProfileId>>,
+{
+    let home = home.into().unwrap_or_default();
+    let profile = get_or_active(&home, id)?;
+    let manifest = archive::export(profile.paths(), out)?;
+    Ok((profile.id().clone(), manifest.peer_id))
+}
+
+/// Import an archive created by [`export`] as a new [`Profile`].
+///
+/// The archive is checked for integrity, and the key it contains must be
+/// decryptable and belong to the peer the archive was created for. The new
+/// profile is not set as the active profile. If the import fails, the
+/// partially imported profile is left on disk.
+pub fn import<H, C: Crypto>(home: H, archive: &Path, crypto: C) -> Result<(Profile, PeerId), Error>
+where
+    H: Into<Option<LnkHome>>,
+    C::Error: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    C::SecretBox: Serialize + DeserializeOwned,
+{
+    let home = home.into().unwrap_or_default();
+    let profile = Profile::new(&home)?;
+    let (manifest, bundle) = archive::unpack(profile.paths(), archive)?;
+
+    let store: FileStorage<C, PublicKey, SecretKey, _> = keys::file_storage(&profile, crypto);
+    let key = store.get_key()?.secret_key;
+    let peer_id = PeerId::from(key.clone());
+    if peer_id != manifest.peer_id {
+        return Err(Error::PeerMismatch {
+            expected: manifest.peer_id,
+            actual: peer_id,
+        });
+    }
+
+    let storage = Storage::open(profile.paths(), key)?;
+    if let Some(bundle) = bundle {
+        archive::fetch_bundle(profile.paths(), &bundle)?;
+    }
+    if let Some(urn) = manifest.user {
+        let user =
+            identities::local::load(&storage, urn).map_err(|e| Error::RestoreUser(Box::new(e)))?;
+        if let Some(user) = user {
+            storage
+                .config()
+                .and_then(|mut config| config.set_user(user))
+                .map_err(|e| Error::RestoreUser(Box::new(e)))?;
+        }
+    }
+
+    Ok((profile, peer_id))
+}
@@ -0,0 +1,6 @@
+#[cfg(test)]
+#[macro_use]
+extern crate assert_matches;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,176 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{fs, io::Write as _, path::Path};
+
+use tempfile::tempdir;
+
+use it_helpers::fixed::TestPerson;
+use librad::{
+    crypto::{
+        keystore::{
+            crypto::{Pwhash, KDF_PARAMS_TEST},
+            pinentry::SecUtf8,
+            Keystore as _,
+        },
+        PeerId,
+        SecretKey,
+    },
+    git::{
+        identities,
+        storage::{ReadOnly, Storage},
+        Urn,
+    },
+    paths::Paths,
+    profile::{LnkHome, Profile},
+};
+use lnk_clib::keys::file_storage;
+use lnk_profile::{
+    archive::{self, Manifest, MAX_MANIFEST_LEN},
+    Error,
+};
+
+const MAGIC: &[u8] = b"lnk-profile-archive\n";
+const HOOK: &str = "post-receive";
+
+#[test]
+fn round_trip() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let out = tmp.path().join("profile.archive");
+    let (exported, urn) = setup(tmp.path())?;
+    let manifest = archive::export(exported.paths(), &out)?;
+
+    let home = LnkHome::Root(tmp.path().join("import"));
+    let (imported, peer_id) = lnk_profile::import(home, &out, pass())?;
+    assert_eq!(peer_id, manifest.peer_id);
+
+    let storage = ReadOnly::open(imported.paths())?;
+    assert_eq!(storage.peer_id(), &peer_id);
+    assert_eq!(storage.config()?.user()?, Some(urn));
+    assert_eq!(refs(imported.paths())?, refs(exported.paths())?);
+    assert_eq!(
+        fs::read(imported.paths().hooks_dir().join(HOOK))?,
+        fs::read(exported.paths().hooks_dir().join(HOOK))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn peer_mismatch() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let out = tmp.path().join("profile.archive");
+    let (exported, _) = setup(tmp.path())?;
+    archive::export(exported.paths(), &out)?;
+
+    let other = PeerId::from(SecretKey::new());
+    rewrite_manifest(&out, |manifest| manifest.peer_id = other)?;
+
+    let home = LnkHome::Root(tmp.path().join("import"));
+    assert_matches!(
+        lnk_profile::import(home, &out, pass()),
+        Err(Error::PeerMismatch { expected, .. }) if expected == other
+    );
+
+    Ok(())
+}
+
+#[test]
+fn integrity_failure() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let out = tmp.path().join("profile.archive");
+    let (exported, _) = setup(tmp.path())?;
+    archive::export(exported.paths(), &out)?;
+
+    // Flip the last byte, ie. the last byte of the last file in the archive
+    let mut bytes = fs::read(&out)?;
+    *bytes.last_mut().unwrap() ^= 0xff;
+    fs::write(&out, bytes)?;
+
+    let home = LnkHome::Root(tmp.path().join("import"));
+    assert_matches!(
+        lnk_profile::import(home, &out, pass()),
+        Err(Error::Archive(archive::Error::Integrity { .. }))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn manifest_too_large() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let out = tmp.path().join("profile.archive");
+    {
+        let mut file = fs::File::create(&out)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&u64::MAX.to_be_bytes())?;
+    }
+
+    let home = LnkHome::Root(tmp.path().join("import"));
+    assert_matches!(
+        lnk_profile::import(home, &out, pass()),
+        Err(Error::Archive(archive::Error::ManifestTooLarge(len))) if len > MAX_MANIFEST_LEN
+    );
+
+    Ok(())
+}
+
+fn pass() -> Pwhash<SecUtf8> {
+    Pwhash::new(SecUtf8::from(b"42".to_vec()), *KDF_PARAMS_TEST)
+}
+
+/// Create a profile below `root` with a default identity and a hook.
+fn setup(root: &Path) -> anyhow::Result<(Profile, Urn)> {
+    let home = LnkHome::Root(root.join("export"));
+    let profile = Profile::new(&home)?;
+    let key = SecretKey::new();
+    file_storage(&profile, pass()).put_key(key.clone())?;
+    let storage = Storage::open(profile.paths(), key)?;
+
+    let person = TestPerson::create(&storage)?;
+    let urn = person.owner.urn();
+    let user = identities::local::load(&storage, urn.clone())?;
+    storage.config()?.set_user(user)?;
+
+    let hooks = profile.paths().hooks_dir();
+    fs::create_dir_all(hooks)?;
+    fs::write(hooks.join(HOOK), b"#!/bin/sh\nexit 0\n")?;
+
+    Ok((profile, urn))
+}
+
+fn refs(paths: &Paths) -> anyhow::Result<Vec<(String, git2::Oid)>> {
+    let repo = git2::Repository::open_bare(paths.git_dir())?;
+    let mut refs = repo
+        .references_glob("refs/namespaces/*")?
+        .filter_map(|r| {
+            r.map(|r| Some((r.name()?.to_owned(), r.target()?)))
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    refs.sort();
+    Ok(refs)
+}
+
+/// Modify the [`Manifest`] of the archive at `path` in place.
+fn rewrite_manifest<F>(path: &Path, f: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&mut Manifest),
+{
+    let bytes = fs::read(path)?;
+    let (len, rest) = bytes[MAGIC.len()..].split_at(8);
+    let len = u64::from_be_bytes(len.try_into()?) as usize;
+    let (header, contents) = rest.split_at(len);
+
+    let mut manifest: Manifest = serde_json::from_slice(header)?;
+    f(&mut manifest);
+    let header = serde_json::to_vec(&manifest)?;
+
+    let mut file = fs::File::create(path)?;
+    file.write_all(MAGIC)?;
+    file.write_all(&(header.len() as u64).to_be_bytes())?;
+    file.write_all(&header)?;
+    file.write_all(contents)?;
+
+    Ok(())
+}
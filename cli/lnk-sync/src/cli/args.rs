@@ -24,6 +24,9 @@ pub enum Args {
     /// remotes set up in the form rad://<handle>@<peer id> for each delegate of
     /// the URN.
     ///
+    /// If the project was moved to a new URN, as indicated by its status, the
+    /// new URN is cloned instead.
+    ///
     /// # Choosing a peer
     ///
     /// If you run clone without a peer selected (the --peer argument) then this
@@ -1,13 +1,17 @@
 // Copyright © 2022 The Radicle Link Contributors
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
 use lnk_identities::working_copy_dir::WorkingCopyDir;
 use tokio::runtime::Runtime;
 
 use librad::{
-    git::{identities::project::heads, storage::ReadOnlyStorage},
+    git::{
+        identities::project::{self, heads},
+        storage::ReadOnlyStorage,
+    },
+    identities::payload::ProjectStatus,
     net::{
         self,
         peer::{client, Client},
@@ -79,18 +83,46 @@ pub fn main(
             },
             Args::Clone { urn, path, peer } => {
                 let storage = librad::git::Storage::open(paths, signer.clone())?;
-
-                let already_had_urn = storage.has_urn(&urn)?;
                 let path = WorkingCopyDir::at_or_current_dir(path)?;
-                println!("cloning urn {} into {}", urn, path);
-                println!("syncing monorepo with seeds");
-                sync(&client, urn.clone(), seeds, crate::Mode::Fetch).await;
 
-                if !already_had_urn {
-                    // This is the first time we've seen this project, so we set the default head
+                // Follow the project to its new URN if it moved
+                let mut urn = urn;
+                let mut seen = BTreeSet::new();
+                let (vp, already_had_urn) = loop {
+                    let already_had_urn = storage.has_urn(&urn)?;
+                    println!("cloning urn {} into {}", urn, path);
+                    println!("syncing monorepo with seeds");
+                    sync(&client, urn.clone(), seeds.clone(), crate::Mode::Fetch).await;
+                    seen.insert(urn.clone());
 
-                    let vp = librad::git::identities::project::verify(&storage, &urn)?
+                    let vp = project::verify(&storage, &urn)?
                         .ok_or_else(|| anyhow::anyhow!("no such project"))?;
+                    match vp.status() {
+                        ProjectStatus::Moved { to } => {
+                            if seen.contains(&to) {
+                                anyhow::bail!(
+                                    "project {} was moved to {}, which moved back to it",
+                                    urn,
+                                    to
+                                );
+                            }
+                            println!("project {} has moved to {}", urn, to);
+                            urn = to;
+                        },
+                        ProjectStatus::Archived { since } => {
+                            println!("warning: project {} was archived at {}", urn, since);
+                            break (vp, already_had_urn);
+                        },
+                        ProjectStatus::Deprecated { since } => {
+                            println!("warning: project {} was deprecated at {}", urn, since);
+                            break (vp, already_had_urn);
+                        },
+                        ProjectStatus::Active => break (vp, already_had_urn),
+                    }
+                };
+
+                if !already_had_urn {
+                    // This is the first time we've seen this project, so we set the default head
 
                     if peer.is_none() {
                         match heads::set_default_head(&storage, vp) {
@@ -3,7 +3,14 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use link_identities::{git::Urn, Person, Project, VerifiedPerson, VerifiedProject};
+use link_identities::{
+    git::Urn,
+    payload::PersonRevocations,
+    Person,
+    Project,
+    VerifiedPerson,
+    VerifiedProject,
+};
 
 pub enum AuthDecision {
     Authorized,
@@ -18,6 +25,8 @@ pub trait AuthorizingIdentity: std::fmt::Debug {
     fn check_authorization(&self, principal: &VerifiedPerson) -> AuthDecision;
     /// The OID of the tip of this identity
     fn content_id(&self) -> git2::Oid;
+    /// Keys revoked by this identity, or the persons it delegates to
+    fn revocations(&self) -> PersonRevocations;
 }
 
 impl AuthorizingIdentity for VerifiedPerson {
@@ -40,6 +49,11 @@ impl AuthorizingIdentity for VerifiedPerson {
     fn content_id(&self) -> git2::Oid {
         self.content_id.into()
     }
+
+    fn revocations(&self) -> PersonRevocations {
+        let p: &Person = self;
+        p.revocations()
+    }
 }
 
 impl AuthorizingIdentity for VerifiedProject {
@@ -55,4 +69,8 @@ impl AuthorizingIdentity for VerifiedProject {
     fn content_id(&self) -> git2::Oid {
         self.content_id.into()
     }
+    fn revocations(&self) -> PersonRevocations {
+        let p: &Project = self;
+        p.revocations()
+    }
 }
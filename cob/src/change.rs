@@ -6,8 +6,8 @@
 use super::{trailers, EntryContents, HistoryType, TypeName};
 
 use git_trailers::{parse as parse_trailers, OwnedTrailer};
-use link_crypto::BoxedSigner;
-use link_identities::sign::Signatures;
+use link_crypto::{BoxedSigner, PublicKey};
+use link_identities::{payload::PersonRevocations, sign::Signatures};
 
 use std::{convert::TryFrom, fmt};
 
@@ -267,6 +267,21 @@ impl Change {
         self.authorizing_identity_commit
     }
 
+    /// The first key among the signatures of this change whose signature is
+    /// rejected by `revocations`, see [`PersonRevocations::rejects`].
+    pub fn revoked_signer(
+        &self,
+        repo: &git2::Repository,
+        revocations: &PersonRevocations,
+    ) -> Result<Option<PublicKey>, git2::Error> {
+        for key in self.signatures.keys() {
+            if revocations.rejects(repo, key, self.commit)? {
+                return Ok(Some(*key));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn valid_signatures(&self) -> bool {
         for (key, sig) in self.signatures.iter() {
             if !key.verify(sig, self.revision.as_bytes()) {
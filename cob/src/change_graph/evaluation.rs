@@ -5,6 +5,8 @@
 
 use std::{collections::HashMap, ops::ControlFlow};
 
+use link_crypto::PublicKey;
+
 use crate::{
     change::Change,
     history,
@@ -106,6 +108,23 @@ impl<'a, I: IdentityStorage> Evaluating<'a, I> {
             },
         };
 
+        // Check that the change was not signed by a revoked key, unless it is
+        // among the commits the revocation retains. The author identity
+        // referenced by the change may predate the revocation, so consult the
+        // latest one we know of.
+        let latest_author = self
+            .identities
+            .delegate_oid(author.urn())
+            .ok()
+            .and_then(|oid| lookup_person(self.repo, oid).ok().flatten());
+        let mut revocations = latest_author.as_ref().unwrap_or(&author).revocations();
+        revocations.merge(self.authorizing_identity.revocations());
+        match change.revoked_signer(self.repo, &revocations) {
+            Ok(None) => {},
+            Ok(Some(key)) => return Err(RejectionReason::RevokedKey { key }),
+            Err(e) => return Err(RejectionReason::ErrorCheckingRevocations(Box::new(e))),
+        }
+
         Ok(history::HistoryEntry::new(
             *change.commit(),
             author.urn(),
@@ -146,6 +165,10 @@ enum RejectionReason {
     Unauthorized {
         reason: &'static str,
     },
+    RevokedKey {
+        key: PublicKey,
+    },
+    ErrorCheckingRevocations(Box<dyn std::error::Error>),
 }
 
 impl RejectionReason {
@@ -195,6 +218,20 @@ impl RejectionReason {
                     "rejecting change as it was not authorized"
                 );
             },
+            RejectionReason::RevokedKey { key } => {
+                tracing::warn!(
+                    commit=?change.commit(),
+                    ?key,
+                    "rejecting change signed by a revoked key"
+                );
+            },
+            RejectionReason::ErrorCheckingRevocations(error) => {
+                tracing::warn!(
+                    commit=?change.commit(),
+                    err=?error,
+                    "rejecting change due to an error checking the revocations of its signers"
+                );
+            },
         }
     }
 }
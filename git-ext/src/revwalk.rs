@@ -3,6 +3,27 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use crate::error::is_not_found_err;
+
+/// Whether `ancestor` is the same commit as `descendant`, or one of its
+/// ancestors.
+///
+/// Commits which are not in the object database are not ancestors of anything,
+/// nor have they any ancestors.
+pub fn is_ancestor(
+    repo: &git2::Repository,
+    ancestor: git2::Oid,
+    descendant: git2::Oid,
+) -> Result<bool, git2::Error> {
+    if ancestor == descendant {
+        return Ok(true);
+    }
+    match repo.graph_descendant_of(descendant, ancestor) {
+        Err(e) if is_not_found_err(&e) => Ok(false),
+        res => res,
+    }
+}
+
 pub enum Start {
     Oid(git2::Oid),
     Ref(String),
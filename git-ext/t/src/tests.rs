@@ -253,3 +253,44 @@ mod pattern {
         );
     }
 }
+
+mod revwalk {
+    use radicle_git_ext::is_ancestor;
+
+    fn commit(repo: &git2::Repository, parents: &[&git2::Commit]) -> git2::Oid {
+        let sig = git2::Signature::now("leboeuf", "leboeuf@acme.com").unwrap();
+        let tree = {
+            let oid = repo.treebuilder(None).unwrap().write().unwrap();
+            repo.find_tree(oid).unwrap()
+        };
+        repo.commit(None, &sig, &sig, "whatever", &tree, parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn ancestry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init_bare(tmp.path()).unwrap();
+
+        let root = commit(&repo, &[]);
+        let child = commit(&repo, &[&repo.find_commit(root).unwrap()]);
+        let unrelated = commit(&repo, &[]);
+
+        assert!(is_ancestor(&repo, root, root).unwrap());
+        assert!(is_ancestor(&repo, root, child).unwrap());
+        assert!(!is_ancestor(&repo, child, root).unwrap());
+        assert!(!is_ancestor(&repo, unrelated, child).unwrap());
+    }
+
+    #[test]
+    fn missing_is_unrelated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init_bare(tmp.path()).unwrap();
+
+        let root = commit(&repo, &[]);
+        let missing = git2::Oid::hash_object(git2::ObjectType::Commit, b"missing").unwrap();
+
+        assert!(!is_ancestor(&repo, root, missing).unwrap());
+        assert!(!is_ancestor(&repo, missing, root).unwrap());
+    }
+}
@@ -6,7 +6,7 @@
 use crate::git::{
     identities::{self, any::get as get_identity, local::LocalIdentity, Identities},
     refs::{self, Refs},
-    storage::{read::Error as ReadError, ReadOnlyStorage, Storage},
+    storage::{read::Error as ReadError, ReadOnly, ReadOnlyStorage, Storage},
     types::{Namespace, Reference, RefsCategory},
 };
 
@@ -30,7 +30,7 @@ use link_identities::git::{SomeIdentity, Urn};
 
 pub mod error {
     use super::RefsError;
-    use crate::git::identities::Error as IdentitiesError;
+    use crate::git::{identities::Error as IdentitiesError, storage::snapshot};
     use link_identities::git::Urn;
     use thiserror::Error;
 
@@ -50,6 +50,8 @@ pub mod error {
         Cob(#[from] cob::error::Retrieve<RefsError>),
         #[error(transparent)]
         ResolveAuth(#[from] ResolveAuthorizer),
+        #[error(transparent)]
+        Snapshot(#[from] snapshot::Error),
     }
 
     #[allow(clippy::large_enum_variant)]
@@ -138,17 +140,25 @@ impl<'a> CollaborativeObjects<'a> {
         .map_err(error::Create::from)
     }
 
+    /// Load the object `oid`.
+    ///
+    /// The object, and the identity it belongs to, are read from a
+    /// [`crate::git::storage::Snapshot`] of the namespace of `identity_urn`, so
+    /// a concurrent update of the refs is either observed completely, or not at
+    /// all.
     pub fn retrieve(
         &self,
         identity_urn: &Urn,
         typename: &cob::TypeName,
         oid: &cob::ObjectId,
     ) -> Result<Option<cob::CollaborativeObject>, error::Retrieve> {
+        let snapshot = self.store.read_only().snapshot(Some(identity_urn))?;
+        let view = View(&snapshot);
         cob::retrieve(
-            self,
-            &self,
-            self.store.as_raw(),
-            resolve_authorizing_identity(self.store, identity_urn)?.as_ref(),
+            &view,
+            &view,
+            snapshot.as_raw(),
+            resolve_authorizing_identity(&*snapshot, identity_urn)?.as_ref(),
             typename,
             oid,
             self.cache_dir.clone(),
@@ -156,16 +166,21 @@ impl<'a> CollaborativeObjects<'a> {
         .map_err(error::Retrieve::from)
     }
 
+    /// Load all objects of type `typename`.
+    ///
+    /// Like [`Self::retrieve`], this reads from a snapshot of the namespace.
     pub fn list(
         &self,
         identity_urn: &Urn,
         typename: &cob::TypeName,
     ) -> Result<Vec<cob::CollaborativeObject>, error::Retrieve> {
+        let snapshot = self.store.read_only().snapshot(Some(identity_urn))?;
+        let view = View(&snapshot);
         cob::list(
-            self,
-            &self,
-            self.store.as_raw(),
-            resolve_authorizing_identity(self.store, identity_urn)?.as_ref(),
+            &view,
+            &view,
+            snapshot.as_raw(),
+            resolve_authorizing_identity(&*snapshot, identity_urn)?.as_ref(),
             typename,
             self.cache_dir.clone(),
         )
@@ -201,10 +216,11 @@ impl<'a> CollaborativeObjects<'a> {
         typename: &cob::TypeName,
         oid: &cob::ObjectId,
     ) -> Result<Option<ChangeGraphInfo>, error::Retrieve> {
+        let snapshot = self.store.read_only().snapshot(Some(identity_urn))?;
         cob::changegraph_info_for_object(
-            self,
-            self.store.as_raw(),
-            resolve_authorizing_identity(self.store, identity_urn)?.as_ref(),
+            &View(&snapshot),
+            snapshot.as_raw(),
+            resolve_authorizing_identity(&*snapshot, identity_urn)?.as_ref(),
             typename,
             oid,
         )
@@ -220,6 +236,8 @@ pub enum RefsError {
     Read(#[from] ReadError),
     #[error(transparent)]
     Refs(#[from] refs::stored::Error),
+    #[error("cannot update the refs of a read-only view")]
+    ReadOnly,
 }
 
 impl<'a> RefsStorage for CollaborativeObjects<'a> {
@@ -231,19 +249,7 @@ impl<'a> RefsStorage for CollaborativeObjects<'a> {
         typename: &TypeName,
         oid: &ObjectId,
     ) -> Result<cob::ObjectRefs<'b>, Self::Error> {
-        let mut local = None;
-        if let Some(local_ref) = local_ref(self.store, project_urn, typename, oid)? {
-            local = Some(local_ref);
-        }
-        let glob = remote_glob(project_urn, typename, oid);
-        let mut remote = Vec::new();
-        let remote_refs: Vec<git2::Reference> = self
-            .store
-            .references_glob(glob.compile_matcher())?
-            .flatten()
-            .collect();
-        remote.extend(remote_refs);
-        Ok(cob::ObjectRefs { local, remote })
+        object_references(self.store.read_only(), project_urn, typename, oid)
     }
 
     fn type_references<'b>(
@@ -251,32 +257,7 @@ impl<'a> RefsStorage for CollaborativeObjects<'a> {
         project_urn: &Urn,
         typename: &TypeName,
     ) -> Result<HashMap<ObjectId, ObjectRefs<'b>>, Self::Error> {
-        let matcher = ObjRefMatcher::new(project_urn, typename);
-
-        let refs: git2::References<'a> = self.store.as_raw().references()?;
-        let mut result = HashMap::new();
-        for reference in refs {
-            let reference = reference?;
-            if let Some(name) = reference.name() {
-                match matcher.match_ref(name) {
-                    ObjRefMatch::Local(oid) => {
-                        result.entry(oid).or_insert_with(|| ObjectRefs {
-                            local: Some(reference),
-                            remote: Vec::new(),
-                        });
-                    },
-                    ObjRefMatch::Remote(oid) => {
-                        let refs = result.entry(oid).or_insert_with(|| ObjectRefs {
-                            local: None,
-                            remote: Vec::new(),
-                        });
-                        refs.remote.push(reference);
-                    },
-                    ObjRefMatch::NoMatch => {},
-                }
-            }
-        }
-        Ok(result)
+        type_references(self.store.read_only(), project_urn, typename)
     }
 
     fn update_ref(
@@ -303,6 +284,106 @@ impl<'a> RefsStorage for CollaborativeObjects<'a> {
     }
 }
 
+/// Read-only access to the objects of a snapshot of the storage.
+struct View<'a>(&'a ReadOnly);
+
+impl<'a> RefsStorage for View<'a> {
+    type Error = RefsError;
+
+    fn object_references<'b>(
+        &'b self,
+        project_urn: &Urn,
+        typename: &TypeName,
+        oid: &ObjectId,
+    ) -> Result<cob::ObjectRefs<'b>, Self::Error> {
+        object_references(self.0, project_urn, typename, oid)
+    }
+
+    fn type_references<'b>(
+        &'b self,
+        project_urn: &Urn,
+        typename: &TypeName,
+    ) -> Result<HashMap<ObjectId, ObjectRefs<'b>>, Self::Error> {
+        type_references(self.0, project_urn, typename)
+    }
+
+    fn update_ref(
+        &self,
+        _project_urn: &Urn,
+        _typename: &TypeName,
+        _object_id: ObjectId,
+        _new_commit: git2::Oid,
+    ) -> Result<(), Self::Error> {
+        Err(RefsError::ReadOnly)
+    }
+}
+
+impl<'a> IdentityStorage for View<'a> {
+    type Error = git2::Error;
+
+    fn delegate_oid(&self, urn: Urn) -> Result<git2::Oid, Self::Error> {
+        delegate_oid(self.0.as_raw(), urn)
+    }
+}
+
+fn object_references<'a>(
+    store: &'a ReadOnly,
+    project_urn: &Urn,
+    typename: &TypeName,
+    oid: &ObjectId,
+) -> Result<ObjectRefs<'a>, RefsError> {
+    let mut local = None;
+    if let Some(local_ref) = local_ref(store, project_urn, typename, oid)? {
+        local = Some(local_ref);
+    }
+    let glob = remote_glob(project_urn, typename, oid);
+    let mut remote = Vec::new();
+    let remote_refs: Vec<git2::Reference> = store
+        .references_glob(glob.compile_matcher())?
+        .flatten()
+        .collect();
+    remote.extend(remote_refs);
+    Ok(cob::ObjectRefs { local, remote })
+}
+
+fn type_references<'a>(
+    store: &'a ReadOnly,
+    project_urn: &Urn,
+    typename: &TypeName,
+) -> Result<HashMap<ObjectId, ObjectRefs<'a>>, RefsError> {
+    let matcher = ObjRefMatcher::new(project_urn, typename);
+
+    let refs: git2::References<'a> = store.as_raw().references()?;
+    let mut result = HashMap::new();
+    for reference in refs {
+        let reference = reference?;
+        if let Some(name) = reference.name() {
+            match matcher.match_ref(name) {
+                ObjRefMatch::Local(oid) => {
+                    result.entry(oid).or_insert_with(|| ObjectRefs {
+                        local: Some(reference),
+                        remote: Vec::new(),
+                    });
+                },
+                ObjRefMatch::Remote(oid) => {
+                    let refs = result.entry(oid).or_insert_with(|| ObjectRefs {
+                        local: None,
+                        remote: Vec::new(),
+                    });
+                    refs.remote.push(reference);
+                },
+                ObjRefMatch::NoMatch => {},
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn delegate_oid(repo: &git2::Repository, urn: Urn) -> Result<git2::Oid, git2::Error> {
+    let refname = Reference::rad_id(Namespace::from(urn));
+    repo.refname_to_id(&refname.to_string())
+}
+
 fn local_ref<'a, S: ReadOnlyStorage>(
     store: &'a S,
     project_urn: &Urn,
@@ -335,11 +416,14 @@ fn remote_glob(identity_urn: &Urn, typename: &TypeName, oid: &ObjectId) -> globs
     .unwrap()
 }
 
-fn resolve_authorizing_identity(
-    store: &Storage,
+fn resolve_authorizing_identity<S>(
+    store: &S,
     urn: &Urn,
-) -> Result<Box<dyn AuthorizingIdentity>, error::ResolveAuthorizer> {
-    let identities: Identities<'_, SomeIdentity> = Identities::from(store.as_raw());
+) -> Result<Box<dyn AuthorizingIdentity>, error::ResolveAuthorizer>
+where
+    S: AsRef<ReadOnly>,
+{
+    let identities: Identities<'_, SomeIdentity> = store.as_ref().identities();
     let id = get_identity(store, urn)?
         .ok_or_else(|| error::ResolveAuthorizer::NoSuchIdentity { urn: urn.clone() })?;
     match id {
@@ -417,7 +501,6 @@ impl<'a> IdentityStorage for &'a CollaborativeObjects<'a> {
     type Error = git2::Error;
 
     fn delegate_oid(&self, urn: Urn) -> Result<git2::Oid, Self::Error> {
-        let refname = Reference::rad_id(Namespace::from(urn));
-        self.store.as_raw().refname_to_id(&refname.to_string())
+        delegate_oid(self.store.as_raw(), urn)
     }
 }
@@ -3,11 +3,14 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+pub mod bundle;
+pub mod fsck;
 pub mod hooks;
 pub mod identities;
 pub mod include;
 pub mod local;
 pub mod p2p;
+pub mod purge;
 pub mod refs;
 
 pub mod storage;
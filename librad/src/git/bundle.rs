@@ -0,0 +1,621 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Export and import of a [`Urn`] as a git bundle, for offline transfer.
+//!
+//! [`export`] writes the refs of a namespace, including those of all remotes,
+//! to a git bundle, together with the `rad/*` refs of the namespaces of its
+//! delegates. A [`Manifest`] describing the contents is written next to it.
+//!
+//! [`import`] unpacks the objects of a bundle into the storage, and verifies
+//! them before any refs are updated, the same way replication does: the
+//! identities of the [`Urn`] and its delegates must verify, the
+//! `rad/signed_refs` of every peer must carry a valid signature, and the refs
+//! of every peer must match what it signed. Only then are the refs applied as
+//! the remote-tracking refs of the respective peers. Of the namespaces of the
+//! delegates, only `rad/id` is applied, and a bundle containing any other
+//! namespace is rejected.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use either::Either;
+use serde::{Deserialize, Serialize};
+use std_ext::Void;
+use thiserror::Error;
+
+use crate::{
+    git::{
+        refs::{self, Refs},
+        storage::Storage,
+        tracking,
+        types::Namespace,
+        Urn,
+    },
+    git_ext as ext,
+    identities::git::{Person, Project, SomeIdentity, VerifiedPerson, VerifiedProject},
+    PeerId,
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("nothing to export for {0}")]
+    Empty(Urn),
+
+    #[error("`git {cmd}` failed: {stderr}")]
+    Command { cmd: &'static str, stderr: String },
+
+    #[error("the bundle does not match its manifest")]
+    ManifestMismatch,
+
+    #[error("invalid ref `{0}` in bundle")]
+    InvalidRef(String),
+
+    #[error("namespace {0} in bundle is not a delegate")]
+    ForeignNamespace(String),
+
+    #[error("missing rad/id of {0}")]
+    MissingIdentity(Urn),
+
+    #[error("identity {urn} does not verify")]
+    Verification {
+        urn: Urn,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("identity filed under {expected} is {actual}")]
+    UrnMismatch { expected: Urn, actual: Urn },
+
+    #[error("missing rad/signed_refs of delegate {0}")]
+    MissingSignedRefs(PeerId),
+
+    #[error("invalid rad/signed_refs of {peer}")]
+    SignedRefs {
+        peer: PeerId,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("{name} of {peer} is at {actual:?}, but {signed} was signed")]
+    Mismatch {
+        peer: PeerId,
+        name: String,
+        signed: ext::Oid,
+        actual: Option<ext::Oid>,
+    },
+
+    #[error("{name} of {peer} is not signed")]
+    Unsigned { peer: PeerId, name: String },
+
+    #[error("malformed manifest")]
+    Manifest(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Track(#[from] tracking::error::Track),
+
+    #[error(transparent)]
+    Refs(#[from] refs::stored::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Describes the contents of a bundle created by [`export`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Manifest {
+    pub urn: Urn,
+    /// The peer which created the bundle. Its own refs are not qualified by
+    /// `refs/remotes/<peer>` in the bundle.
+    pub peer: PeerId,
+    /// The delegates of `urn`, whose `rad/*` refs are included.
+    ///
+    /// This is informational only: [`import`] determines the delegates from
+    /// the verified identity.
+    pub delegates: BTreeSet<Urn>,
+    /// The namespaced refs in the bundle, and their targets.
+    pub refs: BTreeMap<String, ext::Oid>,
+}
+
+/// The result of [`import`].
+#[derive(Clone, Debug, Default)]
+pub struct Imported {
+    /// The refs which were created or updated.
+    pub updated: Vec<String>,
+    /// Peers which were tracked as a result of the import.
+    pub tracked: Vec<PeerId>,
+}
+
+/// The path of the [`Manifest`] accompanying `bundle`.
+pub fn manifest_path(bundle: &Path) -> PathBuf {
+    bundle.with_extension("json")
+}
+
+/// Write the namespace of `urn` to a git bundle at `out`, and its [`Manifest`]
+/// to [`manifest_path`].
+///
+/// Symbolic refs are not included, they are re-created on [`import`].
+#[tracing::instrument(level = "debug", skip(storage), err)]
+pub fn export(storage: &Storage, urn: &Urn, out: &Path) -> Result<Manifest, Error> {
+    let repo = storage.as_raw();
+    let mut manifest = Manifest {
+        urn: urn.clone(),
+        peer: *storage.peer_id(),
+        delegates: BTreeSet::new(),
+        refs: BTreeMap::new(),
+    };
+
+    for (name, oid) in direct_refs(repo, urn)? {
+        if let Some((_, _, rest)) = parse(&name) {
+            if let Some(id) = rest.strip_prefix("rad/ids/") {
+                let delegate =
+                    Urn::try_from_id(id).map_err(|_| Error::InvalidRef(name.to_owned()))?;
+                manifest.delegates.insert(delegate);
+            }
+        }
+        manifest.refs.insert(name, oid);
+    }
+    if manifest.refs.is_empty() {
+        return Err(Error::Empty(urn.clone()));
+    }
+    for delegate in &manifest.delegates {
+        for (name, oid) in direct_refs(repo, delegate)? {
+            if matches!(parse(&name), Some((_, _, rest)) if rest.starts_with("rad/")) {
+                manifest.refs.insert(name, oid);
+            }
+        }
+    }
+
+    let mut child = Command::new("git")
+        .arg("--git-dir")
+        .arg(storage.path())
+        .args(&["bundle", "create"])
+        .arg(out)
+        .arg("--stdin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    {
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        for name in manifest.refs.keys() {
+            writeln!(stdin, "{}", name)?;
+        }
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::Command {
+            cmd: "bundle create",
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    fs::write(manifest_path(out), serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok(manifest)
+}
+
+/// Import a bundle created by [`export`] into `storage`.
+///
+/// The objects of the bundle are added to the storage, but refs are only
+/// updated after verifying the identities and signed refs the bundle
+/// contains. The refs of the local peer contained in the bundle are ignored,
+/// the refs of all other peers are written as their remote-tracking refs,
+/// replacing the existing ones. In the namespaces of delegates, only their
+/// `rad/id` is written. `rad/id` and `rad/ids/*` are set up if they don't exist
+/// yet, and the peers are tracked.
+///
+/// Only the namespace of the [`Manifest::urn`] and those of its delegates, as
+/// determined by the verified identity, may be contained in the bundle.
+#[tracing::instrument(level = "debug", skip(storage), err)]
+pub fn import(storage: &Storage, bundle: &Path) -> Result<Imported, Error> {
+    let manifest: Manifest = serde_json::from_slice(&fs::read(manifest_path(bundle))?)?;
+    git(
+        storage,
+        "bundle verify",
+        &["bundle", "verify", "--quiet"],
+        bundle,
+    )?;
+    if unbundle(storage, bundle)? != manifest.refs {
+        return Err(Error::ManifestMismatch);
+    }
+    let views = Views::new(&manifest)?;
+    let local = *storage.peer_id();
+    let namespace = Namespace::from(&manifest.urn).to_string();
+
+    // Verify the identity of the urn, resolving its delegates from the other
+    // namespaces of the bundle
+    let mut candidates = BTreeMap::new();
+    for ns in views.namespaces().filter(|ns| **ns != namespace) {
+        if let Ok(urn) = Urn::try_from_id(ns) {
+            if let Some(tip) = views.tip(&manifest, &urn) {
+                candidates.insert(urn, tip);
+            }
+        }
+    }
+    let mut anchor = None;
+    for (peer, oid) in views.rad_ids(&manifest.urn) {
+        let verified = verify(storage, &manifest.urn, oid, &candidates)?;
+        if anchor.is_none() || peer == manifest.peer {
+            anchor = Some((oid, verified));
+        }
+    }
+    let (anchor, (delegate_ids, delegate_urns)) =
+        anchor.ok_or_else(|| Error::MissingIdentity(manifest.urn.clone()))?;
+
+    // Nothing but the namespaces of the delegates may come along
+    for ns in views.namespaces().filter(|ns| **ns != namespace) {
+        let is_delegate = Urn::try_from_id(ns)
+            .map(|urn| delegate_urns.contains(&urn))
+            .unwrap_or(false);
+        if !is_delegate {
+            return Err(Error::ForeignNamespace(ns.clone()));
+        }
+    }
+
+    // Verify the identities of the delegates as seen by every peer
+    let mut delegates = BTreeMap::new();
+    for delegate in &delegate_urns {
+        let ids = views.rad_ids(delegate);
+        let mut tip = None;
+        for (peer, oid) in &ids {
+            let person = storage
+                .read_only()
+                .identities::<Person>()
+                .verify(**oid)
+                .map_err(|e| Error::Verification {
+                    urn: delegate.clone(),
+                    source: Box::new(e),
+                })?;
+            ensure_urn(delegate, person.urn())?;
+            if tip.is_none() || *peer == manifest.peer {
+                tip = Some(*oid);
+            }
+        }
+        let tip = tip.ok_or_else(|| Error::MissingIdentity(delegate.clone()))?;
+        delegates.insert(delegate.clone(), tip);
+    }
+
+    // Verify the signed refs of all peers
+    let peers = views.peers(&manifest.urn);
+    for delegate in &delegate_ids {
+        if *delegate != local && !peers.contains_key(delegate) {
+            return Err(Error::MissingSignedRefs(*delegate));
+        }
+    }
+    for (peer, refs) in &peers {
+        if *peer != local {
+            verify_signed(storage, &manifest.urn, *peer, refs)?;
+        }
+    }
+
+    // Apply
+    let repo = storage.as_raw();
+    let mut imported = Imported::default();
+    for (name, oid) in &manifest.refs {
+        let (ns, peer, rest) = owner(&manifest, name)?;
+        if peer == local || (ns != namespace && rest != "rad/id") {
+            continue;
+        }
+        let target = format!("refs/namespaces/{}/refs/remotes/{}/{}", ns, peer, rest);
+        repo.reference(&target, **oid, true, "import from bundle")?;
+        imported.updated.push(target);
+    }
+
+    let rad_id = |urn: &Urn| format!("refs/namespaces/{}/refs/rad/id", Namespace::from(urn));
+    for (urn, tip) in delegates
+        .iter()
+        .chain(std::iter::once((&manifest.urn, &anchor)))
+    {
+        let name = rad_id(urn);
+        if repo.find_reference(&name).is_err() {
+            repo.reference(&name, **tip, false, "import from bundle")?;
+            imported.updated.push(name);
+        }
+    }
+    for delegate in delegates.keys() {
+        let name = format!(
+            "refs/namespaces/{}/refs/rad/ids/{}",
+            Namespace::from(&manifest.urn),
+            delegate.encode_id()
+        );
+        if repo.find_reference(&name).is_err() {
+            repo.reference_symbolic(&name, &rad_id(delegate), false, "import from bundle")?;
+            imported.updated.push(name);
+        }
+    }
+
+    for peer in peers.keys().filter(|peer| **peer != local) {
+        let tracked = tracking::track(
+            storage,
+            &manifest.urn,
+            Some(*peer),
+            tracking::Config::default(),
+            tracking::policy::Track::MustNotExist,
+        )?;
+        if tracked.is_ok() {
+            imported.tracked.push(*peer);
+        }
+    }
+    for urn in delegates.keys().chain(std::iter::once(&manifest.urn)) {
+        Refs::update(storage, urn)?;
+    }
+
+    Ok(imported)
+}
+
+/// The refs of a bundle, by namespace and peer.
+struct Views(BTreeMap<String, BTreeMap<PeerId, BTreeMap<String, ext::Oid>>>);
+
+impl Views {
+    fn new(manifest: &Manifest) -> Result<Self, Error> {
+        let mut views: BTreeMap<_, BTreeMap<_, BTreeMap<_, _>>> = BTreeMap::new();
+        for (name, oid) in &manifest.refs {
+            let (namespace, peer, rest) = owner(manifest, name)?;
+            views
+                .entry(namespace.to_owned())
+                .or_default()
+                .entry(peer)
+                .or_default()
+                .insert(rest.to_owned(), *oid);
+        }
+        Ok(Self(views))
+    }
+
+    fn namespaces(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    fn peers(&self, urn: &Urn) -> BTreeMap<PeerId, BTreeMap<String, ext::Oid>> {
+        self.0
+            .get(&Namespace::from(urn).to_string())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn rad_ids(&self, urn: &Urn) -> Vec<(PeerId, ext::Oid)> {
+        self.peers(urn)
+            .into_iter()
+            .filter_map(|(peer, refs)| refs.get("rad/id").map(|oid| (peer, *oid)))
+            .collect()
+    }
+
+    /// The `rad/id` of `urn`, preferring the one of the [`Manifest::peer`].
+    fn tip(&self, manifest: &Manifest, urn: &Urn) -> Option<ext::Oid> {
+        let ids = self.rad_ids(urn);
+        ids.iter()
+            .find(|(peer, _)| *peer == manifest.peer)
+            .or_else(|| ids.first())
+            .map(|(_, oid)| *oid)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("missing delegate {0}")]
+struct MissingDelegate(Urn);
+
+/// Verify the identity at `tip`, returning the [`PeerId`]s of its delegates,
+/// and the [`Urn`]s of its indirect delegates.
+///
+/// The identity must be the one of `urn`, lest the `rad/id` of another
+/// identity is passed off as the one of `urn`.
+fn verify(
+    storage: &Storage,
+    urn: &Urn,
+    tip: ext::Oid,
+    delegates: &BTreeMap<Urn, ext::Oid>,
+) -> Result<(BTreeSet<PeerId>, BTreeSet<Urn>), Error> {
+    let failed = |e: BoxError| Error::Verification {
+        urn: urn.clone(),
+        source: e,
+    };
+    let ids = storage.read_only();
+    match ids
+        .identities::<Void>()
+        .some_identity(tip.into())
+        .map_err(|e| failed(Box::new(e)))?
+    {
+        SomeIdentity::Person(_) => {
+            let person: VerifiedPerson = ids
+                .identities::<Person>()
+                .verify(tip.into())
+                .map_err(|e| failed(Box::new(e)))?;
+            ensure_urn(urn, person.urn())?;
+            let ids = person
+                .delegations()
+                .iter()
+                .map(|pk| PeerId::from(*pk))
+                .collect();
+            Ok((ids, BTreeSet::new()))
+        },
+        SomeIdentity::Project(_) => {
+            let project: VerifiedProject = ids
+                .identities::<Project>()
+                .verify(tip.into(), |urn| {
+                    delegates
+                        .get(&urn)
+                        .map(|oid| **oid)
+                        .ok_or(MissingDelegate(urn))
+                })
+                .map_err(|e| failed(Box::new(e)))?;
+            ensure_urn(urn, project.urn())?;
+            let ids = project
+                .delegations()
+                .iter()
+                .flat_map(|delegate| match delegate {
+                    Either::Left(pk) => vec![PeerId::from(*pk)],
+                    Either::Right(person) => person
+                        .delegations()
+                        .iter()
+                        .map(|pk| PeerId::from(*pk))
+                        .collect(),
+                })
+                .collect();
+            let urns = project
+                .delegations()
+                .iter()
+                .filter_map(|delegate| match delegate {
+                    Either::Left(_) => None,
+                    Either::Right(person) => Some(person.urn()),
+                })
+                .collect();
+            Ok((ids, urns))
+        },
+        _ => Err(failed("unknown identity kind".into())),
+    }
+}
+
+fn ensure_urn(expected: &Urn, actual: Urn) -> Result<(), Error> {
+    if actual.id == expected.id {
+        Ok(())
+    } else {
+        Err(Error::UrnMismatch {
+            expected: expected.clone(),
+            actual,
+        })
+    }
+}
+
+/// Verify the `rad/signed_refs` of `peer`, and that `refs` match what was
+/// signed.
+fn verify_signed(
+    storage: &Storage,
+    urn: &Urn,
+    peer: PeerId,
+    refs: &BTreeMap<String, ext::Oid>,
+) -> Result<(), Error> {
+    let at = match refs.get("rad/signed_refs") {
+        Some(at) => *at,
+        None => {
+            return match refs.keys().find(|name| !name.starts_with("rad/")) {
+                Some(name) => Err(Error::Unsigned {
+                    peer,
+                    name: name.clone(),
+                }),
+                None => Ok(()),
+            }
+        },
+    };
+    let signed = refs::load_at(storage, urn, at, Some(&peer))
+        .map_err(|e| Error::SignedRefs {
+            peer,
+            source: Box::new(e),
+        })?
+        .ok_or_else(|| Error::SignedRefs {
+            peer,
+            source: "missing signed refs blob".into(),
+        })?
+        .refs;
+
+    let mut names = BTreeSet::new();
+    for ((name, oid), category) in signed.iter_categorised() {
+        let name = format!("{}/{}", category, name.as_str());
+        let actual = refs.get(&name).copied();
+        if actual != Some(*oid) {
+            return Err(Error::Mismatch {
+                peer,
+                name,
+                signed: *oid,
+                actual,
+            });
+        }
+        names.insert(name);
+    }
+    match refs
+        .keys()
+        .find(|name| !name.starts_with("rad/") && !names.contains(*name))
+    {
+        Some(name) => Err(Error::Unsigned {
+            peer,
+            name: name.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Parse a ref of the bundle into its namespace, the peer it belongs to, and
+/// the remainder of the name, eg. `heads/main`.
+fn owner<'a>(manifest: &Manifest, name: &'a str) -> Result<(&'a str, PeerId, &'a str), Error> {
+    let (namespace, peer, rest) = parse(name).ok_or_else(|| Error::InvalidRef(name.to_owned()))?;
+    let peer = match peer {
+        None => manifest.peer,
+        Some(peer) => peer
+            .parse()
+            .map_err(|_| Error::InvalidRef(name.to_owned()))?,
+    };
+    Ok((namespace, peer, rest))
+}
+
+/// Parse `refs/namespaces/<namespace>/refs/[remotes/<peer>/]<rest>`.
+fn parse(name: &str) -> Option<(&str, Option<&str>, &str)> {
+    let (namespace, rest) = name.strip_prefix("refs/namespaces/")?.split_once('/')?;
+    let rest = rest.strip_prefix("refs/")?;
+    match rest.strip_prefix("remotes/") {
+        Some(remote) => {
+            let (peer, rest) = remote.split_once('/')?;
+            Some((namespace, Some(peer), rest))
+        },
+        None => Some((namespace, None, rest)),
+    }
+}
+
+/// The direct refs in the namespace of `urn`.
+fn direct_refs(repo: &git2::Repository, urn: &Urn) -> Result<Vec<(String, ext::Oid)>, Error> {
+    let glob = format!("refs/namespaces/{}/*", Namespace::from(urn));
+    let mut refs = Vec::new();
+    for reference in repo.references_glob(&glob)? {
+        let reference = reference?;
+        if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+            refs.push((name.to_owned(), oid.into()));
+        }
+    }
+    Ok(refs)
+}
+
+/// Add the objects of `bundle` to the storage, returning the refs it contains.
+fn unbundle(storage: &Storage, bundle: &Path) -> Result<BTreeMap<String, ext::Oid>, Error> {
+    let stdout = git(storage, "bundle unbundle", &["bundle", "unbundle"], bundle)?;
+    String::from_utf8_lossy(&stdout)
+        .lines()
+        .map(|line| {
+            let (oid, name) = line
+                .split_once(' ')
+                .ok_or_else(|| Error::InvalidRef(line.to_owned()))?;
+            let oid = git2::Oid::from_str(oid)?;
+            Ok((name.to_owned(), oid.into()))
+        })
+        .collect()
+}
+
+fn git(
+    storage: &Storage,
+    cmd: &'static str,
+    args: &[&str],
+    bundle: &Path,
+) -> Result<Vec<u8>, Error> {
+    let out = Command::new("git")
+        .arg("--git-dir")
+        .arg(storage.path())
+        .args(args)
+        .arg(bundle)
+        .stdin(Stdio::null())
+        .output()?;
+    if out.status.success() {
+        Ok(out.stdout)
+    } else {
+        Err(Error::Command {
+            cmd,
+            stderr: String::from_utf8_lossy(&out.stderr).into_owned(),
+        })
+    }
+}
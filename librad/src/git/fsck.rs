@@ -0,0 +1,539 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Verify the radicle-specific invariants of the storage.
+//!
+//! `git fsck` ensures the object database is sound, but knows nothing about
+//! identities, signed refs, or tracking. [`check`] walks every namespace of
+//! the storage and reports the [`Problem`]s it finds. [`repair`] prunes
+//! whatever was found to be invalid, such that it can be fetched again from
+//! the network.
+
+use std::{collections::BTreeSet, error, fmt};
+
+use git_ext::is_not_found_err;
+use thiserror::Error;
+
+use crate::{
+    git::{
+        identities::{self, SomeIdentity},
+        refs::{self, Refs},
+        storage::Storage,
+        tracking::{self, git::odb::Read as _, reference::RefName},
+        types::Namespace,
+        Urn,
+    },
+    git_ext as ext,
+    PeerId,
+};
+
+const NAMESPACES: &str = "refs/namespaces/";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error(transparent)]
+    Refs(#[from] refs::stored::Error),
+}
+
+/// An inconsistency found by [`check`].
+///
+/// Where a `peer` is optional, `None` refers to the local peer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    /// The namespace does not decode as a [`Urn`].
+    InvalidNamespace { namespace: String },
+    /// The namespace has no `rad/id`.
+    MissingIdentity { urn: Urn },
+    /// The `rad/id` of the namespace does not verify.
+    InvalidIdentity { urn: Urn, reason: String },
+    /// A remote of the namespace is not named by a [`PeerId`].
+    InvalidRemote { urn: Urn, remote: String },
+    /// The `rad/signed_refs` could not be loaded, or the signature is invalid.
+    InvalidSignedRefs {
+        urn: Urn,
+        peer: Option<PeerId>,
+        reason: String,
+    },
+    /// A remote has refs, but no `rad/signed_refs`.
+    MissingSignedRefs { urn: Urn, peer: PeerId },
+    /// A signed ref of a remote is missing, or does not point to the signed
+    /// object.
+    RefMismatch {
+        urn: Urn,
+        peer: PeerId,
+        name: String,
+        signed: ext::Oid,
+        actual: Option<ext::Oid>,
+    },
+    /// The `rad/self` ref does not resolve to a valid person.
+    DanglingSelf {
+        urn: Urn,
+        peer: Option<PeerId>,
+        reason: String,
+    },
+    /// A tracking entry has a malformed name, or its configuration does not
+    /// parse.
+    InvalidTracking { name: String, reason: String },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn whose(peer: &Option<PeerId>) -> String {
+            peer.map(|peer| format!(" of {}", peer)).unwrap_or_default()
+        }
+
+        match self {
+            Self::InvalidNamespace { namespace } => {
+                write!(f, "namespace `{}` is not a valid URN", namespace)
+            },
+            Self::MissingIdentity { urn } => write!(f, "{}: missing rad/id", urn),
+            Self::InvalidIdentity { urn, reason } => {
+                write!(f, "{}: rad/id does not verify: {}", urn, reason)
+            },
+            Self::InvalidRemote { urn, remote } => {
+                write!(f, "{}: remote `{}` is not a valid peer id", urn, remote)
+            },
+            Self::InvalidSignedRefs { urn, peer, reason } => write!(
+                f,
+                "{}: invalid rad/signed_refs{}: {}",
+                urn,
+                whose(peer),
+                reason
+            ),
+            Self::MissingSignedRefs { urn, peer } => {
+                write!(f, "{}: missing rad/signed_refs of {}", urn, peer)
+            },
+            Self::RefMismatch {
+                urn,
+                peer,
+                name,
+                signed,
+                actual,
+            } => match actual {
+                Some(actual) => write!(
+                    f,
+                    "{}: {} of {} is at {}, but {} was signed",
+                    urn, name, peer, actual, signed
+                ),
+                None => write!(
+                    f,
+                    "{}: {} of {} is missing, but {} was signed",
+                    urn, name, peer, signed
+                ),
+            },
+            Self::DanglingSelf { urn, peer, reason } => {
+                write!(f, "{}: dangling rad/self{}: {}", urn, whose(peer), reason)
+            },
+            Self::InvalidTracking { name, reason } => {
+                write!(f, "invalid tracking entry `{}`: {}", name, reason)
+            },
+        }
+    }
+}
+
+/// The result of [`check`].
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// Number of namespaces checked.
+    pub namespaces: usize,
+    /// Number of tracking entries checked.
+    pub tracking: usize,
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// What was pruned by [`repair`].
+#[derive(Clone, Debug, Default)]
+pub struct Repaired {
+    /// URNs without a valid identity, whose remote-tracking refs were
+    /// deleted.
+    pub unverified: Vec<Urn>,
+    /// Names of the refs deleted.
+    pub refs: Vec<String>,
+    /// URNs for which the local `rad/signed_refs` were re-computed.
+    pub resigned: Vec<Urn>,
+}
+
+/// Check every namespace of `storage`, and all tracking entries.
+///
+/// For each namespace, `rad/id` must verify as either a person or a project.
+/// The `rad/signed_refs` of the local peer and all remotes must carry a valid
+/// signature, and the refs of remotes must match what they signed. If a
+/// `rad/self` is present, it must resolve to a valid person.
+///
+/// Note that the local refs are not compared against the local
+/// `rad/signed_refs`, as it is perfectly normal for them to diverge until the
+/// next [`Refs::update`].
+///
+/// Only errors accessing the storage itself are returned as [`Error`]s, every
+/// inconsistency is recorded as a [`Problem`] in the [`Report`].
+#[tracing::instrument(level = "debug", skip(storage), err)]
+pub fn check(storage: &Storage) -> Result<Report, Error> {
+    let mut report = Report::default();
+    for namespace in namespaces(storage)? {
+        report.namespaces += 1;
+        match Urn::try_from_id(&namespace) {
+            Ok(urn) => check_namespace(storage, &urn, &mut report.problems)?,
+            Err(_) => report
+                .problems
+                .push(Problem::InvalidNamespace { namespace }),
+        }
+    }
+    check_tracking(storage, &mut report)?;
+
+    Ok(report)
+}
+
+/// Prune everything found to be invalid by [`check`].
+///
+/// * Namespaces without a valid identity have all their remote-tracking refs
+///   deleted. Local branches and tracking entries are kept, so the identity can
+///   be fetched again.
+/// * Remotes with invalid or missing `rad/signed_refs` are deleted (but stay
+///   tracked), so they are fetched again on the next replication.
+/// * Refs of remotes not matching what was signed are deleted individually,
+///   leaving the rest of the remote alone. A mismatch alone is not unusual,
+///   e.g. if some refs were excluded by a quota, so this is rather mild.
+/// * Invalid local `rad/signed_refs` are re-computed and signed.
+/// * Dangling `rad/self` refs and invalid tracking entries are deleted.
+#[tracing::instrument(level = "debug", skip(storage, report), err)]
+pub fn repair(storage: &Storage, report: &Report) -> Result<Repaired, Error> {
+    let mut repaired = Repaired::default();
+    let mut resign = BTreeSet::new();
+    for problem in &report.problems {
+        match problem {
+            Problem::InvalidNamespace { namespace } => {
+                let prefix = format!("{}{}/", NAMESPACES, namespace);
+                repaired.refs.extend(delete_prefix(storage, &prefix)?)
+            },
+            Problem::MissingIdentity { urn } | Problem::InvalidIdentity { urn, .. } => {
+                let prefix = remotes_prefix(urn);
+                repaired.refs.extend(delete_prefix(storage, &prefix)?);
+                repaired.unverified.push(urn.clone());
+            },
+            Problem::InvalidRemote { urn, remote } => {
+                let prefix = remote_prefix(urn, remote);
+                repaired.refs.extend(delete_prefix(storage, &prefix)?)
+            },
+            Problem::InvalidSignedRefs {
+                urn, peer: None, ..
+            } => {
+                resign.insert(urn.clone());
+            },
+            Problem::InvalidSignedRefs {
+                urn,
+                peer: Some(peer),
+                ..
+            }
+            | Problem::MissingSignedRefs { urn, peer } => {
+                let prefix = remote_prefix(urn, &peer.to_string());
+                repaired.refs.extend(delete_prefix(storage, &prefix)?)
+            },
+            Problem::RefMismatch {
+                urn,
+                peer,
+                name,
+                actual: Some(_),
+                ..
+            } => {
+                let name = format!("{}{}", remote_prefix(urn, &peer.to_string()), name);
+                if delete_ref(storage, &name)? {
+                    repaired.refs.push(name)
+                }
+            },
+            Problem::RefMismatch { actual: None, .. } => {},
+            Problem::DanglingSelf { urn, peer, .. } => {
+                let name = rad_self(urn, *peer);
+                if delete_ref(storage, &name)? {
+                    repaired.refs.push(name)
+                }
+            },
+            Problem::InvalidTracking { name, .. } => {
+                if delete_ref(storage, name)? {
+                    repaired.refs.push(name.clone())
+                }
+            },
+        }
+    }
+
+    // Computing the signed refs loads the signed refs of all tracked remotes,
+    // so this must happen after those have been pruned.
+    for urn in resign {
+        if repaired.unverified.contains(&urn) {
+            continue;
+        }
+        Refs::update(storage, &urn)?;
+        repaired.resigned.push(urn);
+    }
+
+    Ok(repaired)
+}
+
+fn check_namespace(storage: &Storage, urn: &Urn, problems: &mut Vec<Problem>) -> Result<(), Error> {
+    let verified = match identities::any::get(storage, urn) {
+        Ok(Some(SomeIdentity::Person(_))) => identities::person::verify(storage, urn)
+            .map(|person| person.map(drop))
+            .map_err(|e| reason(&e)),
+        Ok(Some(SomeIdentity::Project(_))) => identities::project::verify(storage, urn)
+            .map(|project| project.map(drop))
+            .map_err(|e| reason(&e)),
+        Ok(None) => Ok(None),
+        Err(e) => Err(reason(&e)),
+    };
+    match verified {
+        Ok(Some(())) => {},
+        Ok(None) => {
+            problems.push(Problem::MissingIdentity { urn: urn.clone() });
+            return Ok(());
+        },
+        Err(reason) => {
+            problems.push(Problem::InvalidIdentity {
+                urn: urn.clone(),
+                reason,
+            });
+            return Ok(());
+        },
+    }
+
+    if let Err(e) = Refs::load(storage, urn, None::<PeerId>) {
+        problems.push(Problem::InvalidSignedRefs {
+            urn: urn.clone(),
+            peer: None,
+            reason: reason(&e),
+        })
+    }
+    check_self(storage, urn, None, problems)?;
+
+    for remote in remotes(storage, urn)? {
+        let peer = match remote.parse::<PeerId>() {
+            Ok(peer) => peer,
+            Err(_) => {
+                problems.push(Problem::InvalidRemote {
+                    urn: urn.clone(),
+                    remote,
+                });
+                continue;
+            },
+        };
+        match Refs::load(storage, urn, peer) {
+            Ok(Some(signed)) => check_signed(storage, urn, peer, &signed, problems)?,
+            Ok(None) => problems.push(Problem::MissingSignedRefs {
+                urn: urn.clone(),
+                peer,
+            }),
+            Err(e) => problems.push(Problem::InvalidSignedRefs {
+                urn: urn.clone(),
+                peer: Some(peer),
+                reason: reason(&e),
+            }),
+        }
+        check_self(storage, urn, Some(peer), problems)?;
+    }
+
+    Ok(())
+}
+
+fn check_signed(
+    storage: &Storage,
+    urn: &Urn,
+    peer: PeerId,
+    signed: &Refs,
+    problems: &mut Vec<Problem>,
+) -> Result<(), Error> {
+    let prefix = remote_prefix(urn, &peer.to_string());
+    for (category, refs) in &signed.categorised_refs {
+        for (name, oid) in refs {
+            let name = format!("{}/{}", category, name);
+            let actual = match storage
+                .as_raw()
+                .refname_to_id(&format!("{}{}", prefix, name))
+            {
+                Ok(actual) => Some(ext::Oid::from(actual)),
+                Err(e) if is_not_found_err(&e) => None,
+                Err(e) => return Err(e.into()),
+            };
+            if actual.as_ref() != Some(oid) {
+                problems.push(Problem::RefMismatch {
+                    urn: urn.clone(),
+                    peer,
+                    name,
+                    signed: *oid,
+                    actual,
+                })
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_self(
+    storage: &Storage,
+    urn: &Urn,
+    peer: Option<PeerId>,
+    problems: &mut Vec<Problem>,
+) -> Result<(), Error> {
+    let name = rad_self(urn, peer);
+    match storage.as_raw().find_reference(&name) {
+        Ok(_) => {},
+        Err(e) if is_not_found_err(&e) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+
+    let path = match peer {
+        None => reflike!("refs/rad/self"),
+        Some(peer) => reflike!("refs/remotes")
+            .join(peer)
+            .join(reflike!("rad/self")),
+    };
+    let reason = match identities::person::verify(storage, &urn.clone().with_path(path)) {
+        Ok(Some(_)) => return Ok(()),
+        Ok(None) => "target not found".to_owned(),
+        Err(e) => reason(&e),
+    };
+    problems.push(Problem::DanglingSelf {
+        urn: urn.clone(),
+        peer,
+        reason,
+    });
+
+    Ok(())
+}
+
+fn check_tracking(storage: &Storage, report: &mut Report) -> Result<(), Error> {
+    let glob = format!("{}/*", tracking::reference::base().as_str());
+    for reference in storage.as_raw().references_glob(&glob)? {
+        let reference = reference?;
+        let name = match reference.name() {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        report.tracking += 1;
+
+        let invalid = if let Err(e) = name.parse::<RefName<'_, ext::Oid>>() {
+            Some(reason(&e))
+        } else {
+            match reference.target() {
+                None => Some("symbolic ref".to_owned()),
+                Some(target) => match storage.find_config(&target.into()) {
+                    Ok(Some(_)) => None,
+                    Ok(None) => Some("configuration not found".to_owned()),
+                    Err(e) => Some(reason(&e)),
+                },
+            }
+        };
+        if let Some(reason) = invalid {
+            report
+                .problems
+                .push(Problem::InvalidTracking { name, reason })
+        }
+    }
+
+    Ok(())
+}
+
+/// The distinct namespaces of `storage`, in their encoded form.
+fn namespaces(storage: &Storage) -> Result<BTreeSet<String>, Error> {
+    let mut namespaces = BTreeSet::new();
+    for name in storage
+        .as_raw()
+        .references_glob(&format!("{}*", NAMESPACES))?
+        .names()
+    {
+        if let Some(namespace) = name?
+            .strip_prefix(NAMESPACES)
+            .and_then(|rest| rest.split('/').next())
+        {
+            namespaces.insert(namespace.to_owned());
+        }
+    }
+
+    Ok(namespaces)
+}
+
+/// The distinct remotes of `urn`, unparsed.
+fn remotes(storage: &Storage, urn: &Urn) -> Result<BTreeSet<String>, Error> {
+    let prefix = format!("{}{}/refs/remotes/", NAMESPACES, Namespace::from(urn));
+    let mut remotes = BTreeSet::new();
+    for name in storage
+        .as_raw()
+        .references_glob(&format!("{}*", prefix))?
+        .names()
+    {
+        if let Some(remote) = name?
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split('/').next())
+        {
+            remotes.insert(remote.to_owned());
+        }
+    }
+
+    Ok(remotes)
+}
+
+fn remotes_prefix(urn: &Urn) -> String {
+    format!("{}{}/refs/remotes/", NAMESPACES, Namespace::from(urn))
+}
+
+fn remote_prefix(urn: &Urn, remote: &str) -> String {
+    format!("{}{}/", remotes_prefix(urn), remote)
+}
+
+fn rad_self(urn: &Urn, peer: Option<PeerId>) -> String {
+    match peer {
+        None => format!("{}{}/refs/rad/self", NAMESPACES, Namespace::from(urn)),
+        Some(peer) => format!("{}rad/self", remote_prefix(urn, &peer.to_string())),
+    }
+}
+
+/// Delete all refs starting with `prefix`, returning their names.
+fn delete_prefix(storage: &Storage, prefix: &str) -> Result<Vec<String>, Error> {
+    let names = storage
+        .as_raw()
+        .references_glob(&format!("{}*", prefix))?
+        .names()
+        .map(|name| name.map(ToOwned::to_owned))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut deleted = Vec::with_capacity(names.len());
+    for name in names {
+        if delete_ref(storage, &name)? {
+            deleted.push(name)
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Returns `true` if the ref existed.
+fn delete_ref(storage: &Storage, name: &str) -> Result<bool, Error> {
+    match storage.as_raw().find_reference(name) {
+        Ok(mut r) => {
+            r.delete()?;
+            tracing::trace!(name = %name, "deleted");
+            Ok(true)
+        },
+        Err(e) if is_not_found_err(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Render `e` including its sources, as it ends up in a [`Problem`].
+fn reason(e: &dyn error::Error) -> String {
+    let mut reason = e.to_string();
+    let mut source = e.source();
+    while let Some(e) = source {
+        reason.push_str(": ");
+        reason.push_str(&e.to_string());
+        source = e.source();
+    }
+    reason
+}
@@ -4,6 +4,9 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 pub mod any;
+pub mod attestation;
+pub mod device;
+pub mod diff;
 pub mod error;
 pub mod local;
 pub mod person;
@@ -0,0 +1,441 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Attestations of [`Person`]s by other [`Person`]s.
+//!
+//! An [`Attestation`] is a statement by an `attester` that it vouches for a
+//! `subject`, eg. "this URN is my colleague". It is signed by one of the keys
+//! the attester delegates to, and stored under the attester's namespace at:
+//!
+//! ```text
+//! refs/namespaces/<attester>/refs/rad/attestations/<subject>
+//! ```
+//!
+//! The ref points to a commit, whose tree contains a single blob with the JSON
+//! encoding of the [`Signed`] attestation. Being a `rad` ref, it is covered by
+//! the `rad/signed_refs` of the attester's namespace, and is thus replicated
+//! along with the attester's identity. Attestations made on other devices of
+//! the attester are found under `refs/remotes/<peer>/rad/attestations/`.
+//!
+//! Withdrawing an attestation replaces it with a signed tombstone, ie. an
+//! [`Attestation`] marked as `withdrawn`. Copies of the attestation replicated
+//! from other peers are thereby superseded, as only the most recent statement
+//! of the attester about a `subject` is taken into account.
+//!
+//! An attestation is only considered valid if it is signed by a key the
+//! attester delegates to. If that key was revoked since, the attestation
+//! remains valid only if its commit is covered by the revocation (see
+//! [`Person::revocations`]).
+//!
+//! [`Person`]: super::Person
+//! [`Person::revocations`]: super::Person::revocations
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use git_ext as ext;
+use link_canonical::{Cjson, CjsonError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    super::{
+        refs::{self, Refs},
+        storage::{self, ReadOnlyStorage as _, Storage},
+        types::{Namespace, Reference},
+    },
+    any,
+    local::LocalIdentity,
+    person,
+    project,
+    Urn,
+};
+use crate::{identities::SomeIdentity, PeerId, PublicKey, Signature, Signer as _};
+
+const BLOB_PATH: &str = "attestation";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("the attester {0} could not be found")]
+    UnknownAttester(Urn),
+
+    #[error("attestation by {attester} found in the namespace of {namespace}")]
+    Namespace { attester: Urn, namespace: Urn },
+
+    #[error("invalid signature on attestation of {subject} by {attester}")]
+    InvalidSignature { attester: Urn, subject: Urn },
+
+    #[error("{key} is not a delegate of {attester}")]
+    NotADelegate { attester: Urn, key: PublicKey },
+
+    #[error("{key} of {attester} was revoked, and does not cover the attestation at {at}")]
+    Revoked {
+        attester: Urn,
+        key: PublicKey,
+        at: git2::Oid,
+    },
+
+    #[error("no attestation found at {0}")]
+    Missing(git2::Oid),
+
+    #[error("failed to sign attestation")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error(transparent)]
+    Identities(#[from] Box<super::Error>),
+
+    #[error(transparent)]
+    Sigrefs(#[from] refs::stored::Error),
+
+    #[error(transparent)]
+    Storage(#[from] storage::Error),
+
+    #[error(transparent)]
+    Cjson(#[from] CjsonError),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl From<super::Error> for Error {
+    fn from(e: super::Error) -> Self {
+        Self::Identities(Box::new(e))
+    }
+}
+
+/// The statement that `attester` vouches for `subject`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attestation {
+    /// The [`Person`] making the statement.
+    pub attester: Urn,
+    /// The [`Person`] being vouched for.
+    pub subject: Urn,
+    /// A free-form note, eg. describing the relationship to the `subject`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// The time the attestation was made, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Whether this is a tombstone, withdrawing any earlier attestation of
+    /// `subject` by `attester`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub withdrawn: bool,
+}
+
+impl Attestation {
+    fn canonical_form(&self) -> Result<Vec<u8>, CjsonError> {
+        Cjson(self).canonical_form()
+    }
+
+    /// Whether `self` supersedes `other`, ie. was made later.
+    ///
+    /// A withdrawal made in the same second as an attestation supersedes it.
+    fn supersedes(&self, other: &Self) -> bool {
+        self.timestamp > other.timestamp
+            || (self.timestamp == other.timestamp && self.withdrawn && !other.withdrawn)
+    }
+}
+
+/// An [`Attestation`] along with the signature over its canonical JSON form,
+/// and the key which made it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signed {
+    pub attestation: Attestation,
+    pub key: PublicKey,
+    pub signature: Signature,
+}
+
+impl Signed {
+    /// Verify that the signature is valid, and made by a key of the (verified)
+    /// attester, which was either not revoked, or whose revocation retains the
+    /// validity of the commit `at` the attestation was loaded from.
+    pub fn verify<S>(&self, storage: &S, at: git2::Oid) -> Result<(), Error>
+    where
+        S: AsRef<storage::ReadOnly>,
+    {
+        let Attestation {
+            attester, subject, ..
+        } = &self.attestation;
+        if !self
+            .signature
+            .verify(&self.attestation.canonical_form()?, &self.key)
+        {
+            return Err(Error::InvalidSignature {
+                attester: attester.clone(),
+                subject: subject.clone(),
+            });
+        }
+
+        let person = person::verify(storage, attester)?
+            .ok_or_else(|| Error::UnknownAttester(attester.clone()))?;
+        let revocations = person.revocations();
+        if !person.delegations().contains(&self.key) && !revocations.is_revoked(&self.key) {
+            return Err(Error::NotADelegate {
+                attester: attester.clone(),
+                key: self.key,
+            });
+        }
+        if revocations.rejects(storage.as_ref().as_raw(), &self.key, at)? {
+            return Err(Error::Revoked {
+                attester: attester.clone(),
+                key: self.key,
+                at,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Vouch for `subject` as `whoami`, signing the [`Attestation`] with the key of
+/// `storage`.
+///
+/// An existing attestation of `subject` by `whoami` is replaced, and the
+/// `rad/signed_refs` of `whoami` are updated.
+#[tracing::instrument(level = "debug", skip(storage, whoami), fields(attester = %whoami.urn()))]
+pub fn vouch(
+    storage: &Storage,
+    whoami: &LocalIdentity,
+    subject: &Urn,
+    note: Option<String>,
+) -> Result<Signed, Error> {
+    let attestation = Attestation {
+        attester: whoami.urn(),
+        subject: subject.clone().with_path(None),
+        note,
+        timestamp: now(),
+        withdrawn: false,
+    };
+    commit(storage, attestation, &format!("Attest {}", subject))
+}
+
+/// Withdraw the attestation of `subject` by `whoami`, by replacing it with a
+/// signed tombstone.
+///
+/// Returns `false` if no such attestation exists, or if it was already
+/// withdrawn.
+#[tracing::instrument(level = "debug", skip(storage, whoami), fields(attester = %whoami.urn()))]
+pub fn withdraw(storage: &Storage, whoami: &LocalIdentity, subject: &Urn) -> Result<bool, Error> {
+    let attester = whoami.urn();
+    let branch = Reference::rad_attestation(Namespace::from(&attester), None, subject);
+    let at = match storage.reference(&branch)? {
+        None => return Ok(false),
+        Some(r) => r.peel_to_commit()?.id(),
+    };
+    if read(storage, &attester, at)?.attestation.withdrawn {
+        return Ok(false);
+    }
+
+    let attestation = Attestation {
+        attester,
+        subject: subject.clone().with_path(None),
+        note: None,
+        timestamp: now(),
+        withdrawn: true,
+    };
+    commit(
+        storage,
+        attestation,
+        &format!("Withdraw attestation of {}", subject),
+    )?;
+
+    Ok(true)
+}
+
+/// Sign `attestation` with the key of `storage`, and commit it on top of the
+/// attester's ref for the subject.
+fn commit(storage: &Storage, attestation: Attestation, message: &str) -> Result<Signed, Error> {
+    let attester = attestation.attester.clone();
+    let subject = attestation.subject.clone();
+    let signer = storage.signer();
+    let signature = signer
+        .sign_blocking(&attestation.canonical_form()?)
+        .map_err(|err| Error::Sign(Box::new(err)))?;
+    let signed = Signed {
+        attestation,
+        key: *PeerId::from_signer(signer).as_public_key(),
+        signature: signature.into(),
+    };
+
+    let branch = Reference::rad_attestation(Namespace::from(&attester), None, &subject);
+    let raw = storage.as_raw();
+    let parent = storage
+        .reference(&branch)?
+        .map(|r| r.peel_to_commit())
+        .transpose()?;
+    let tree = {
+        let blob = raw.blob(&serde_json::to_vec(&signed)?)?;
+        let mut builder = raw.treebuilder(None)?;
+        builder.insert(BLOB_PATH, blob, 0o100_644)?;
+        raw.find_tree(builder.write()?)?
+    };
+    let author = raw.signature()?;
+    raw.commit(
+        Some(ext::RefLike::from(&branch).as_str()),
+        &author,
+        &author,
+        message,
+        &tree,
+        &parent.iter().collect::<Vec<_>>(),
+    )?;
+    Refs::update(storage, &attester)?;
+
+    Ok(signed)
+}
+
+/// Load and verify the attestation of `subject` made by `attester`, as seen by
+/// `peer`.
+///
+/// If `peer` is `None`, the local attestation is loaded. If no attestation is
+/// found, or it was withdrawn, `None` is returned.
+pub fn get<S, P>(
+    storage: &S,
+    attester: &Urn,
+    subject: &Urn,
+    peer: P,
+) -> Result<Option<Signed>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+    P: Into<Option<PeerId>>,
+{
+    let branch = Reference::rad_attestation(Namespace::from(attester), peer.into(), subject);
+    match storage.as_ref().reference(&branch)? {
+        None => Ok(None),
+        Some(r) => {
+            let signed = load(storage, attester, r.peel_to_commit()?.id())?;
+            Ok(Some(signed).filter(|signed| !signed.attestation.withdrawn))
+        },
+    }
+}
+
+/// List the valid attestations made by `attester`, including those made on any
+/// of the attester's devices known to `storage`.
+///
+/// Invalid attestations are skipped. If the same `subject` was attested more
+/// than once, the most recent attestation is returned, unless it was withdrawn
+/// since.
+pub fn list<S>(storage: &S, attester: &Urn) -> Result<Vec<Signed>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    let glob = globset::Glob::new(&format!(
+        "refs/namespaces/{}/refs/{{rad,remotes/*/rad}}/attestations/*",
+        Namespace::from(attester)
+    ))
+    .expect("attestations glob is valid")
+    .compile_matcher();
+
+    let mut latest = BTreeMap::<Urn, Signed>::new();
+    for r in storage.references_glob(glob)? {
+        let r = r?;
+        let signed = match load(storage, attester, r.peel_to_commit()?.id()) {
+            Ok(signed) => signed,
+            Err(e) => {
+                tracing::warn!(
+                    reference = ?r.name(),
+                    err = %e,
+                    "skipping invalid attestation"
+                );
+                continue;
+            },
+        };
+        let subject = signed.attestation.subject.clone();
+        let newer = latest.get(&subject).map_or(true, |seen| {
+            signed.attestation.supersedes(&seen.attestation)
+        });
+        if newer {
+            latest.insert(subject, signed);
+        }
+    }
+
+    Ok(latest
+        .into_values()
+        .filter(|signed| !signed.attestation.withdrawn)
+        .collect())
+}
+
+/// The [`PeerId`]s of the [`Person`]s vouched for by the delegates of the
+/// identity `urn`.
+///
+/// For a project, these are the attestations made by its indirect delegates,
+/// for a person, those made by the person itself. Subjects which are not
+/// present in `storage` are skipped, as their keys cannot be determined.
+pub fn vouched_peers<S>(storage: &S, urn: &Urn) -> Result<BTreeSet<PeerId>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    let attesters: Vec<Urn> = match any::get(storage, urn)? {
+        Some(SomeIdentity::Project(_)) => project::verify(storage, urn)?
+            .ok_or_else(|| super::Error::NotFound(urn.clone()))?
+            .delegations()
+            .iter()
+            .indirect()
+            .map(|p| p.urn())
+            .collect(),
+        Some(_) => vec![urn.clone()],
+        None => return Err(super::Error::NotFound(urn.clone()).into()),
+    };
+
+    let mut peers = BTreeSet::new();
+    for attester in attesters {
+        for signed in list(storage, &attester)? {
+            match person::verify(storage, &signed.attestation.subject)? {
+                Some(subject) => {
+                    peers.extend(subject.delegations().iter().copied().map(PeerId::from))
+                },
+                None => tracing::debug!(
+                    subject = %signed.attestation.subject,
+                    "skipping unknown subject"
+                ),
+            }
+        }
+    }
+
+    Ok(peers)
+}
+
+fn load<S>(storage: &S, attester: &Urn, at: git2::Oid) -> Result<Signed, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let signed = read(storage, attester, at)?;
+    signed.verify(storage, at)?;
+    Ok(signed)
+}
+
+/// Read the attestation at `at` in the namespace of `attester`, without
+/// verifying it.
+fn read<S>(storage: &S, attester: &Urn, at: git2::Oid) -> Result<Signed, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let blob = storage
+        .as_ref()
+        .blob_at(at.into(), Path::new(BLOB_PATH))?
+        .ok_or(Error::Missing(at))?;
+    let signed: Signed = serde_json::from_slice(blob.content())?;
+    if &signed.attestation.attester != attester {
+        return Err(Error::Namespace {
+            attester: signed.attestation.attester,
+            namespace: attester.clone(),
+        });
+    }
+    Ok(signed)
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,204 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Certificates for device keys of a [`Person`].
+//!
+//! A key of a person can issue a time-limited [`Certificate`] for the key of
+//! another device, which then acts on behalf of the person until the
+//! certificate expires. This allows every device to have its own [`PeerId`],
+//! without requiring a new revision of the person for each device.
+//!
+//! Certificates are stored in the namespace of the person at:
+//!
+//! ```text
+//! refs/namespaces/<person>/refs/rad/certs/<device>
+//! ```
+//!
+//! The ref points to a commit, whose tree contains a single blob with the JSON
+//! encoding of the [`Certificate`]. Being a `rad` ref, it is replicated along
+//! with the person's identity.
+//!
+//! Certificates found in the namespace of a URN are also consulted when
+//! verifying its `rad/signed_refs`, so that refs signed by a device are
+//! accepted as signed by the issuer (see [`refs::Signed::verify_certified`]).
+//!
+//! A certificate is only honoured while it is valid according to the local
+//! clock, see [`certificate`].
+//!
+//! [`Person`]: super::Person
+
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use git_ext as ext;
+use thiserror::Error;
+
+use super::{
+    super::{
+        refs::{self, Refs},
+        storage::{self, ReadOnlyStorage as _, Storage},
+        types::{Namespace, Reference},
+    },
+    local::LocalIdentity,
+    Urn,
+};
+use crate::{
+    identities::delegation::certificate::{self, Certificate, Certificates},
+    PeerId,
+};
+
+const BLOB_PATH: &str = "certificate";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("no certificate found at {0}")]
+    Missing(git2::Oid),
+
+    #[error(transparent)]
+    Certificate(#[from] certificate::Error),
+
+    #[error(transparent)]
+    Sigrefs(#[from] Box<refs::stored::Error>),
+
+    #[error(transparent)]
+    Storage(#[from] storage::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl From<refs::stored::Error> for Error {
+    fn from(e: refs::stored::Error) -> Self {
+        Self::Sigrefs(Box::new(e))
+    }
+}
+
+/// Certify `device` to act on behalf of `whoami` for the given `validity`
+/// period, starting now.
+///
+/// The [`Certificate`] is signed with the key of `storage`, which must be a
+/// delegate of `whoami`. An existing certificate for `device` is replaced, and
+/// the `rad/signed_refs` of `whoami` are updated.
+#[tracing::instrument(level = "debug", skip(storage, whoami), fields(person = %whoami.urn()))]
+pub fn certify(
+    storage: &Storage,
+    whoami: &LocalIdentity,
+    device: PeerId,
+    validity: Duration,
+) -> Result<Certificate, Error> {
+    let urn = whoami.urn();
+    let not_before = now();
+    let cert = Certificate::issue(
+        storage.signer(),
+        *device.as_public_key(),
+        not_before,
+        not_before.saturating_add(validity.as_secs() as i64),
+    )?;
+
+    let branch = Reference::rad_certificate(Namespace::from(&urn), None, &device);
+    let raw = storage.as_raw();
+    let parent = storage
+        .reference(&branch)?
+        .map(|r| r.peel_to_commit())
+        .transpose()?;
+    let tree = {
+        let blob = raw.blob(&serde_json::to_vec(&cert)?)?;
+        let mut builder = raw.treebuilder(None)?;
+        builder.insert(BLOB_PATH, blob, 0o100_644)?;
+        raw.find_tree(builder.write()?)?
+    };
+    let author = raw.signature()?;
+    raw.commit(
+        Some(ext::RefLike::from(&branch).as_str()),
+        &author,
+        &author,
+        &format!("Certify {}", device),
+        &tree,
+        &parent.iter().collect::<Vec<_>>(),
+    )?;
+    Refs::update(storage, &urn)?;
+
+    Ok(cert)
+}
+
+/// Remove the certificate for `device` issued on behalf of `whoami`.
+///
+/// Returns `false` if no such certificate exists. Note that copies of the
+/// certificate which were already replicated remain valid until they expire.
+#[tracing::instrument(level = "debug", skip(storage, whoami), fields(person = %whoami.urn()))]
+pub fn revoke(storage: &Storage, whoami: &LocalIdentity, device: &PeerId) -> Result<bool, Error> {
+    let urn = whoami.urn();
+    let branch = Reference::rad_certificate(Namespace::from(&urn), None, device);
+    match storage.reference(&branch)? {
+        None => Ok(false),
+        Some(mut r) => {
+            r.delete()?;
+            Refs::update(storage, &urn)?;
+            Ok(true)
+        },
+    }
+}
+
+/// Load the certificates for devices of the person `urn`, including those
+/// found under any of its remotes.
+///
+/// Certificates with an invalid signature are skipped. Note that this does not
+/// check whether the issuers are delegates of the person, which is left to
+/// [`Certified`] when verifying.
+///
+/// [`Certified`]: crate::identities::delegation::certificate::Certified
+pub fn certificates<S>(storage: &S, urn: &Urn) -> Result<Certificates, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    let glob = globset::Glob::new(&format!(
+        "refs/namespaces/{}/refs/{{rad,remotes/*/rad}}/certs/*",
+        Namespace::from(urn)
+    ))
+    .expect("certificates glob is valid")
+    .compile_matcher();
+
+    let mut certs = Certificates::default();
+    for r in storage.references_glob(glob)? {
+        let r = r?;
+        match load(storage, r.peel_to_commit()?.id()) {
+            Ok(cert) => {
+                if !certs.insert(cert) {
+                    tracing::warn!(reference = ?r.name(), "skipping invalid certificate");
+                }
+            },
+            Err(e) => tracing::warn!(
+                reference = ?r.name(),
+                err = %e,
+                "skipping unreadable certificate"
+            ),
+        }
+    }
+
+    Ok(certs)
+}
+
+fn load<S>(storage: &S, at: git2::Oid) -> Result<Certificate, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let blob = storage
+        .as_ref()
+        .blob_at(at.into(), Path::new(BLOB_PATH))?
+        .ok_or(Error::Missing(at))?;
+    Ok(serde_json::from_slice(blob.content())?)
+}
+
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,305 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Structured differences between two revisions of an identity.
+//!
+//! A [`Diff`] describes which fields of the payload subject (eg. the project
+//! `name`) were added, removed, or modified, which extension namespaces (see
+//! [`crate::identities::payload::Ext`]) came or went, and which direct keys or
+//! indirect delegates (by [`Urn`]) were added to or removed from the
+//! delegations.
+//!
+//! The [`fmt::Display`] impl of [`Diff`] renders a summary for humans, while
+//! its [`serde::Serialize`] impl is suitable for producing JSON.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom as _,
+    fmt,
+};
+
+use serde::Serialize;
+use url::Url;
+
+use super::{
+    super::{
+        storage,
+        types::{Namespace, Reference},
+    },
+    error::Error,
+    person,
+    project,
+};
+use crate::{
+    identities::{
+        git::{ContentId, Person, Project, Urn},
+        payload::Payload,
+    },
+    PeerId,
+    PublicKey,
+};
+
+/// The difference between an `old` and a `new` revision of an identity.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diff {
+    /// The [`Urn`] of the new revision.
+    pub urn: Urn,
+    /// The commit of the old revision.
+    pub old: ContentId,
+    /// The commit of the new revision.
+    pub new: ContentId,
+    /// Changes to the fields of the payload subject, by field name.
+    pub subject: BTreeMap<String, Change>,
+    /// Changes to the payload extensions, by namespace.
+    pub ext: BTreeMap<Url, Change>,
+    /// Changes to the delegations.
+    pub delegations: Delegations,
+}
+
+/// The change of a single payload field or extension.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Change {
+    Added {
+        new: serde_json::Value,
+    },
+    Removed {
+        old: serde_json::Value,
+    },
+    Modified {
+        old: serde_json::Value,
+        new: serde_json::Value,
+    },
+}
+
+/// The keys and indirect delegates added to or removed from the delegations.
+///
+/// Person identities only ever delegate to keys directly, so the `*_urns` sets
+/// are always empty for them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delegations {
+    pub added_keys: BTreeSet<PublicKey>,
+    pub removed_keys: BTreeSet<PublicKey>,
+    pub added_urns: BTreeSet<Urn>,
+    pub removed_urns: BTreeSet<Urn>,
+}
+
+impl Delegations {
+    fn new(
+        (old_keys, new_keys): (BTreeSet<PublicKey>, BTreeSet<PublicKey>),
+        (old_urns, new_urns): (BTreeSet<Urn>, BTreeSet<Urn>),
+    ) -> Self {
+        Self {
+            added_keys: new_keys.difference(&old_keys).copied().collect(),
+            removed_keys: old_keys.difference(&new_keys).copied().collect(),
+            added_urns: new_urns.difference(&old_urns).cloned().collect(),
+            removed_urns: old_urns.difference(&new_urns).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_keys.is_empty()
+            && self.removed_keys.is_empty()
+            && self.added_urns.is_empty()
+            && self.removed_urns.is_empty()
+    }
+}
+
+impl Diff {
+    /// `true` if the payloads and delegations of both revisions are the same.
+    pub fn is_empty(&self) -> bool {
+        self.subject.is_empty() && self.ext.is_empty() && self.delegations.is_empty()
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "identity {}", self.urn)?;
+        writeln!(f, "old {}", self.old)?;
+        writeln!(f, "new {}", self.new)?;
+        if self.is_empty() {
+            return writeln!(f, "\nno changes");
+        }
+
+        if !self.subject.is_empty() {
+            writeln!(f, "\npayload:")?;
+            for (field, change) in &self.subject {
+                writeln!(f, "{}", Line(field, change))?;
+            }
+        }
+        if !self.ext.is_empty() {
+            writeln!(f, "\nextensions:")?;
+            for (namespace, change) in &self.ext {
+                writeln!(f, "{}", Line(namespace, change))?;
+            }
+        }
+        if !self.delegations.is_empty() {
+            let Delegations {
+                added_keys,
+                removed_keys,
+                added_urns,
+                removed_urns,
+            } = &self.delegations;
+            writeln!(f, "\ndelegations:")?;
+            for key in added_keys {
+                writeln!(f, "  + key {}", key)?;
+            }
+            for key in removed_keys {
+                writeln!(f, "  - key {}", key)?;
+            }
+            for urn in added_urns {
+                writeln!(f, "  + urn {}", urn)?;
+            }
+            for urn in removed_urns {
+                writeln!(f, "  - urn {}", urn)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Line<'a, K>(&'a K, &'a Change);
+
+impl<'a, K: fmt::Display> fmt::Display for Line<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(key, change) = self;
+        match change {
+            Change::Added { new } => write!(f, "  + {}: {}", key, new),
+            Change::Removed { old } => write!(f, "  - {}: {}", key, old),
+            Change::Modified { old, new } => write!(f, "  ~ {}: {} -> {}", key, old, new),
+        }
+    }
+}
+
+/// Compute the [`Diff`] between two [`Person`] revisions.
+pub fn person(old: &Person, new: &Person) -> Diff {
+    let keys = |p: &Person| p.delegations().iter().copied().collect();
+    diff(
+        (old.content_id, old.payload()),
+        (new.urn(), new.content_id, new.payload()),
+        Delegations::new((keys(old), keys(new)), Default::default()),
+    )
+}
+
+/// Compute the [`Diff`] between two [`Project`] revisions.
+pub fn project(old: &Project, new: &Project) -> Diff {
+    let keys = |p: &Project| p.delegations().iter().direct().copied().collect();
+    let urns = |p: &Project| p.delegations().iter().indirect().map(|i| i.urn()).collect();
+    diff(
+        (old.content_id, old.payload()),
+        (new.urn(), new.content_id, new.payload()),
+        Delegations::new((keys(old), keys(new)), (urns(old), urns(new))),
+    )
+}
+
+/// Compute the [`Diff`] between the [`Person`] revisions at the commits `old`
+/// and `new`.
+pub fn person_revisions<S>(storage: &S, old: git2::Oid, new: git2::Oid) -> Result<Diff, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let ids = storage.as_ref().identities::<Person>();
+    Ok(person(&ids.get(old)?, &ids.get(new)?))
+}
+
+/// Compute the [`Diff`] between the [`Project`] revisions at the commits `old`
+/// and `new`.
+pub fn project_revisions<S>(storage: &S, old: git2::Oid, new: git2::Oid) -> Result<Diff, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let ids = storage.as_ref().identities::<Project>();
+    Ok(project(&ids.get(old)?, &ids.get(new)?))
+}
+
+/// Compute the [`Diff`] between the local view of the [`Person`] `urn`, and
+/// the view of `peer`.
+///
+/// If either of them does not exist, [`Error::NotFound`] is returned.
+pub fn person_with_peer<S>(storage: &S, urn: &Urn, peer: PeerId) -> Result<Diff, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let ours = person::get(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let remote = remote_urn(urn, peer)?;
+    let theirs = person::get(storage, &remote)?.ok_or(Error::NotFound(remote))?;
+    Ok(person(&ours, &theirs))
+}
+
+/// Compute the [`Diff`] between the local view of the [`Project`] `urn`, and
+/// the view of `peer`.
+///
+/// If either of them does not exist, [`Error::NotFound`] is returned.
+pub fn project_with_peer<S>(storage: &S, urn: &Urn, peer: PeerId) -> Result<Diff, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let ours = project::get(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let remote = remote_urn(urn, peer)?;
+    let theirs = project::get(storage, &remote)?.ok_or(Error::NotFound(remote))?;
+    Ok(project(&ours, &theirs))
+}
+
+fn remote_urn(urn: &Urn, peer: PeerId) -> Result<Urn, Error> {
+    Ok(Urn::try_from(
+        Reference::rad_id(Namespace::from(urn)).with_remote(peer),
+    )?)
+}
+
+fn diff<T>(
+    (old_id, old): (ContentId, &Payload<T>),
+    (urn, new_id, new): (Urn, ContentId, &Payload<T>),
+    delegations: Delegations,
+) -> Diff
+where
+    T: Serialize,
+{
+    let fields = |payload: &Payload<T>| match serde_json::to_value(&payload.subject) {
+        Ok(serde_json::Value::Object(fields)) => fields
+            .into_iter()
+            .filter(|(_, val)| !val.is_null())
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+    let exts = |payload: &Payload<T>| {
+        payload
+            .exts()
+            .map(|(namespace, val)| (namespace.clone(), val.clone()))
+            .collect()
+    };
+
+    Diff {
+        urn,
+        old: old_id,
+        new: new_id,
+        subject: changes(fields(old), fields(new)),
+        ext: changes(exts(old), exts(new)),
+        delegations,
+    }
+}
+
+fn changes<K: Ord>(
+    mut old: BTreeMap<K, serde_json::Value>,
+    new: BTreeMap<K, serde_json::Value>,
+) -> BTreeMap<K, Change> {
+    let mut changes = BTreeMap::new();
+    for (key, new) in new {
+        match old.remove(&key) {
+            None => {
+                changes.insert(key, Change::Added { new });
+            },
+            Some(old) if old != new => {
+                changes.insert(key, Change::Modified { old, new });
+            },
+            Some(_) => {},
+        }
+    }
+    changes.extend(
+        old.into_iter()
+            .map(|(key, old)| (key, Change::Removed { old })),
+    );
+    changes
+}
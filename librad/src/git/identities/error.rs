@@ -8,6 +8,7 @@ use thiserror::Error;
 
 use super::{
     super::{refs, storage, types::reference},
+    device,
     local,
 };
 use crate::identities::{
@@ -34,6 +35,9 @@ pub enum Error {
     #[error(transparent)]
     LocalId(#[from] local::ValidationError),
 
+    #[error(transparent)]
+    Device(#[from] device::Error),
+
     #[error(transparent)]
     Verification(#[from] VerificationError),
 
@@ -14,6 +14,7 @@ use super::{
         types::Reference,
     },
     common,
+    device,
     error::Error,
     local::LocalIdentity,
 };
@@ -57,6 +58,9 @@ where
 ///
 /// If the ref pointed to by [`Urn::path`] is not found, `None` is returned.
 ///
+/// Signatures made by device keys which were certified by a delegate of the
+/// person (see [`device`]) count as signatures of that delegate.
+///
 /// # Caveats
 ///
 /// Keep in mind that the `content_id` of a successfully verified person may
@@ -74,8 +78,43 @@ where
     match storage.reference(&branch) {
         Ok(Some(reference)) => {
             let tip = reference.peel_to_commit()?.id();
-            identities(storage)
+            let certs = device::certificates(storage, urn)?;
+            let person = identities(storage)
+                .with_certificates(&certs)
                 .verify(tip)
+                .map_err(|e| Error::Verify(e.into()))?;
+            for invalid in person.invalid_exts() {
+                tracing::warn!(urn = %urn, "{}", invalid);
+            }
+            Ok(Some(person))
+        },
+
+        Ok(None) => Ok(None),
+        Err(storage::Error::Git(e)) if is_not_found_err(&e) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read and verify the [`Person`] pointed to by `urn`, requiring its history
+/// to be linear.
+///
+/// Like [`verify`], but fails with
+/// [`identities::git::error::VerifyPerson::NonLinear`] if a revision in the
+/// history was replaced by more than one other revision. Use [`linearise`] to
+/// rewrite such a history.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn verify_linear<S>(storage: &S, urn: &Urn) -> Result<Option<VerifiedPerson>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    match storage.reference(&Reference::try_from(urn)?) {
+        Ok(Some(reference)) => {
+            let tip = reference.peel_to_commit()?.id();
+            let certs = device::certificates(storage, urn)?;
+            identities(storage)
+                .with_certificates(&certs)
+                .verify_linear(tip)
                 .map(Some)
                 .map_err(|e| Error::Verify(e.into()))
         },
@@ -179,6 +218,42 @@ pub fn merge(storage: &Storage, urn: &Urn, from: PeerId) -> Result<Person, Error
     Ok(next)
 }
 
+/// Rewrite the history of the [`Person`] at `urn` to be linear.
+///
+/// The history is replayed up to its latest verified revision, see
+/// [`Identities::linearise`], and `rad/id` is reset to the result. Revisions
+/// which were not approved by a quorum are dropped, and need to be proposed
+/// again.
+///
+/// If the history is already linear, it is left untouched.
+///
+/// # Caveats
+///
+/// The rewritten history is not a descendant of the previous one, so peers
+/// which replicated the latter will not accept it as a fast-forward.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn linearise(storage: &Storage, urn: &Urn) -> Result<Person, Error> {
+    let current = get(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let ids = identities(storage);
+    if ids.fork_point(*current.content_id)?.is_none() {
+        return Ok(current);
+    }
+
+    let verified = ids
+        .verify(*current.content_id)
+        .map_err(|e| Error::Verify(e.into()))?;
+    let next = ids.linearise(verified)?;
+
+    common::IdRef::from(urn).update(
+        storage,
+        next.content_id,
+        &format!("linearise from {}", current.content_id),
+    )?;
+    Refs::update(storage, urn)?;
+
+    Ok(next)
+}
+
 /// Return the newer of `a` and `b`, or an error if their histories are
 /// unrelated.
 pub fn newer<S>(storage: &S, a: VerifiedPerson, b: VerifiedPerson) -> Result<VerifiedPerson, Error>
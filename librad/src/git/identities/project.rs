@@ -106,8 +106,43 @@ where
     match storage.reference(&Reference::try_from(urn)?) {
         Ok(Some(reference)) => {
             let tip = reference.peel_to_commit()?.id();
-            identities(storage)
+            let project = identities(storage)
                 .verify(tip, lookup)
+                .map_err(|e| Error::Verify(e.into()))?;
+            for invalid in project.invalid_exts() {
+                tracing::warn!(urn = %urn, "{}", invalid);
+            }
+            Ok(Some(project))
+        },
+
+        Ok(None) => Ok(None),
+        Err(storage::Error::Git(e)) if is_not_found_err(&e) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read and verify the [`Project`] pointed to by `urn`, requiring its history
+/// to be linear.
+///
+/// Like [`verify`], but fails with
+/// [`identities::git::error::VerifyProject::NonLinear`] if a revision in the
+/// history was replaced by more than one other revision. Use [`linearise`] to
+/// rewrite such a history.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn verify_linear<S>(storage: &S, urn: &Urn) -> Result<Option<VerifiedProject>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    let lookup = |urn| {
+        let refname = Reference::rad_id(Namespace::from(urn));
+        storage.reference_oid(&refname).map(|oid| oid.into())
+    };
+    match storage.reference(&Reference::try_from(urn)?) {
+        Ok(Some(reference)) => {
+            let tip = reference.peel_to_commit()?.id();
+            identities(storage)
+                .verify_linear(tip, lookup)
                 .map(Some)
                 .map_err(|e| Error::Verify(e.into()))
         },
@@ -201,6 +236,36 @@ pub fn merge(storage: &Storage, urn: &Urn, from: PeerId) -> Result<Project, Erro
     Ok(next)
 }
 
+/// Rewrite the history of the [`Project`] at `urn` to be linear.
+///
+/// The history is replayed up to its latest verified revision, see
+/// [`Identities::linearise`], and `rad/id` is reset to the result. Revisions
+/// which were not approved by a quorum are dropped, and need to be proposed
+/// again.
+///
+/// If the history is already linear, it is left untouched.
+///
+/// # Caveats
+///
+/// The rewritten history is not a descendant of the previous one, so peers
+/// which replicated the latter will not accept it as a fast-forward.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn linearise(storage: &Storage, urn: &Urn) -> Result<Project, Error> {
+    let current = get(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let ids = identities(storage);
+    if ids.fork_point(*current.content_id)?.is_none() {
+        return Ok(current);
+    }
+
+    let verified = verify(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let next = ids.linearise(verified)?;
+
+    ProjectRefs::Update(&next, &format!("linearise from {}", current.content_id)).apply(storage)?;
+    Sigrefs::update(storage, urn)?;
+
+    Ok(next)
+}
+
 /// Return the newer of `a` and `b`, or an error if their histories are
 /// unrelated.
 pub fn newer<S>(
@@ -0,0 +1,125 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Remove all traces of an identity from the local storage.
+
+use std::{io, path::PathBuf};
+
+use git_ext::is_not_found_err;
+use thiserror::Error;
+
+use crate::{
+    git::{
+        storage::Storage,
+        tracking::{self, policy, UntrackAllArgs},
+        types::{Namespace, RefsCategory},
+        Urn,
+    },
+    paths::Paths,
+};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Untrack(#[from] tracking::error::UntrackAll),
+}
+
+/// What was removed by [`purge`].
+#[derive(Clone, Debug, Default)]
+pub struct Purged {
+    /// Names of the refs deleted from the namespace of the URN.
+    pub refs: Vec<String>,
+    /// Number of tracking entries removed.
+    pub tracking: usize,
+    /// Paths of the collaborative object cache entries removed.
+    pub cob_cache: Vec<PathBuf>,
+    /// Path of the include file, if one existed.
+    pub include: Option<PathBuf>,
+}
+
+impl Purged {
+    pub fn is_empty(&self) -> bool {
+        self.refs.is_empty()
+            && self.tracking == 0
+            && self.cob_cache.is_empty()
+            && self.include.is_none()
+    }
+}
+
+/// Purge `urn` from the storage.
+///
+/// This deletes every ref under `refs/namespaces/<urn>` (including the `rad/`
+/// refs of all remotes), all tracking entries for `urn`, the collaborative
+/// objects of `urn` from the cache in [`Paths::cob_cache_dir`], and the
+/// include file in [`Paths::git_includes_dir`].
+///
+/// The objects of `urn` are not deleted, but become unreachable, and will thus
+/// be reclaimed by the next `git gc` which prunes unreachable objects.
+///
+/// Note that symbolic refs in _other_ namespaces pointing to `urn` (eg. when
+/// `urn` is a delegate of another project) are not removed, as doing so would
+/// render the other project invalid. Purging a person which is still a
+/// delegate of a local project is likely not what you want.
+#[tracing::instrument(level = "debug", skip(storage, paths), err)]
+pub fn purge(storage: &Storage, paths: &Paths, urn: &Urn) -> Result<Purged, Error> {
+    let mut purged = Purged::default();
+    let namespace = Namespace::from(urn);
+    let prefix = format!("refs/namespaces/{}/", namespace);
+    let cobs = format!("/{}/", RefsCategory::Cobs);
+
+    let repo = storage.as_raw();
+    let names = repo
+        .references_glob(&format!("{}*", prefix))?
+        .names()
+        .map(|name| name.map(ToOwned::to_owned))
+        .collect::<Result<Vec<_>, _>>()?;
+    for name in names {
+        // refs/namespaces/<urn>/refs/[remotes/<peer>/]cobs/<typename>/<object id>
+        if name.contains(&cobs) {
+            if let Some(oid) = name.rsplit('/').next() {
+                let cached = paths.cob_cache_dir().join("v1").join(oid);
+                if remove_file(&cached)? && !purged.cob_cache.contains(&cached) {
+                    purged.cob_cache.push(cached)
+                }
+            }
+        }
+
+        match repo.find_reference(&name) {
+            Ok(mut r) => r.delete()?,
+            Err(e) if is_not_found_err(&e) => continue,
+            Err(e) => return Err(e.into()),
+        }
+        tracing::trace!(name = %name, "deleted");
+        purged.refs.push(name);
+    }
+
+    let untracked =
+        tracking::untrack_all(storage, urn, UntrackAllArgs::new(policy::UntrackAll::Any))?;
+    purged.tracking = untracked.untracked.filter(Result::is_ok).count();
+
+    let include = paths
+        .git_includes_dir()
+        .join(urn.encode_id())
+        .with_extension("inc");
+    if remove_file(&include)? {
+        purged.include = Some(include)
+    }
+
+    Ok(purged)
+}
+
+/// Returns `true` if the file existed.
+fn remove_file(path: &std::path::Path) -> Result<bool, io::Error> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
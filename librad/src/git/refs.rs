@@ -26,11 +26,12 @@ use std_ext::result::ResultExt as _;
 use thiserror::Error;
 
 use super::{
+    identities::device,
     storage::{self, ReadOnlyStorage, Storage},
     tracking,
     types::{Namespace, Reference, RefsCategory},
 };
-use crate::{PeerId, Signature, Signer};
+use crate::{identities::delegation::certificate::Certificates, PeerId, Signature, Signer};
 
 pub use crate::identities::git::Urn;
 pub use git_ext::Oid;
@@ -202,6 +203,9 @@ pub mod stored {
 
         #[error(transparent)]
         Tracked(#[from] tracking::error::TrackedPeers),
+
+        #[error(transparent)]
+        Certificates(#[from] Box<device::Error>),
     }
 }
 
@@ -545,6 +549,11 @@ pub struct Unverified;
 /// [`PeerId`], using [`Signed::verify`]. A shorthand for verifying bytes with a
 /// `PeerId` is given by [`Signed::from_json`].
 ///
+/// A signature made by a device key on behalf of the expected signer is
+/// accepted if the latter certified the device, see
+/// [`Signed::verify_certified`]. The certificates are not part of the signed
+/// refs, so as to keep their encoding compatible with older peers.
+///
 /// Note that we may only persist a `Signed<Verified>`, and can only deserialize
 /// a `Signed<Unverified>`.
 pub struct Signed<V> {
@@ -560,8 +569,35 @@ impl Signed<Verified> {
     }
 
     pub fn verify(unknown: Signed<Unverified>, signer: &PeerId) -> Result<Self, signed::Error> {
+        Self::verify_certified(unknown, signer, &Certificates::default())
+    }
+
+    /// Like [`Signed::from_json`], but see [`Signed::verify_certified`].
+    pub fn from_json_certified(
+        data: &[u8],
+        signer: &PeerId,
+        certs: &Certificates,
+    ) -> Result<Self, signed::Error> {
+        let unknown = serde_json::from_slice(data)?;
+        Self::verify_certified(unknown, signer, certs)
+    }
+
+    /// Verify that `unknown` was signed by `signer`, or by a device key which
+    /// `signer` certified in `certs`.
+    ///
+    /// A certificate is only honoured while it is valid according to the local
+    /// clock, see [`device`].
+    pub fn verify_certified(
+        unknown: Signed<Unverified>,
+        signer: &PeerId,
+        certs: &Certificates,
+    ) -> Result<Self, signed::Error> {
         let canonical = unknown.refs.canonical_form()?;
-        if unknown.signature.verify(&canonical, &**signer) {
+        let valid = unknown.signature.verify(&canonical, &**signer)
+            || certs
+                .devices(signer.as_public_key(), device::now())
+                .any(|key| unknown.signature.verify(&canonical, key));
+        if valid {
             Ok(Signed {
                 refs: unknown.refs,
                 signature: unknown.signature,
@@ -662,6 +698,10 @@ pub(crate) struct Loaded {
     pub refs: Signed<Verified>,
 }
 
+/// Load the signed refs of `peer` in the namespace of `urn`.
+///
+/// A signature by a device key is accepted if `peer` certified it, according
+/// to the certificates found in the namespace (see [`device::certificates`]).
 pub(crate) fn load<S>(
     storage: S,
     urn: &Urn,
@@ -683,27 +723,35 @@ where
         None => Ok(None),
         Some(at) => {
             tracing::debug!("loading signed_refs from {}:{}", &sigrefs, &at);
-            load_at(storage, at, peer)
+            load_at(storage, urn, at, peer)
         },
     }
 }
 
+/// Load the signed refs of `peer` stored at `at`, see [`load`].
 pub(crate) fn load_at<S>(
     storage: S,
+    urn: &Urn,
     at: git_ext::Oid,
     peer: Option<&PeerId>,
 ) -> Result<Option<Loaded>, stored::Error>
 where
     S: AsRef<storage::ReadOnly>,
 {
-    let signer = peer.unwrap_or_else(|| storage.as_ref().peer_id());
-    let loaded = storage
-        .as_ref()
-        .blob_at(at, Path::new(stored::BLOB_PATH))?
-        .map(|blob| Signed::from_json(blob.content(), signer))
-        .transpose()
-        .map_err(stored::Error::from)?
-        .map(|refs| Loaded { at, refs });
+    let storage = storage.as_ref();
+    let signer = peer.unwrap_or_else(|| storage.peer_id());
+    let blob = match storage.blob_at(at, Path::new(stored::BLOB_PATH))? {
+        None => return Ok(None),
+        Some(blob) => blob,
+    };
+    // Only look up certificates if the signer didn't sign itself
+    let refs = match Signed::from_json(blob.content(), signer) {
+        Err(signed::Error::InvalidSignature(_)) => {
+            let certs = device::certificates(storage, urn).map_err(Box::new)?;
+            Signed::from_json_certified(blob.content(), signer, &certs)
+        },
+        verified => verified,
+    }?;
 
-    Ok(loaded)
+    Ok(Some(Loaded { at, refs }))
 }
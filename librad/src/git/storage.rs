@@ -18,7 +18,7 @@ use std_ext::Void;
 
 use crate::{
     collaborative_objects::CollaborativeObjects,
-    identities::git::Urn,
+    identities::git::{cache::FileSystemCache, Urn},
     paths::Paths,
     PeerId,
     Signer,
@@ -26,8 +26,12 @@ use crate::{
 
 pub mod config;
 pub mod glob;
+pub mod maintenance;
+pub mod migration;
 pub mod pool;
 pub mod read;
+pub mod snapshot;
+pub mod stats;
 pub mod watch;
 
 pub use config::Config;
@@ -42,12 +46,13 @@ pub use read::{
     References,
     ReferencesGlob,
 };
-pub use watch::{NamespaceEvent, Watcher};
+pub use snapshot::Snapshot;
+pub use watch::{NamespaceEvent, RefEvent, Watcher};
 
 pub mod error {
     use thiserror::Error;
 
-    use super::config;
+    use super::{config, migration};
 
     #[derive(Debug, Error)]
     #[non_exhaustive]
@@ -62,19 +67,7 @@ pub mod error {
         SignerKeyMismatch,
 
         #[error(transparent)]
-        TrackingMigration(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
-    }
-
-    impl From<crate::git::identities::error::Error> for Init {
-        fn from(err: crate::git::identities::error::Error) -> Self {
-            Self::TrackingMigration(Box::new(err))
-        }
-    }
-
-    impl From<crate::git::tracking::migration::Error> for Init {
-        fn from(err: crate::git::tracking::migration::Error) -> Self {
-            Self::TrackingMigration(Box::new(err))
-        }
+        Migration(#[from] migration::Error),
     }
 }
 
@@ -128,25 +121,15 @@ impl Storage {
         }
 
         let storage = Self {
-            inner: ReadOnly { backend, peer_id },
+            inner: ReadOnly {
+                backend,
+                peer_id,
+                identities_cache: FileSystemCache::new(paths.identities_cache_dir()),
+            },
             signer: BoxedSigner::from(SomeSigner { signer }),
         };
 
-        // NOTE: this is temporary migration code, converting v1 tracking entries into
-        // v2 tracking entries. It should eventually be phased out as upstream
-        // dependencies migrate to the latest version.
-        {
-            let urns = crate::git::identities::any::list(&storage)?
-                .map(|i| i.map(|i| i.urn()))
-                .collect::<Result<std::collections::BTreeSet<_>, _>>()?;
-
-            // The failures will eventually converge as `Storage::open` is called on the
-            // next start up, and we report them via trace in the meantime.
-            let results = crate::git::tracking::migration::migrate(&storage, urns)?;
-            results.failures.into_iter().for_each(|(err, urn, peer)| {
-                tracing::warn!(urn = %urn, peer = %peer, reason = %err, "failed to migrate");
-            });
-        }
+        migration::run(&storage)?;
 
         Ok(storage)
     }
@@ -221,6 +204,11 @@ impl Storage {
         watch::Watch { storage: self }
     }
 
+    /// Compute the [`stats::UrnStats`] of all identities in the storage.
+    pub fn stats(&self) -> Result<Vec<stats::UrnStats>, stats::Error> {
+        stats::all(self)
+    }
+
     pub(super) fn signer(&self) -> &BoxedSigner {
         &self.signer
     }
@@ -12,7 +12,7 @@ use git_ext::{self as ext, is_not_found_err};
 use std_ext::prelude::*;
 use thiserror::Error;
 
-use super::{super::identities::local::LocalIdentity, Storage};
+use super::{super::identities::local::LocalIdentity, migration, Storage};
 use crate::{
     identities::{
         git::{Identities, Urn, VerifiedPerson},
@@ -27,6 +27,8 @@ const CONFIG_USER_NAME: &str = "user.name";
 const CONFIG_USER_EMAIL: &str = "user.email";
 const CONFIG_RAD_SELF: &str = "rad.self";
 const CONFIG_RAD_PEER_ID: &str = "rad.peerid";
+const CONFIG_RAD_VERSION: &str = "rad.version";
+const CONFIG_LOG_ALL_REF_UPDATES: &str = "core.logAllRefUpdates";
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -40,6 +42,9 @@ pub enum Error {
     #[error(transparent)]
     Urn(#[from] urn::error::FromStr<ext::oid::FromMultihashError>),
 
+    #[error("invalid storage version {0}")]
+    InvalidVersion(i64),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
@@ -104,13 +109,17 @@ where
         }
     }
 
-    fn ensure_reflog(&mut self) -> Result<(), Error> {
-        if let Err(e) = self.inner.get_bool("core.logAllRefUpdates") {
-            return if is_not_found_err(&e) {
-                Ok(self.inner.set_bool("core.logAllRefUpdates", true)?)
-            } else {
-                Err(e.into())
-            };
+    /// Set `core.logAllRefUpdates` to `always`, so a reflog is written for
+    /// every ref, including refs created by external `git` processes. This is
+    /// relied upon by [`super::watch`].
+    pub(super) fn ensure_reflog(&mut self) -> Result<(), Error> {
+        let current = self
+            .inner
+            .get_string(CONFIG_LOG_ALL_REF_UPDATES)
+            .map(Some)
+            .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?;
+        if current.as_deref() != Some("always") {
+            self.inner.set_str(CONFIG_LOG_ALL_REF_UPDATES, "always")?;
         }
 
         Ok(())
@@ -129,6 +138,7 @@ where
         this.ensure_reflog()?;
         this.set_peer_id(PeerId::from_signer(signer))?;
         this.set_user_info("anonymous")?;
+        this.set_version(migration::VERSION)?;
 
         Ok(this)
     }
@@ -148,6 +158,13 @@ where
             .map_err(Error::from)
     }
 
+    /// Record the version of the storage layout, cf. [`migration`].
+    pub(crate) fn set_version(&mut self, version: u32) -> Result<(), Error> {
+        self.inner
+            .set_i64(CONFIG_RAD_VERSION, version.into())
+            .map_err(Error::from)
+    }
+
     /// Set the default identity.
     ///
     /// Passing [`Option::None`] removes the setting.
@@ -200,6 +217,19 @@ impl<S> Config<'_, S> {
             .and_then(|peer_id| peer_id.parse().map_err(Error::from))
     }
 
+    /// The version of the storage layout, cf. [`migration`].
+    ///
+    /// Storages which predate versioning are at version `0`.
+    pub fn version(&self) -> Result<u32, Error> {
+        self.inner
+            .get_i64(CONFIG_RAD_VERSION)
+            .map(Some)
+            .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?
+            .map_or(Ok(0), |v| {
+                u32::try_from(v).map_err(|_| Error::InvalidVersion(v))
+            })
+    }
+
     pub fn user(&self) -> Result<Option<Urn>, Error> {
         self.inner
             .get_string(CONFIG_RAD_SELF)
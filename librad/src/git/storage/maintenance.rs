@@ -0,0 +1,182 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Housekeeping of the monorepo.
+//!
+//! Runs the `git` maintenance tasks (`gc`, `repack`, `commit-graph`,
+//! `multi-pack-index`) over the storage. All tasks which (re-)write packfiles
+//! are run with [delta islands][islands] configured per namespace, ie. objects
+//! of one URN are never stored as deltas against objects of another. This
+//! keeps the packfiles we serve to other peers from having to include (or
+//! recompute) objects from unrelated namespaces.
+//!
+//! Maintenance rewrites packfiles, which must not happen while a fetch writes
+//! to the storage. Use [`Lock::acquire`] to obtain exclusive access to a
+//! [`Pool`], which in turn is used by replication.
+//!
+//! [islands]: https://git-scm.com/docs/git-pack-objects#_delta_islands
+
+use std::{
+    fmt,
+    io,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use super::{Pool, PoolError, PooledRef, Storage};
+
+/// Delta island regex, grouping all refs of a namespace into one island.
+pub const NAMESPACE_ISLAND: &str = "refs/namespaces/([^/]+)/";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Task {
+    /// `git gc`
+    Gc,
+    /// `git repack -a -d`
+    Repack,
+    /// `git commit-graph write --reachable --split`
+    CommitGraph,
+    /// `git multi-pack-index write`
+    MultiPackIndex,
+}
+
+impl Task {
+    fn args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Gc => &["gc", "--quiet"],
+            Self::Repack => &["repack", "-a", "-d", "-q"],
+            Self::CommitGraph => &["commit-graph", "write", "--reachable", "--split"],
+            Self::MultiPackIndex => &["multi-pack-index", "write"],
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Gc => "gc",
+            Self::Repack => "repack",
+            Self::CommitGraph => "commit-graph",
+            Self::MultiPackIndex => "multi-pack-index",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The tasks to run, in order.
+    ///
+    /// Default: [`Task::Gc`], [`Task::CommitGraph`], [`Task::MultiPackIndex`]
+    pub tasks: Vec<Task>,
+    /// Configure delta islands per namespace.
+    ///
+    /// Default: true
+    pub delta_islands: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tasks: vec![Task::Gc, Task::CommitGraph, Task::MultiPackIndex],
+            delta_islands: true,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("failed to spawn `git {task}`")]
+    Spawn {
+        task: Task,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("`git {task}` exited with {status}: {stderr}")]
+    Failed {
+        task: Task,
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+
+    #[error(transparent)]
+    Pool(#[from] PoolError),
+}
+
+/// The tasks which were run, and how long they took.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub tasks: Vec<(Task, Duration)>,
+}
+
+/// Run the maintenance [`Task`]s configured in `config` over `storage`.
+///
+/// This blocks until all tasks have completed, or one of them failed. The
+/// caller is responsible for ensuring that no concurrent writes to the
+/// storage occur, see [`Lock`].
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn run(storage: &Storage, config: &Config) -> Result<Report, Error> {
+    let mut report = Report::default();
+    for task in &config.tasks {
+        let started = Instant::now();
+        let mut git = Command::new("git");
+        git.arg("--git-dir").arg(storage.path());
+        if config.delta_islands {
+            git.args(&["-c", &format!("pack.island={}", NAMESPACE_ISLAND)])
+                .args(&["-c", "repack.useDeltaIslands=true"]);
+        }
+        let out = git
+            .args(task.args())
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|source| Error::Spawn {
+                task: *task,
+                source,
+            })?;
+        if !out.status.success() {
+            return Err(Error::Failed {
+                task: *task,
+                status: out.status,
+                stderr: String::from_utf8_lossy(&out.stderr).into_owned(),
+            });
+        }
+
+        let elapsed = started.elapsed();
+        tracing::debug!(%task, ?elapsed, "completed");
+        report.tasks.push((*task, elapsed));
+    }
+
+    Ok(report)
+}
+
+/// Exclusive access to a [`Pool`].
+///
+/// Holds all the [`Storage`]s the pool can hand out, such that other users of
+/// the pool are blocked until the [`Lock`] is dropped.
+///
+/// Note that two [`Lock`]s attempting to acquire the same pool concurrently
+/// may deadlock. Maintenance should thus be scheduled from a single task.
+pub struct Lock {
+    held: Vec<PooledRef<Storage>>,
+}
+
+impl Lock {
+    /// Wait for all [`Storage`]s of `pool` to be returned, and hold on to
+    /// them.
+    pub async fn acquire(pool: &Pool<Storage>) -> Result<Self, PoolError> {
+        let max = pool.status().max_size;
+        let mut held = Vec::with_capacity(max);
+        for _ in 0..max {
+            held.push(PooledRef::from(pool.get().await?));
+        }
+        Ok(Self { held })
+    }
+
+    /// Borrow one of the held [`Storage`]s.
+    pub fn storage(&self) -> Option<&Storage> {
+        self.held.first().map(|s| &**s)
+    }
+}
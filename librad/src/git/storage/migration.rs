@@ -0,0 +1,143 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Versioned migrations of the storage layout.
+//!
+//! The version of the layout is recorded in the storage config. When a
+//! [`Storage`] of an older version is opened, the pending migrations are run
+//! in order, and the version is recorded after each one has completed.
+//!
+//! Migrations must be idempotent: if a migration is interrupted, or reports
+//! that it could not complete, it is run again the next time the storage is
+//! opened.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use super::{config, Storage};
+use crate::git::{identities, tracking};
+
+/// The current version of the storage layout.
+pub const VERSION: u32 = 2;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("storage version {found} is newer than the supported version {VERSION}")]
+    Unsupported { found: u32 },
+
+    #[error("migration to storage version {version} ({name}) failed")]
+    Migration {
+        version: u32,
+        name: &'static str,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error(transparent)]
+    Config(#[from] config::Error),
+}
+
+/// The outcome of running a single migration.
+pub enum Progress {
+    /// The migration has completed, and the storage is at its version.
+    Done,
+    /// The migration could not complete, and should be run again.
+    Retry,
+}
+
+struct Migration {
+    version: u32,
+    name: &'static str,
+    run: fn(&Storage) -> Result<Progress, BoxError>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "tracking-v2",
+        run: tracking_v2,
+    },
+    Migration {
+        version: 2,
+        name: "reflog-always",
+        run: reflog_always,
+    },
+];
+
+/// The versions of the storage before and after [`run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Migrated {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl Migrated {
+    pub fn is_current(&self) -> bool {
+        self.to == VERSION
+    }
+}
+
+/// Run all migrations newer than the version recorded in `storage`.
+///
+/// Stops at the first migration which could not complete, leaving the
+/// remaining ones to the next invocation.
+#[tracing::instrument(skip(storage))]
+pub fn run(storage: &Storage) -> Result<Migrated, Error> {
+    let mut config = storage.config()?;
+    let from = config.version()?;
+    if from > VERSION {
+        return Err(Error::Unsupported { found: from });
+    }
+
+    let mut to = from;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from) {
+        let Migration { version, name, run } = migration;
+        tracing::info!(version, name, "running storage migration");
+        let progress = run(storage).map_err(|source| Error::Migration {
+            version: *version,
+            name: *name,
+            source,
+        })?;
+        match progress {
+            Progress::Done => {
+                config.set_version(*version)?;
+                to = *version;
+                tracing::info!(version, name, "completed storage migration");
+            },
+            Progress::Retry => {
+                tracing::warn!(version, name, "storage migration incomplete, will retry");
+                break;
+            },
+        }
+    }
+
+    Ok(Migrated { from, to })
+}
+
+/// Convert tracking entries stored in the remotes config (tracking v1) into
+/// tracking refs (tracking v2), cf. [`tracking::migration`].
+fn tracking_v2(storage: &Storage) -> Result<Progress, BoxError> {
+    let urns = identities::any::list(storage)?
+        .map(|i| i.map(|i| i.urn()))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    let results = tracking::migration::migrate(storage, urns)?;
+    if results.failures.is_empty() {
+        Ok(Progress::Done)
+    } else {
+        for (err, urn, peer) in results.failures {
+            tracing::warn!(urn = %urn, peer = %peer, reason = %err, "failed to migrate");
+        }
+        Ok(Progress::Retry)
+    }
+}
+
+/// Write reflogs for all refs, cf. [`super::watch::Watch::refs`].
+fn reflog_always(storage: &Storage) -> Result<Progress, BoxError> {
+    storage.config()?.ensure_reflog()?;
+    Ok(Progress::Done)
+}
@@ -14,7 +14,7 @@ use std_ext::prelude::*;
 
 use crate::{
     git::types::{reference, Reference},
-    identities::git::{Identities, Urn},
+    identities::git::{cache::FileSystemCache, Identities, Urn},
     paths::Paths,
     PeerId,
 };
@@ -22,6 +22,7 @@ use crate::{
 use super::{
     config::{self, Config},
     glob::{self, Pattern},
+    snapshot::{self, Snapshot},
 };
 
 #[derive(Debug, Error)]
@@ -157,6 +158,7 @@ pub trait ReadOnlyStorage {
 pub struct ReadOnly {
     pub(super) backend: git2::Repository,
     pub(super) peer_id: PeerId,
+    pub(super) identities_cache: FileSystemCache,
 }
 
 impl ReadOnly {
@@ -176,7 +178,11 @@ impl ReadOnly {
         crate::git::init();
         let backend = git2::Repository::open(paths.git_dir())?;
         let peer_id = Config::try_from(&backend)?.peer_id()?;
-        Ok(Self { backend, peer_id })
+        Ok(Self {
+            backend,
+            peer_id,
+            identities_cache: FileSystemCache::new(paths.identities_cache_dir()),
+        })
     }
 
     pub fn peer_id(&self) -> &PeerId {
@@ -187,6 +193,10 @@ impl ReadOnly {
         self.backend.path()
     }
 
+    pub(crate) fn as_raw(&self) -> &git2::Repository {
+        &self.backend
+    }
+
     /// Check the existence of `oid` as a **commit**.
     ///
     /// The result will be `false` if:
@@ -211,8 +221,17 @@ impl ReadOnly {
         Ok(Config::try_from(&self.backend)?)
     }
 
+    /// Access the identities stored in this [`ReadOnly`].
+    ///
+    /// Verification results are cached in [`Paths::identities_cache_dir`].
     pub fn identities<'a, T: 'a>(&'a self) -> Identities<'a, T> {
-        Identities::from(&self.backend)
+        Identities::from(&self.backend).with_cache(&self.identities_cache)
+    }
+
+    /// Take a [`Snapshot`] of the refs of the whole storage, or only of the
+    /// namespace of `urn`.
+    pub fn snapshot(&self, urn: Option<&Urn>) -> Result<Snapshot, snapshot::Error> {
+        Snapshot::new(self, urn)
     }
 }
 
@@ -0,0 +1,191 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Consistent, read-only views of the storage.
+//!
+//! A [`Snapshot`] captures the refs of the storage, or of a single namespace,
+//! at a point in time, and serves [`ReadOnlyStorage`] queries from them. This
+//! allows multi-step reads, eg. listing refs and then resolving the identities
+//! they point to, without observing a concurrent update half-way through.
+//!
+//! The snapshot is materialised as a temporary bare repository, which borrows
+//! the objects of the storage via `objects/info/alternates`, and contains a
+//! copy of the captured refs. Its [`ReadOnly::path`] can thus also be handed
+//! to `git` processes, eg. `upload-pack`.
+//!
+//! Note that objects are only protected from being pruned by the
+//! `gc.pruneExpire` grace period of the storage, so snapshots should not be
+//! held on to for long.
+//!
+//! [`ReadOnlyStorage`]: super::ReadOnlyStorage
+
+use std::{collections::BTreeMap, fs, io, ops::Deref};
+
+use thiserror::Error;
+
+use super::ReadOnly;
+use crate::git::{types::Namespace, Urn};
+
+/// The number of times the refs are read when taking a [`Snapshot`], until two
+/// consecutive reads agree.
+const ATTEMPTS: usize = 5;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("refs did not settle after {0} attempts")]
+    Unstable(usize),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A read-only view of the storage at a point in time, see the [module
+/// documentation][self].
+///
+/// The temporary repository is removed when the [`Snapshot`] is dropped.
+pub struct Snapshot {
+    storage: ReadOnly,
+    refs: usize,
+    _tmp: tempfile::TempDir,
+}
+
+impl Snapshot {
+    /// Capture the refs of `storage`.
+    ///
+    /// If `urn` is given, only the namespace of `urn`, its tracking entries,
+    /// and the targets of symbolic refs within it (eg. `rad/ids/*`) are
+    /// captured.
+    ///
+    /// To avoid capturing an update which is in progress, the refs are read
+    /// until two consecutive reads agree. If they keep changing,
+    /// [`Error::Unstable`] is returned, and the caller may try again later.
+    #[tracing::instrument(level = "debug", skip(storage), err)]
+    pub fn new(storage: &ReadOnly, urn: Option<&Urn>) -> Result<Self, Error> {
+        let repo = &storage.backend;
+        let mut refs = capture(repo, urn)?;
+        let mut settled = false;
+        for _ in 1..ATTEMPTS {
+            let again = capture(repo, urn)?;
+            if again == refs {
+                settled = true;
+                break;
+            }
+            refs = again;
+        }
+        if !settled {
+            return Err(Error::Unstable(ATTEMPTS));
+        }
+
+        let tmp = tempfile::Builder::new().prefix("snapshot-").tempdir()?;
+        let backend = git2::Repository::init_bare(tmp.path())?;
+        fs::write(
+            backend.path().join("objects/info/alternates"),
+            format!("{}\n", repo.path().join("objects").display()),
+        )?;
+        fs::copy(repo.path().join("config"), backend.path().join("config"))?;
+
+        let mut packed = String::from("# pack-refs with: sorted \n");
+        for (name, target) in &refs {
+            match target {
+                Target::Direct(oid) => packed.push_str(&format!("{} {}\n", oid, name)),
+                Target::Symbolic(to) => {
+                    backend.reference_symbolic(name, to, true, "snapshot")?;
+                },
+            }
+        }
+        fs::write(backend.path().join("packed-refs"), packed)?;
+
+        Ok(Self {
+            storage: ReadOnly {
+                backend,
+                peer_id: storage.peer_id,
+                identities_cache: storage.identities_cache.clone(),
+            },
+            refs: refs.len(),
+            _tmp: tmp,
+        })
+    }
+
+    /// The number of refs captured.
+    pub fn refs(&self) -> usize {
+        self.refs
+    }
+}
+
+impl Deref for Snapshot {
+    type Target = ReadOnly;
+
+    fn deref(&self) -> &Self::Target {
+        &self.storage
+    }
+}
+
+impl AsRef<ReadOnly> for Snapshot {
+    fn as_ref(&self) -> &ReadOnly {
+        &self.storage
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Direct(git2::Oid),
+    Symbolic(String),
+}
+
+fn capture(repo: &git2::Repository, urn: Option<&Urn>) -> Result<BTreeMap<String, Target>, Error> {
+    let globs = match urn {
+        None => vec!["refs/*".to_owned()],
+        Some(urn) => vec![
+            format!("refs/namespaces/{}/*", Namespace::from(urn)),
+            format!("refs/rad/remotes/{}/*", urn.encode_id()),
+        ],
+    };
+
+    let mut refs = BTreeMap::new();
+    let mut symbolic = Vec::new();
+    for glob in &globs {
+        for reference in repo.references_glob(glob)? {
+            let reference = reference?;
+            if let Some((name, target)) = target(&reference) {
+                if let Target::Symbolic(to) = &target {
+                    symbolic.push(to.clone());
+                }
+                refs.insert(name, target);
+            }
+        }
+    }
+    // Capture the targets of symbolic refs pointing outside of the namespace
+    while let Some(name) = symbolic.pop() {
+        if refs.contains_key(&name) {
+            continue;
+        }
+        match repo.find_reference(&name) {
+            Ok(reference) => {
+                if let Some((name, target)) = target(&reference) {
+                    if let Target::Symbolic(to) = &target {
+                        symbolic.push(to.clone());
+                    }
+                    refs.insert(name, target);
+                }
+            },
+            Err(e) if git_ext::is_not_found_err(&e) => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(refs)
+}
+
+fn target(reference: &git2::Reference) -> Option<(String, Target)> {
+    let name = reference.name()?.to_owned();
+    match reference.target() {
+        Some(oid) => Some((name, Target::Direct(oid))),
+        None => reference
+            .symbolic_target()
+            .map(|to| (name, Target::Symbolic(to.to_owned()))),
+    }
+}
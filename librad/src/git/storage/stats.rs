@@ -0,0 +1,325 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Statistics about the contents of the storage, per [`Urn`].
+//!
+//! Intended to inform capacity decisions, eg. which projects to keep seeding.
+//! Computing the statistics requires walking all objects reachable from the
+//! namespace, and is thus not cheap for large projects.
+
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::{Hash as _, Hasher as _},
+    str::FromStr as _,
+    sync::Arc,
+};
+
+use git_ext::is_not_found_err;
+use parking_lot::Mutex;
+use thiserror::Error;
+
+use super::Storage;
+use crate::{
+    collaborative_objects::TypeName,
+    git::{
+        identities,
+        tracking,
+        types::{Namespace, RefsCategory},
+        Urn,
+    },
+    PeerId,
+};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+
+    #[error(transparent)]
+    Identities(#[from] identities::Error),
+
+    #[error(transparent)]
+    Tracked(#[from] tracking::error::TrackedPeers),
+}
+
+/// Statistics of a single [`Urn`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UrnStats {
+    pub urn: Urn,
+    /// Number of refs in the namespace, including those of remotes.
+    pub refs: usize,
+    /// Number of remotes tracked for the [`Urn`].
+    pub tracked: usize,
+    /// Number of distinct objects reachable from the refs in the namespace.
+    pub objects: usize,
+    /// Uncompressed size in bytes of the objects reachable from the refs in
+    /// the namespace.
+    ///
+    /// This is an upper bound of the size of a packfile containing only those
+    /// objects. Note that objects may be shared with other namespaces.
+    pub size: u64,
+    /// The most recent commit time of the refs in the namespace, in seconds
+    /// since the epoch.
+    ///
+    /// As `rad/signed_refs` are committed to whenever the refs change, this
+    /// approximates the time the namespace was last updated.
+    pub updated: Option<i64>,
+    /// Number of distinct collaborative objects by type, across all remotes.
+    pub cobs: BTreeMap<TypeName, usize>,
+}
+
+/// Compute the [`UrnStats`] of all identities in `storage`.
+pub fn all(storage: &Storage) -> Result<Vec<UrnStats>, Error> {
+    identities::any::list_urns(storage)?
+        .map(|urn| {
+            urn.map_err(Error::from)
+                .and_then(|urn| for_urn(storage, &urn))
+        })
+        .collect()
+}
+
+/// Compute the [`UrnStats`] of `urn`.
+///
+/// Symbolic refs (eg. `rad/ids/*`) are counted, but the objects they point to
+/// are attributed to the namespace of their target.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn for_urn(storage: &Storage, urn: &Urn) -> Result<UrnStats, Error> {
+    let repo = storage.as_raw();
+    let prefix = format!("refs/namespaces/{}/", Namespace::from(urn));
+
+    let mut refs = 0;
+    let mut cobs: BTreeMap<TypeName, BTreeSet<String>> = BTreeMap::new();
+    let mut reachable = Reachable::new(repo)?;
+    for reference in repo.references_glob(&format!("{}*", prefix))? {
+        let reference = reference?;
+        refs += 1;
+
+        if let Some((typename, id)) = reference
+            .name()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(cob)
+        {
+            if let Ok(typename) = TypeName::from_str(typename) {
+                cobs.entry(typename).or_default().insert(id.to_owned());
+            }
+        }
+        if let Some(target) = reference.target() {
+            reachable.tip(target)?;
+        }
+    }
+    reachable.walk()?;
+
+    let tracked = tracking::tracked_peers(storage.read_only(), Some(urn))?
+        .filter_map(Result::ok)
+        .count();
+
+    Ok(UrnStats {
+        urn: urn.clone(),
+        refs,
+        tracked,
+        objects: reachable.seen.len(),
+        size: reachable.size,
+        updated: reachable.updated,
+        cobs: cobs.into_iter().map(|(ty, ids)| (ty, ids.len())).collect(),
+    })
+}
+
+/// Uncompressed size in bytes of the objects reachable from the refs of `urn`.
+///
+/// If `remote` is given, only the refs of that remote are considered.
+pub fn size(storage: &Storage, urn: &Urn, remote: Option<&PeerId>) -> Result<u64, Error> {
+    let repo = storage.as_raw();
+    size_of(repo, &tips(repo, urn, remote)?)
+}
+
+/// Cache of [`size`]s, which only walks the reachable objects again if any of
+/// the refs in question changed.
+///
+/// Cloning a [`SizeCache`] yields a handle to the same cache.
+#[derive(Clone, Default)]
+pub struct SizeCache {
+    #[allow(clippy::type_complexity)]
+    sizes: Arc<Mutex<HashMap<(Urn, Option<PeerId>), (u64, u64)>>>,
+}
+
+impl SizeCache {
+    /// Same as [`size`], but returns the cached size if the refs did not change
+    /// since it was computed.
+    pub fn size(
+        &self,
+        storage: &Storage,
+        urn: &Urn,
+        remote: Option<&PeerId>,
+    ) -> Result<u64, Error> {
+        let repo = storage.as_raw();
+        let tips = tips(repo, urn, remote)?;
+        let fingerprint = {
+            let mut hasher = DefaultHasher::new();
+            tips.hash(&mut hasher);
+            hasher.finish()
+        };
+        let key = (Urn::new(urn.id), remote.copied());
+        if let Some((seen, size)) = self.sizes.lock().get(&key) {
+            if *seen == fingerprint {
+                return Ok(*size);
+            }
+        }
+
+        let size = size_of(repo, &tips)?;
+        self.sizes.lock().insert(key, (fingerprint, size));
+        Ok(size)
+    }
+}
+
+/// The targets of the refs of `urn` (or only those of `remote`), sorted by
+/// name.
+fn tips(
+    repo: &git2::Repository,
+    urn: &Urn,
+    remote: Option<&PeerId>,
+) -> Result<Vec<(String, git2::Oid)>, Error> {
+    let mut prefix = format!("refs/namespaces/{}/", Namespace::from(urn));
+    if let Some(remote) = remote {
+        prefix.push_str(&format!("refs/remotes/{}/", remote));
+    }
+
+    let mut tips = Vec::new();
+    for reference in repo.references_glob(&format!("{}*", prefix))? {
+        let reference = reference?;
+        if let (Some(name), Some(target)) = (reference.name(), reference.target()) {
+            tips.push((name.to_owned(), target));
+        }
+    }
+    tips.sort();
+
+    Ok(tips)
+}
+
+fn size_of(repo: &git2::Repository, tips: &[(String, git2::Oid)]) -> Result<u64, Error> {
+    let mut reachable = Reachable::new(repo)?;
+    for (_, target) in tips {
+        reachable.tip(*target)?;
+    }
+    reachable.walk()?;
+
+    Ok(reachable.size)
+}
+
+/// Parse `refs/[remotes/<peer>/]cobs/<typename>/<id>` into `(typename, id)`.
+fn cob(name: &str) -> Option<(&str, &str)> {
+    let name = name.strip_prefix("refs/")?;
+    let name = match name.strip_prefix("remotes/") {
+        Some(remote) => remote.split_once('/')?.1,
+        None => name,
+    };
+    name.strip_prefix(RefsCategory::Cobs.to_string().as_str())?
+        .strip_prefix('/')?
+        .rsplit_once('/')
+}
+
+/// The set of objects reachable from a number of tips.
+struct Reachable<'a> {
+    repo: &'a git2::Repository,
+    odb: git2::Odb<'a>,
+    revwalk: git2::Revwalk<'a>,
+    seen: HashSet<git2::Oid>,
+    size: u64,
+    updated: Option<i64>,
+}
+
+impl<'a> Reachable<'a> {
+    fn new(repo: &'a git2::Repository) -> Result<Self, git2::Error> {
+        Ok(Self {
+            repo,
+            odb: repo.odb()?,
+            revwalk: repo.revwalk()?,
+            seen: HashSet::new(),
+            size: 0,
+            updated: None,
+        })
+    }
+
+    fn tip(&mut self, oid: git2::Oid) -> Result<(), git2::Error> {
+        let object = match self.repo.find_object(oid, None) {
+            Ok(object) => object,
+            Err(e) if is_not_found_err(&e) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        match object.kind() {
+            Some(git2::ObjectType::Commit) => {
+                if let Some(commit) = object.as_commit() {
+                    let time = commit.committer().when().seconds();
+                    self.updated = Some(self.updated.map_or(time, |t| t.max(time)));
+                }
+                self.revwalk.push(oid)
+            },
+            Some(git2::ObjectType::Tag) => {
+                if self.add(oid)? {
+                    if let Some(tag) = object.as_tag() {
+                        self.tip(tag.target_id())?
+                    }
+                }
+                Ok(())
+            },
+            Some(git2::ObjectType::Tree) => self.tree(oid),
+            _ => self.add(oid).map(|_| ()),
+        }
+    }
+
+    fn walk(&mut self) -> Result<(), git2::Error> {
+        let commits = self.revwalk.by_ref().collect::<Result<Vec<_>, _>>()?;
+        for oid in commits {
+            if self.add(oid)? {
+                let tree = self.repo.find_commit(oid)?.tree_id();
+                self.tree(tree)?
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tree(&mut self, oid: git2::Oid) -> Result<(), git2::Error> {
+        use git2::{ObjectType, TreeWalkMode, TreeWalkResult};
+
+        if !self.add(oid)? {
+            return Ok(());
+        }
+
+        let tree = self.repo.find_tree(oid)?;
+        let mut res = Ok(());
+        let walked = tree.walk(TreeWalkMode::PreOrder, |_, entry| {
+            let kind = entry.kind();
+            // Submodules are not part of the repository
+            if !matches!(kind, Some(ObjectType::Tree) | Some(ObjectType::Blob)) {
+                return TreeWalkResult::Ok;
+            }
+            match self.add(entry.id()) {
+                Ok(true) => TreeWalkResult::Ok,
+                Ok(false) if kind == Some(ObjectType::Tree) => TreeWalkResult::Skip,
+                Ok(false) => TreeWalkResult::Ok,
+                Err(e) => {
+                    res = Err(e);
+                    TreeWalkResult::Abort
+                },
+            }
+        });
+        res?;
+        walked
+    }
+
+    /// Record `oid` as reachable, returning `false` if it was seen before.
+    fn add(&mut self, oid: git2::Oid) -> Result<bool, git2::Error> {
+        if !self.seen.insert(oid) {
+            return Ok(false);
+        }
+        match self.odb.read_header(oid) {
+            Ok((size, _)) => {
+                self.size += size as u64;
+                Ok(true)
+            },
+            Err(e) if is_not_found_err(&e) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -4,9 +4,11 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 use std::{
+    collections::{hash_map::Entry, HashMap},
+    convert::TryFrom as _,
     fs,
-    io,
-    path::{Path, PathBuf},
+    io::{self, Read as _, Seek as _, SeekFrom},
+    path::{Component, Path, PathBuf},
     sync::{mpsc, Arc},
 };
 
@@ -14,6 +16,7 @@ use notify::Watcher as _;
 use thiserror::Error;
 
 use super::Storage;
+use crate::{git::Urn, git_ext as ext};
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -42,6 +45,21 @@ pub struct NamespaceEvent {
     pub kind: EventKind,
 }
 
+/// A ref inside a namespace was created, updated or deleted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefEvent {
+    /// The namespace the ref belongs to.
+    pub urn: Urn,
+    /// The name of the ref relative to the namespace, eg.
+    /// `refs/remotes/<peer>/heads/main`.
+    pub name: ext::RefLike,
+    /// The previous target, `None` if the ref was created (or the previous
+    /// target is not known).
+    pub old: Option<ext::Oid>,
+    /// The new target, `None` if the ref was deleted.
+    pub new: Option<ext::Oid>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 #[non_exhaustive]
 pub enum EventKind {
@@ -72,9 +90,8 @@ impl<'a> Watch<'a> {
     /// * the directory is watched _non-recursively_, as this tends to miss
     ///   events with most filesystem event backends
     ///
-    /// By default [`super::Config`] sets `core.logAllRefUpdates` to `true`
-    /// (**not** "always"), and refs created by this library will have a
-    /// corresponding reflog created. It is currently unlikely that
+    /// [`super::Config`] sets `core.logAllRefUpdates` to "always", so all refs
+    /// will have a corresponding reflog created. It is currently unlikely that
     /// [`EventKind`]s other than [`EventKind::Create`] will be emitted.
     pub fn namespaces(&self) -> Result<(Watcher, impl Iterator<Item = NamespaceEvent>), Error> {
         use notify::{Op, RawEvent, RecursiveMode::NonRecursive};
@@ -134,4 +151,207 @@ impl<'a> Watch<'a> {
 
         Ok((Watcher(Arc::new(watcher)), rx))
     }
+
+    /// Watch for updates of refs within namespaces.
+    ///
+    /// Implemented by recursively watching `$GIT_DIR/logs/refs/namespaces`,
+    /// and reading the entries appended to the reflogs. Deletion of a ref is
+    /// detected by its reflog being removed. Note that:
+    ///
+    /// * only refs which have a reflog are reported on. The storage sets
+    ///   `core.logAllRefUpdates` to `always` when it is initialised or
+    ///   migrated, so this includes refs created by external `git` processes,
+    ///   eg. `git fetch`, unless the setting is changed afterwards.
+    /// * updates to refs whose reflog is created concurrently with its parent
+    ///   directory may be missed, as the directory is not yet being watched
+    ///   when the first entry is written
+    /// * the `old` target of the first update observed of a pre-existing ref is
+    ///   taken from its reflog, and is thus only as accurate as the reflog is
+    /// * rewrites of a reflog (eg. by `git reflog expire`) do not emit events
+    pub fn refs(&self) -> Result<(Watcher, impl Iterator<Item = RefEvent>), Error> {
+        use notify::{Op, RawEvent, RecursiveMode::Recursive};
+
+        let repo_path = self.storage.path().to_owned();
+        let reflogs_path = repo_path.join("logs");
+        let namespaces_path = reflogs_path.join("refs/namespaces");
+
+        if !namespaces_path.exists() {
+            fs::create_dir_all(&namespaces_path)?;
+        }
+
+        let (tx, rx) = mpsc::channel();
+
+        let mut watcher = notify::raw_watcher(tx)?;
+        watcher.watch(&namespaces_path, Recursive)?;
+
+        let mut reflogs = Reflogs::default();
+        let rx = rx.into_iter().flat_map(move |evt| {
+            tracing::trace!("{:?}", evt);
+
+            let (path, op) = match evt {
+                RawEvent {
+                    path: Some(path),
+                    op: Ok(op),
+                    cookie: _,
+                } => (path, op),
+                _ => return vec![],
+            };
+            let (urn, name) = match path
+                .strip_prefix(&reflogs_path)
+                .ok()
+                .and_then(namespaced_ref)
+            {
+                Some(parsed) => parsed,
+                None => {
+                    tracing::trace!("not a namespaced reflog");
+                    return vec![];
+                },
+            };
+
+            let updates = if op.contains(Op::REMOVE) || !path.exists() {
+                reflogs
+                    .remove(&path)
+                    .map(|old| (Some(old), None))
+                    .into_iter()
+                    .collect()
+            } else if !path.is_file() {
+                vec![]
+            } else if op.contains(Op::RENAME) {
+                reflogs.rewritten(&path).unwrap_or_else(|e| {
+                    tracing::warn!(err = %e, "failed to read reflog {}", path.display());
+                });
+                vec![]
+            } else if op.intersects(Op::CREATE | Op::WRITE | Op::CLOSE_WRITE) {
+                reflogs
+                    .read(&path, op.contains(Op::CREATE))
+                    .unwrap_or_else(|e| {
+                        tracing::warn!(err = %e, "failed to read reflog {}", path.display());
+                        vec![]
+                    })
+            } else {
+                vec![]
+            };
+
+            updates
+                .into_iter()
+                .map(|(old, new)| RefEvent {
+                    urn: urn.clone(),
+                    name: name.clone(),
+                    old,
+                    new,
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Ok((Watcher(Arc::new(watcher)), rx))
+    }
+}
+
+/// Parse a reflog path of the form `refs/namespaces/<urn>/refs/<name>`.
+fn namespaced_ref(path: &Path) -> Option<(Urn, ext::RefLike)> {
+    if path.extension() == Some("lock".as_ref()) {
+        return None;
+    }
+
+    let mut iter = path.components();
+    if iter.next() != Some(Component::Normal("refs".as_ref()))
+        || iter.next() != Some(Component::Normal("namespaces".as_ref()))
+    {
+        return None;
+    }
+    let urn = match iter.next()? {
+        Component::Normal(namespace) => Urn::try_from_id(namespace.to_str()?).ok()?,
+        _ => return None,
+    };
+    let name = iter.as_path();
+    if !name.starts_with("refs") || name.components().count() < 3 {
+        return None;
+    }
+
+    Some((urn, ext::RefLike::try_from(name).ok()?))
+}
+
+/// The position up to which a reflog has been read, and the target of the
+/// last entry read.
+struct Tail {
+    offset: u64,
+    last: Option<ext::Oid>,
+}
+
+#[derive(Default)]
+struct Reflogs(HashMap<PathBuf, Tail>);
+
+type Update = (Option<ext::Oid>, Option<ext::Oid>);
+
+impl Reflogs {
+    /// Read the entries appended to the reflog at `path` since it was last
+    /// read.
+    ///
+    /// If the reflog was not read before, all entries are returned if it was
+    /// just `created`, otherwise only the last one.
+    fn read(&mut self, path: &Path, created: bool) -> io::Result<Vec<Update>> {
+        let mut file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        let (tail, last_only) = match self.0.entry(path.to_owned()) {
+            Entry::Occupied(entry) => (entry.into_mut(), false),
+            Entry::Vacant(entry) => (
+                entry.insert(Tail {
+                    offset: 0,
+                    last: None,
+                }),
+                !created,
+            ),
+        };
+        // Truncated
+        if tail.offset > len {
+            tail.offset = 0;
+        }
+
+        file.seek(SeekFrom::Start(tail.offset))?;
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+        // Leave partially written entries for the next event
+        let complete = buf.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        tail.offset += complete as u64;
+
+        let mut updates = buf[..complete]
+            .lines()
+            .filter_map(parse_entry)
+            .collect::<Vec<_>>();
+        if last_only {
+            updates = updates.pop().into_iter().collect();
+        }
+        if let Some((_, new)) = updates.last() {
+            tail.last = *new;
+        }
+
+        Ok(updates)
+    }
+
+    /// The reflog at `path` was replaced, skip all its entries.
+    fn rewritten(&mut self, path: &Path) -> io::Result<()> {
+        self.0.remove(path);
+        self.read(path, true).map(|_| ())
+    }
+
+    /// The reflog at `path` was removed, return the last known target.
+    fn remove(&mut self, path: &Path) -> Option<ext::Oid> {
+        self.0.remove(path).and_then(|tail| tail.last)
+    }
+}
+
+/// Parse the old and new target of a reflog entry.
+///
+/// Entries are of the form `<old> <new> <committer>\t<message>`, where the
+/// zero oid denotes absence of a target.
+fn parse_entry(line: &str) -> Option<Update> {
+    fn oid(hex: &str) -> Option<Option<ext::Oid>> {
+        let oid = git2::Oid::from_str(hex).ok()?;
+        Some((!oid.is_zero()).then(|| oid.into()))
+    }
+
+    let mut iter = line.splitn(3, ' ');
+    let old = oid(iter.next()?)?;
+    let new = oid(iter.next()?)?;
+    Some((old, new))
 }
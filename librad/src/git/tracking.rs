@@ -27,6 +27,7 @@ pub use link_tracking::{
             tracked,
             tracked_peers,
             untrack,
+            untrack_all,
             PreviousError,
             Ref,
             Tracked,
@@ -42,8 +43,8 @@ pub use link_tracking::{
 
 /// Migration from tracking-v1 to tracking-v2.
 ///
-/// NOTE: This is used in `Storage::open` and will be deprecated once enough
-/// time has passed for upstream dependencies to migrate to the latest version.
+/// NOTE: This is run by `Storage::open` as the first storage migration, cf.
+/// [`crate::git::storage::migration`].
 pub mod migration {
     use std::borrow::Cow;
 
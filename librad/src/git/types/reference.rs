@@ -279,6 +279,41 @@ impl<N, R> Reference<N, R, One> {
         }
     }
 
+    /// Build a reference that points to:
+    ///     * `refs/namespaces/<namespace>/refs/rad/attestations/<id>`
+    ///     * `refs/namespaces/<namespace>/refs/remote/<peer_id>/rad/
+    ///       attestations/<id>`
+    pub fn rad_attestation(
+        namespace: impl Into<Option<N>>,
+        remote: impl Into<Option<R>>,
+        subject: &Urn,
+    ) -> Self {
+        Self {
+            remote: remote.into(),
+            category: RefsCategory::Rad,
+            name: reflike!("attestations")
+                .join(ext::RefLike::try_from(subject.encode_id()).unwrap()),
+            namespace: namespace.into(),
+        }
+    }
+
+    /// Build a reference that points to:
+    ///     * `refs/namespaces/<namespace>/refs/rad/certs/<peer_id>`
+    ///     * `refs/namespaces/<namespace>/refs/remote/<peer_id>/rad/certs/
+    ///       <peer_id>`
+    pub fn rad_certificate(
+        namespace: impl Into<Option<N>>,
+        remote: impl Into<Option<R>>,
+        device: &PeerId,
+    ) -> Self {
+        Self {
+            remote: remote.into(),
+            category: RefsCategory::Rad,
+            name: reflike!("certs").join(device),
+            namespace: namespace.into(),
+        }
+    }
+
     /// Build a reference that points to:
     ///     * `refs/namespaces/<namespace>/refs/rad/signed_refs`
     ///     * `refs/namespaces/<namespace>/refs/remote/<peer_id>/rad/
@@ -60,6 +60,7 @@ pub enum CloseReason {
     InvalidUpgrade = 6,
     TooManyConnections = 7,
     Timeout = 8,
+    QuotaExceeded = 9,
 }
 
 impl CloseReason {
@@ -70,6 +71,7 @@ impl CloseReason {
             Self::InvalidUpgrade => b"invalid or unsupported protocol upgrade",
             Self::TooManyConnections => b"too many connections",
             Self::Timeout => b"timeout",
+            Self::QuotaExceeded => b"quota exceeded",
         }
     }
 }
@@ -35,6 +35,7 @@ pub use crate::net::protocol::{
 pub mod error;
 pub mod storage;
 pub use storage::Storage as PeerStorage;
+pub mod subscription;
 
 #[derive(Clone)]
 pub struct Config<Signer, Guard = config::DenyAll> {
@@ -96,6 +97,7 @@ pub struct Peer<S, G = config::DenyAll> {
     caches: protocol::Caches,
     spawner: Arc<Spawner>,
     repl: Replication,
+    drain: protocol::Drain,
 }
 
 impl<S, G> Peer<S, G>
@@ -124,7 +126,9 @@ where
             protocol::Caches { urns }
         };
 
-        let repl = Replication::new(&config.protocol.paths, config.protocol.replication)?;
+        let drain = protocol::Drain::new();
+        let repl = Replication::new(&config.protocol.paths, config.protocol.replication)?
+            .with_drain(drain.clone());
 
         let peer_store = PeerStorage::new(
             storage::Config {
@@ -153,6 +157,7 @@ where
             caches,
             spawner,
             repl,
+            drain,
         })
     }
 
@@ -173,7 +178,12 @@ where
             user_storage: self.user_store.clone().into(),
             ..self.config.clone().into()
         };
-        Client::new(config, self.spawner.clone(), self.phone.clone())
+        Ok(Client::with_replication(
+            config,
+            self.spawner.clone(),
+            self.phone.clone(),
+            self.repl.clone(),
+        ))
     }
 
     pub fn announce(&self, have: gossip::Payload) -> Result<(), gossip::Payload> {
@@ -245,6 +255,43 @@ where
         self.phone.stats().await
     }
 
+    /// Statistics of replications initiated through this peer, including
+    /// those run by its [`Peer::client`]s and in response to request-pulls.
+    pub fn replication_stats(&self) -> replication::Stats {
+        self.repl.stats()
+    }
+
+    /// Register a unit of work, such that [`Peer::drain`] waits for it to
+    /// complete before the protocol is stopped.
+    ///
+    /// Returns `None` if the [`Peer`] is draining already.
+    pub fn in_flight(&self) -> Option<protocol::drain::InFlight> {
+        self.drain.enter()
+    }
+
+    /// Wind down the protocol gracefully.
+    ///
+    /// New connections and streams are refused, peers in the active
+    /// membership view are sent a `Disconnect`, and in-flight replications
+    /// and served streams (git fetches, request-pulls) are given up to
+    /// `timeout` to complete.
+    ///
+    /// This does not stop the protocol: call the function returned from
+    /// [`protocol::Bound::accept`] afterwards. Once called, the [`Peer`]
+    /// stays in draining mode, and can thus not be re-bound.
+    pub async fn drain(&self, timeout: Duration) -> Result<(), error::Drain> {
+        self.drain.start();
+        let notified = self.phone.leave().await;
+        tracing::info!(
+            membership = notified,
+            inflight = self.drain.inflight(),
+            "draining"
+        );
+        self.drain.wait(timeout).await.map_err(|_| error::Drain {
+            inflight: self.drain.inflight(),
+        })
+    }
+
     #[deprecated(
         note = "use of `self.interrogate(..)` is deprecated in favour of going through `self.client(..)?.interrogate(..)`"
     )]
@@ -323,6 +370,18 @@ where
         self.phone.subscribe()
     }
 
+    /// Like [`Peer::subscribe`], but only yield the events matching `filter`.
+    ///
+    /// Instead of an error, a [`subscription::Event::Lagged`] marker is
+    /// yielded if the subscriber falls behind. The stream ends when the
+    /// network is shut down.
+    pub fn subscribe_filtered(
+        &self,
+        filter: subscription::Filter,
+    ) -> impl futures::Stream<Item = subscription::Event> {
+        subscription::filtered(self.subscribe(), filter)
+    }
+
     /// Borrow a [`git::storage::Storage`] from the pool, and run a blocking
     /// computation on it.
     pub async fn using_storage<F, T>(&self, blocking: F) -> Result<T, error::Storage>
@@ -348,6 +407,43 @@ where
             .await)
     }
 
+    /// Run the storage maintenance tasks configured in `config`.
+    ///
+    /// Waits for all [`git::storage::Storage`]s of the user-facing pool
+    /// (which is shared with [`Peer::client`]s), the protocol pool, and the
+    /// given `pools` to be returned, and holds on to them until maintenance
+    /// has completed. Replication and other users of the storage are thus
+    /// suspended while maintenance is running.
+    ///
+    /// `pools` must include any other [`git::storage::Pool`] which writes to
+    /// the storage, eg. one used by the [`RequestPullGuard`].
+    pub async fn maintain(
+        &self,
+        config: git::storage::maintenance::Config,
+        pools: &[&git::storage::Pool<git::storage::Storage>],
+    ) -> Result<git::storage::maintenance::Report, error::Maintenance> {
+        use git::storage::maintenance::{self, Lock};
+
+        let mut locks = Vec::with_capacity(pools.len() + 2);
+        for pool in [&self.user_store, self.peer_store.pool()]
+            .iter()
+            .chain(pools)
+        {
+            let lock = Lock::acquire(pool)
+                .await
+                .map_err(error::Maintenance::Lock)?;
+            locks.push(lock);
+        }
+        self.spawner
+            .blocking(move || {
+                let storage = locks[0].storage().ok_or(error::Maintenance::EmptyPool)?;
+                let report = maintenance::run(storage, &config)?;
+                drop(locks);
+                Ok(report)
+            })
+            .await
+    }
+
     /// Borrow a [`git::storage::Storage`] from the pool directly.
     ///
     /// # WARNING
@@ -380,6 +476,8 @@ where
             self.config.signer.clone(),
             self.peer_store.clone(),
             self.caches.clone(),
+            self.drain.clone(),
+            self.repl.clone(),
         )
         .await
     }
@@ -60,6 +60,25 @@ pub enum Replicate {
     Replicate(#[from] replication::error::Replicate),
 }
 
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Maintenance {
+    #[error("failed to acquire exclusive access to storage")]
+    Lock(#[source] storage::PoolError),
+
+    #[error("storage pool is empty")]
+    EmptyPool,
+
+    #[error(transparent)]
+    Maintenance(#[from] storage::maintenance::Error),
+}
+
 #[derive(Debug, Error)]
 #[error("unable to obtain connection to {0}")]
 pub struct NoConnection(pub PeerId);
+
+#[derive(Debug, Error)]
+#[error("timed out waiting for {inflight} in-flight tasks to complete")]
+pub struct Drain {
+    pub inflight: usize,
+}
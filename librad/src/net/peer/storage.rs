@@ -67,6 +67,11 @@ impl Storage {
         }
     }
 
+    /// The pool backing this storage, cf. [`storage::maintenance::Lock`].
+    pub(crate) fn pool(&self) -> &Pool<storage::Storage> {
+        &self.pool
+    }
+
     fn is_rate_limited(&self, remote_peer: PeerId, urn: Urn) -> bool {
         self.rate.check_key(&(remote_peer, urn)).is_err()
     }
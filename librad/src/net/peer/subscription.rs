@@ -0,0 +1,164 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Filtered subscriptions to [`ProtocolEvent`]s.
+
+use std::collections::BTreeSet;
+
+use futures::{future, Stream, StreamExt as _};
+
+use super::ProtocolEvent;
+use crate::{
+    git::Urn,
+    net::protocol::{event::upstream::Gossip, membership::Transition, RecvError},
+    PeerId,
+};
+
+/// The kinds of [`ProtocolEvent`]s.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Kind {
+    Endpoint,
+    Gossip,
+    Membership,
+    Caches,
+}
+
+impl Kind {
+    pub fn of(event: &ProtocolEvent) -> Self {
+        match event {
+            ProtocolEvent::Endpoint(_) => Self::Endpoint,
+            ProtocolEvent::Gossip(_) => Self::Gossip,
+            ProtocolEvent::Membership(_) => Self::Membership,
+            ProtocolEvent::Caches(_) => Self::Caches,
+        }
+    }
+}
+
+/// Predicate selecting the [`ProtocolEvent`]s a subscriber is interested in.
+///
+/// The default filter matches all events. Each of the constraints narrows the
+/// selection further, ie. an event must satisfy all of them in order to match.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    urns: Option<BTreeSet<Urn>>,
+    kinds: Option<BTreeSet<Kind>>,
+    peers: Option<BTreeSet<PeerId>>,
+}
+
+impl Filter {
+    /// Only match events pertaining to `urn`.
+    ///
+    /// The path component of `urn` is ignored. May be given multiple times,
+    /// in which case events pertaining to any of the URNs match. Events which
+    /// don't pertain to any URN are not matched.
+    pub fn urn(mut self, urn: Urn) -> Self {
+        self.urns
+            .get_or_insert_with(BTreeSet::new)
+            .insert(Urn::new(urn.id));
+        self
+    }
+
+    /// Only match events of the given [`Kind`].
+    ///
+    /// May be given multiple times, in which case events of any of the kinds
+    /// match.
+    pub fn kind(self, kind: Kind) -> Self {
+        self.kinds(Some(kind))
+    }
+
+    /// Only match events of any of the given [`Kind`]s.
+    ///
+    /// Unlike not calling [`Filter::kind`] at all, an empty `kinds` matches no
+    /// events.
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = Kind>) -> Self {
+        self.kinds.get_or_insert_with(BTreeSet::new).extend(kinds);
+        self
+    }
+
+    /// Only match events pertaining to `peer`.
+    ///
+    /// For gossip events, this is the provider, for membership events the
+    /// peer whose membership changed. May be given multiple times, in which
+    /// case events pertaining to any of the peers match. Events which don't
+    /// pertain to any peer are not matched.
+    pub fn peer(mut self, peer: PeerId) -> Self {
+        self.peers.get_or_insert_with(BTreeSet::new).insert(peer);
+        self
+    }
+
+    pub fn matches(&self, event: &ProtocolEvent) -> bool {
+        if let Some(kinds) = &self.kinds {
+            if !kinds.contains(&Kind::of(event)) {
+                return false;
+            }
+        }
+        if let Some(urns) = &self.urns {
+            match urn_of(event) {
+                Some(urn) if urns.contains(&Urn::new(urn.id)) => {},
+                _ => return false,
+            }
+        }
+        if let Some(peers) = &self.peers {
+            match peer_of(event) {
+                Some(peer) if peers.contains(&peer) => {},
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+fn urn_of(event: &ProtocolEvent) -> Option<&Urn> {
+    match event {
+        ProtocolEvent::Gossip(gossip) => match gossip.as_ref() {
+            Gossip::Put { payload, .. } => Some(&payload.urn),
+        },
+        _ => None,
+    }
+}
+
+fn peer_of(event: &ProtocolEvent) -> Option<PeerId> {
+    match event {
+        ProtocolEvent::Gossip(gossip) => match gossip.as_ref() {
+            Gossip::Put { provider, .. } => Some(provider.peer_id),
+        },
+        ProtocolEvent::Membership(transition) => Some(match transition {
+            Transition::Promoted(info) => info.peer_id,
+            Transition::Demoted(info) => info.peer_id,
+            Transition::Evicted(info) => info.peer_id,
+        }),
+        _ => None,
+    }
+}
+
+/// Item of a filtered subscription.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// A [`ProtocolEvent`] matching the [`Filter`].
+    Protocol(ProtocolEvent),
+    /// The subscriber did not keep up with the rate of events, and `dropped`
+    /// events were lost.
+    ///
+    /// Note that the dropped events have not been matched against the
+    /// [`Filter`], so not all of them may have been of interest.
+    Lagged { dropped: u64 },
+}
+
+/// Filter the raw event stream, as returned by [`super::Peer::subscribe`].
+///
+/// The resulting stream ends when the network is shut down.
+pub fn filtered<S>(events: S, filter: Filter) -> impl Stream<Item = Event>
+where
+    S: Stream<Item = Result<ProtocolEvent, RecvError>>,
+{
+    events
+        .take_while(|event| future::ready(!matches!(event, Err(RecvError::Closed))))
+        .filter_map(move |event| {
+            future::ready(match event {
+                Ok(event) if filter.matches(&event) => Some(Event::Protocol(event)),
+                Ok(_) | Err(RecvError::Closed) => None,
+                Err(RecvError::Lagged(dropped)) => Some(Event::Lagged { dropped }),
+            })
+        })
+}
@@ -30,11 +30,15 @@ use crate::{
     Signer,
 };
 
+pub mod bandwidth;
 pub mod broadcast;
 
 pub mod cache;
 pub use cache::Caches;
 
+pub mod drain;
+pub use drain::Drain;
+
 pub mod error;
 pub mod event;
 pub mod gossip;
@@ -179,6 +183,8 @@ pub async fn bind<Sign, Store, Guard>(
     signer: Sign,
     storage: Store,
     caches: cache::Caches,
+    drain: Drain,
+    repl: replication::Replication,
 ) -> Result<Bound<Store, Guard>, error::Bootstrap>
 where
     Sign: Signer + Clone + Send + Sync + 'static,
@@ -201,11 +207,11 @@ where
     );
     let gossip = broadcast::State::new(
         Storage::new(storage.clone(), config.rate_limits.storage.clone()),
-        (),
+        broadcast::Stats::default(),
     );
     let request_pull = request_pull::State::new(
         Storage::new(storage, config.rate_limits.storage),
-        config.paths.clone(),
+        repl,
         config.request_pull,
     );
     let limits = RateLimits {
@@ -228,6 +234,8 @@ where
         caches,
         spawner,
         limits,
+        drain,
+        bandwidth: bandwidth::Bandwidth::new(config.rate_limits.bandwidth),
     };
 
     Ok(Bound {
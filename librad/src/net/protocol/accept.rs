@@ -139,6 +139,7 @@ where
                 Downstream::Interrogation(x) => control::interrogation(x).await,
                 Downstream::RequestPull(x) => control::request_pull(x).await,
                 Downstream::Connect(x) => control::connect(&state, x).await,
+                Downstream::Leave(x) => control::leave(&state, x).await,
             },
         }
     }
@@ -0,0 +1,415 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Accounting of the bytes transferred while serving `upload-pack` and
+//! request-pull streams.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use futures::{
+    future::{self, Either},
+    StreamExt as _,
+};
+use parking_lot::Mutex;
+use thiserror::Error;
+
+use crate::{git::Urn, net::quic, PeerId};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Interval at which the usage of a [`Transfer`] in progress is recorded.
+pub const RECORD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Byte limits of a [`Quota`].
+///
+/// `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Limit {
+    /// Bytes per day.
+    pub daily: Option<u64>,
+    /// Bytes per [`Quota::burst_window`].
+    pub burst: Option<u64>,
+}
+
+/// Bandwidth quota.
+///
+/// Quotas are checked before a transfer is started, and usage is recorded
+/// every [`RECORD_INTERVAL`] while it is in progress, so concurrent transfers
+/// count towards the quota, too. `upload-pack` transfers are aborted once a
+/// quota is exhausted, while replications on behalf of a request-pull are
+/// allowed to complete, and may thus overshoot a limit. Subsequent transfers
+/// are refused until the respective window has elapsed.
+#[derive(Clone, Debug)]
+pub struct Quota {
+    /// Limit across all peers and URNs.
+    ///
+    /// Default: unlimited
+    pub global: Limit,
+    /// Limit per remote peer.
+    ///
+    /// Default: unlimited
+    pub per_peer: Limit,
+    /// Limit per URN.
+    ///
+    /// Default: unlimited
+    pub per_urn: Limit,
+    /// The window [`Limit::burst`] applies to.
+    ///
+    /// Default: 1min
+    pub burst_window: Duration,
+}
+
+impl Default for Quota {
+    fn default() -> Self {
+        Self {
+            global: Limit::default(),
+            per_peer: Limit::default(),
+            per_urn: Limit::default(),
+            burst_window: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Period {
+    Daily,
+    Burst,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => f.write_str("daily"),
+            Self::Burst => f.write_str("burst"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Exceeded {
+    #[error("global {0} bandwidth quota exceeded")]
+    Global(Period),
+
+    #[error("{1} bandwidth quota exceeded for peer {0}")]
+    Peer(PeerId, Period),
+
+    #[error("{1} bandwidth quota exceeded for {0}")]
+    Urn(Urn, Period),
+}
+
+/// Bytes transferred, as of the time the [`Stats`] were taken.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Usage {
+    /// Since the accounting was started.
+    pub total: u64,
+    /// In the current daily window.
+    pub daily: u64,
+    /// In the current burst window.
+    pub burst: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub global: Usage,
+    pub peers: HashMap<PeerId, Usage>,
+    pub urns: HashMap<Urn, Usage>,
+}
+
+/// Bandwidth accounting.
+///
+/// Cloning yields a handle to the same accounting state.
+#[derive(Clone)]
+pub struct Bandwidth {
+    quota: Arc<Quota>,
+    global: Arc<Mutex<Counter>>,
+    peers: Arc<DashMap<PeerId, Counter>>,
+    urns: Arc<DashMap<Urn, Counter>>,
+}
+
+impl Bandwidth {
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota: Arc::new(quota),
+            global: Arc::new(Mutex::new(Counter::new(Instant::now()))),
+            peers: Default::default(),
+            urns: Default::default(),
+        }
+    }
+
+    pub fn quota(&self) -> &Quota {
+        &self.quota
+    }
+
+    /// Check that neither the global quota, nor the quota for `peer`, nor the
+    /// quota for `urn` (if given) is exhausted.
+    pub fn check(&self, peer: &PeerId, urn: Option<&Urn>) -> Result<(), Exceeded> {
+        self.check_at(Instant::now(), peer, urn)
+    }
+
+    /// Like [`Bandwidth::check`], but as of the point in time `now`.
+    pub fn check_at(&self, now: Instant, peer: &PeerId, urn: Option<&Urn>) -> Result<(), Exceeded> {
+        let window = self.quota.burst_window;
+
+        self.global
+            .lock()
+            .check(now, window, &self.quota.global)
+            .map_err(Exceeded::Global)?;
+        if let Some(counter) = self.peers.get(peer) {
+            counter
+                .check(now, window, &self.quota.per_peer)
+                .map_err(|p| Exceeded::Peer(*peer, p))?;
+        }
+        if let Some(urn) = urn {
+            if let Some(counter) = self.urns.get(urn) {
+                counter
+                    .check(now, window, &self.quota.per_urn)
+                    .map_err(|p| Exceeded::Urn(urn.clone(), p))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start accounting the bytes counted by `meter` as a [`Transfer`] to or
+    /// from `peer`.
+    pub fn transfer(&self, peer: PeerId, meter: quic::Meter) -> Transfer {
+        Transfer {
+            bandwidth: self.clone(),
+            meter,
+            peer,
+            urn: None,
+            recorded: 0,
+        }
+    }
+
+    /// Account `bytes` transferred to or from `peer`, on behalf of `urn` (if
+    /// known).
+    pub fn record(&self, peer: PeerId, urn: Option<&Urn>, bytes: u64) {
+        self.record_at(Instant::now(), peer, urn, bytes)
+    }
+
+    /// Like [`Bandwidth::record`], but as of the point in time `now`.
+    pub fn record_at(&self, now: Instant, peer: PeerId, urn: Option<&Urn>, bytes: u64) {
+        let window = self.quota.burst_window;
+
+        let new_day = self.global.lock().record(now, window, bytes);
+        // Entries which have been idle for a whole day don't affect any quota,
+        // so we can forget about them. Doing this once a day is enough to keep
+        // the maps from growing without bounds.
+        if new_day {
+            self.peers.retain(|_, c| !c.is_idle(now));
+            self.urns.retain(|_, c| !c.is_idle(now));
+        }
+
+        self.peers
+            .entry(peer)
+            .or_insert_with(|| Counter::new(now))
+            .record(now, window, bytes);
+        if let Some(urn) = urn {
+            self.urns
+                .entry(urn.clone())
+                .or_insert_with(|| Counter::new(now))
+                .record(now, window, bytes);
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats_at(Instant::now())
+    }
+
+    /// Like [`Bandwidth::stats`], but as of the point in time `now`.
+    pub fn stats_at(&self, now: Instant) -> Stats {
+        let window = self.quota.burst_window;
+        Stats {
+            global: self.global.lock().usage(now, window),
+            peers: self
+                .peers
+                .iter()
+                .map(|x| (*x.key(), x.value().usage(now, window)))
+                .collect(),
+            urns: self
+                .urns
+                .iter()
+                .map(|x| (x.key().clone(), x.value().usage(now, window)))
+                .collect(),
+        }
+    }
+}
+
+/// Accounting of a single, possibly long-running, transfer.
+///
+/// The bytes counted by the [`quic::Meter`] are recorded incrementally while
+/// the transfer is in progress. Whatever was not recorded yet is recorded when
+/// the [`Transfer`] is dropped.
+pub struct Transfer {
+    bandwidth: Bandwidth,
+    meter: quic::Meter,
+    peer: PeerId,
+    urn: Option<Urn>,
+    recorded: u64,
+}
+
+impl Transfer {
+    /// Attribute the bytes transferred from now on to `urn`.
+    pub fn set_urn(&mut self, urn: Urn) {
+        self.urn = Some(urn)
+    }
+
+    /// Record the bytes counted since the last time.
+    pub fn record(&mut self) {
+        let total = self.meter.total();
+        let bytes = total.saturating_sub(self.recorded);
+        if bytes > 0 {
+            self.bandwidth.record(self.peer, self.urn.as_ref(), bytes);
+            self.recorded = total;
+        }
+    }
+
+    /// Record the bytes counted since the last time, and check the quotas
+    /// applying to this transfer.
+    pub fn check(&mut self) -> Result<(), Exceeded> {
+        self.record();
+        self.bandwidth.check(&self.peer, self.urn.as_ref())
+    }
+
+    /// Drive `fut` to completion, recording the usage every
+    /// [`RECORD_INTERVAL`].
+    pub async fn drive<F>(&mut self, fut: F) -> F::Output
+    where
+        F: Future,
+    {
+        futures::pin_mut!(fut);
+        let mut ticks = link_async::interval(RECORD_INTERVAL, Duration::ZERO);
+        loop {
+            match future::select(fut.as_mut(), ticks.next()).await {
+                Either::Left((out, _)) => {
+                    self.record();
+                    return out;
+                },
+                Either::Right(_) => self.record(),
+            }
+        }
+    }
+
+    /// Like [`Transfer::drive`], but abandon `fut` as soon as a quota is found
+    /// to be exhausted.
+    pub async fn enforce<F>(&mut self, fut: F) -> Result<F::Output, Exceeded>
+    where
+        F: Future,
+    {
+        futures::pin_mut!(fut);
+        let mut ticks = link_async::interval(RECORD_INTERVAL, Duration::ZERO);
+        loop {
+            match future::select(fut.as_mut(), ticks.next()).await {
+                Either::Left((out, _)) => {
+                    self.record();
+                    return Ok(out);
+                },
+                Either::Right(_) => self.check()?,
+            }
+        }
+    }
+}
+
+impl Drop for Transfer {
+    fn drop(&mut self) {
+        self.record()
+    }
+}
+
+struct Counter {
+    total: u64,
+    daily: Window,
+    burst: Window,
+}
+
+impl Counter {
+    fn new(now: Instant) -> Self {
+        Self {
+            total: 0,
+            daily: Window::new(now),
+            burst: Window::new(now),
+        }
+    }
+
+    /// Returns `true` if a new daily window was started.
+    fn record(&mut self, now: Instant, burst_window: Duration, bytes: u64) -> bool {
+        self.total = self.total.saturating_add(bytes);
+        self.burst.add(now, burst_window, bytes);
+        self.daily.add(now, DAY, bytes)
+    }
+
+    fn check(&self, now: Instant, burst_window: Duration, limit: &Limit) -> Result<(), Period> {
+        fn exhausted(used: u64, limit: Option<u64>) -> bool {
+            limit.map(|limit| used >= limit).unwrap_or(false)
+        }
+
+        if exhausted(self.daily.get(now, DAY), limit.daily) {
+            return Err(Period::Daily);
+        }
+        if exhausted(self.burst.get(now, burst_window), limit.burst) {
+            return Err(Period::Burst);
+        }
+
+        Ok(())
+    }
+
+    fn is_idle(&self, now: Instant) -> bool {
+        self.daily.get(now, DAY) == 0
+    }
+
+    fn usage(&self, now: Instant, burst_window: Duration) -> Usage {
+        Usage {
+            total: self.total,
+            daily: self.daily.get(now, DAY),
+            burst: self.burst.get(now, burst_window),
+        }
+    }
+}
+
+/// Fixed time window.
+struct Window {
+    start: Instant,
+    bytes: u64,
+}
+
+impl Window {
+    fn new(now: Instant) -> Self {
+        Self {
+            start: now,
+            bytes: 0,
+        }
+    }
+
+    fn is_expired(&self, now: Instant, len: Duration) -> bool {
+        now.saturating_duration_since(self.start) >= len
+    }
+
+    fn get(&self, now: Instant, len: Duration) -> u64 {
+        if self.is_expired(now, len) {
+            0
+        } else {
+            self.bytes
+        }
+    }
+
+    /// Returns `true` if a new window was started.
+    fn add(&mut self, now: Instant, len: Duration, bytes: u64) -> bool {
+        let expired = self.is_expired(now, len);
+        if expired {
+            self.start = now;
+            self.bytes = 0;
+        }
+        self.bytes = self.bytes.saturating_add(bytes);
+
+        expired
+    }
+}
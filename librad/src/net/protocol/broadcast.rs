@@ -19,7 +19,7 @@ use super::{event::upstream as event, tick, PeerInfo};
 use crate::{PeerId, Signature};
 
 mod metrics;
-pub use metrics::Metrics;
+pub use metrics::{Metrics, Stats, StatsView};
 
 mod storage;
 pub use storage::{LocalStorage, PutResult};
@@ -3,6 +3,40 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StatsView {
+    /// Total number of messages received.
+    pub messages: usize,
+    /// Total number of messages received which had been seen before.
+    pub seen: usize,
+    /// Total number of messages received from old clients, which don't
+    /// report a hop count.
+    pub legacy: usize,
+    /// Sum of the hop counts of all messages received.
+    ///
+    /// Together with `messages` and `legacy`, this allows to compute the
+    /// average hop count.
+    pub hops: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct Stats {
+    inner: Arc<Counters>,
+}
+
+#[derive(Default)]
+struct Counters {
+    messages: AtomicUsize,
+    seen: AtomicUsize,
+    legacy: AtomicUsize,
+    hops: AtomicUsize,
+}
+
 pub trait Metrics {
     type Snapshot;
 
@@ -26,3 +60,28 @@ impl Metrics for () {
 
     fn snapshot(&self) -> Self::Snapshot {}
 }
+
+impl Metrics for Stats {
+    type Snapshot = StatsView;
+
+    fn record_message(&self, hop_count: Option<usize>) {
+        self.inner.messages.fetch_add(1, Ordering::Relaxed);
+        match hop_count {
+            Some(hops) => self.inner.hops.fetch_add(hops, Ordering::Relaxed),
+            None => self.inner.legacy.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    fn record_seen(&self) {
+        self.inner.seen.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        StatsView {
+            messages: self.inner.messages.load(Ordering::Relaxed),
+            seen: self.inner.seen.load(Ordering::Relaxed),
+            legacy: self.inner.legacy.load(Ordering::Relaxed),
+            hops: self.inner.hops.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -14,6 +14,7 @@ use super::{
     gossip,
     interrogation,
     io,
+    membership,
     request_pull,
     tick,
     PeerInfo,
@@ -61,6 +62,7 @@ pub(super) fn info<S, G>(state: &State<S, G>, evt: event::downstream::Info)
 where
     S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + 'static,
 {
+    use broadcast::Metrics as _;
     use event::downstream::{CacheStats, Info, MembershipInfo, Stats};
 
     match evt {
@@ -94,6 +96,8 @@ where
                     caches: CacheStats {
                         urns: state.caches.urns.stats(),
                     },
+                    bandwidth: state.bandwidth.stats(),
+                    broadcast: state.gossip.snapshot(),
                 })
                 .ok();
             }
@@ -158,3 +162,23 @@ pub(super) async fn connect<S, G>(
         tx.send(conn).ok();
     }
 }
+
+/// Send a membership `Disconnect` to all peers in the active view.
+pub(super) async fn leave<S, G>(state: &State<S, G>, reply: event::downstream::Reply<usize>)
+where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + 'static,
+    G: RequestPullGuard,
+{
+    let chan = reply.lock().take();
+    let active = state.membership.active();
+    let n = active.len();
+    stream::iter(active.into_iter().map(|to| tick::Tock::SendConnected {
+        to,
+        message: membership::Message::Disconnect.into(),
+    }))
+    .for_each(|tock| tick::tock(state.clone(), tock))
+    .await;
+    if let Some(tx) = chan {
+        tx.send(n).ok();
+    }
+}
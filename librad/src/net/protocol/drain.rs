@@ -0,0 +1,107 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+/// Book-keeping of in-flight work, used to shut down a protocol instance
+/// gracefully.
+///
+/// Units of work (serving a stream, running a replication) register
+/// themselves via [`Drain::enter`], and deregister when the returned
+/// [`InFlight`] guard is dropped. Once draining has started, no new units of
+/// work are admitted, and [`Drain::wait`] resolves when all outstanding ones
+/// have completed.
+#[derive(Clone, Default)]
+pub struct Drain {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    draining: AtomicBool,
+    inflight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Guard representing a unit of in-flight work.
+///
+/// Dropping it marks the work as completed.
+pub struct InFlight {
+    inner: Arc<Inner>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.inner.inflight.fetch_sub(1, SeqCst) == 1 {
+            self.inner.idle.notify_one()
+        }
+    }
+}
+
+impl Drain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` if [`Drain::start`] has been called.
+    pub fn is_draining(&self) -> bool {
+        self.inner.draining.load(SeqCst)
+    }
+
+    /// Number of units of work currently in flight.
+    pub fn inflight(&self) -> usize {
+        self.inner.inflight.load(SeqCst)
+    }
+
+    /// Register a unit of work.
+    ///
+    /// Returns `None` if draining has already started, in which case the work
+    /// should not be started.
+    pub fn enter(&self) -> Option<InFlight> {
+        if self.is_draining() {
+            return None;
+        }
+        self.inner.inflight.fetch_add(1, SeqCst);
+        // Re-check: `start` may have raced with the increment above, in which
+        // case we back off.
+        if self.is_draining() {
+            drop(InFlight {
+                inner: Arc::clone(&self.inner),
+            });
+            return None;
+        }
+
+        Some(InFlight {
+            inner: Arc::clone(&self.inner),
+        })
+    }
+
+    /// Stop admitting new units of work.
+    pub fn start(&self) {
+        self.inner.draining.store(true, SeqCst)
+    }
+
+    /// Wait for all in-flight units of work to complete, or until `timeout`
+    /// elapses.
+    ///
+    /// Note that this does not imply [`Drain::start`].
+    pub async fn wait(&self, timeout: Duration) -> Result<(), link_async::Elapsed> {
+        link_async::timeout(timeout, async {
+            loop {
+                if self.inflight() == 0 {
+                    break;
+                }
+                self.inner.idle.notified().await
+            }
+        })
+        .await
+    }
+}
@@ -5,7 +5,17 @@
 
 use std::{collections::HashMap, net::SocketAddr};
 
-use super::{broadcast, cache, error, gossip, interrogation, membership, quic, request_pull};
+use super::{
+    bandwidth,
+    broadcast,
+    cache,
+    error,
+    gossip,
+    interrogation,
+    membership,
+    quic,
+    request_pull,
+};
 use crate::PeerId;
 
 #[derive(Clone)]
@@ -15,6 +25,9 @@ pub enum Downstream {
     Interrogation(downstream::Interrogation),
     RequestPull(downstream::RequestPull),
     Connect(downstream::Connect),
+    /// Gracefully leave the membership overlay, replying with the number of
+    /// peers notified.
+    Leave(downstream::Reply<usize>),
 }
 
 pub mod downstream {
@@ -63,6 +76,8 @@ pub mod downstream {
         pub membership_active: usize,
         pub membership_passive: usize,
         pub caches: CacheStats,
+        pub bandwidth: bandwidth::Stats,
+        pub broadcast: broadcast::StatsView,
     }
 
     #[derive(Clone, Copy, Debug, Default)]
@@ -17,6 +17,7 @@ pub use super::error;
 use super::streams;
 use crate::{
     net::{
+        connection::CloseReason,
         protocol::{
             event::upstream as event,
             gossip,
@@ -56,6 +57,10 @@ where
     futures::pin_mut!(ingress);
     while let Some(conn) = ingress.next().await {
         match conn {
+            Ok((conn, _)) if state.drain.is_draining() => {
+                tracing::debug!("draining, refusing ingress connection");
+                conn.close(CloseReason::ServerShutdown)
+            },
             Ok((_, streams)) => {
                 state
                     .spawner
@@ -6,14 +6,16 @@
 
 use std::{io, process::ExitStatus};
 
-use futures::io::{AsyncRead, AsyncWrite};
 use link_git::protocol::upload_pack::{upload_pack, Header};
 use thiserror::Error;
 use tracing::{error, info};
 
 use crate::{
+    git::Urn,
     net::{
-        connection::Duplex,
+        connection::{CloseReason, Duplex, RemotePeer as _},
+        protocol::bandwidth::{Bandwidth, Transfer},
+        quic,
         upgrade::{self, Upgraded},
     },
     paths::Paths,
@@ -28,30 +30,70 @@ enum Error {
     Io(#[from] io::Error),
 }
 
-pub(in crate::net::protocol) async fn git<T>(paths: &Paths, stream: Upgraded<upgrade::Git, T>)
-where
-    T: Duplex,
-    T::Read: AsyncRead + Unpin,
-    T::Write: AsyncWrite + Unpin,
-{
-    if let Err(e) = serve(paths, stream).await {
+/// Serve `upload-pack` over `stream`.
+///
+/// If `bandwidth` is given, the transfer is subject to its quotas and
+/// accounted for while in progress.
+pub(in crate::net::protocol) async fn git(
+    paths: &Paths,
+    bandwidth: Option<&Bandwidth>,
+    stream: Upgraded<upgrade::Git, quic::BidiStream>,
+) {
+    let remote_peer = stream.remote_peer_id();
+    if let Some(Err(e)) = bandwidth.map(|bw| bw.check(&remote_peer, None)) {
+        info!(err = %e, "refusing upload-pack");
+        stream.into_stream().close(CloseReason::QuotaExceeded);
+        return;
+    }
+
+    let meter = quic::Meter::new();
+    let mut transfer = bandwidth.map(|bw| bw.transfer(remote_peer, meter.clone()));
+    if let Err(e) = serve(paths, transfer.as_mut(), stream.map(|s| s.metered(meter))).await {
         error!(err = ?e, "upload-pack error");
     }
 }
 
-async fn serve<T>(paths: &Paths, stream: Upgraded<upgrade::Git, T>) -> Result<(), Error>
-where
-    T: Duplex,
-    T::Read: AsyncRead + Unpin,
-    T::Write: AsyncWrite + Unpin,
-{
-    let (recv, send) = stream.into_stream().split();
+/// If the request path denotes a [`Urn`], the `transfer` is attributed to it.
+///
+/// If a quota is exhausted, the stream is closed with
+/// [`CloseReason::QuotaExceeded`], either without serving the request, or
+/// as soon as it is noticed during the transfer.
+async fn serve(
+    paths: &Paths,
+    transfer: Option<&mut Transfer>,
+    stream: Upgraded<upgrade::Git, quic::BidiStream>,
+) -> Result<(), Error> {
+    let (mut recv, mut send) = stream.into_stream().split();
     let git_dir = paths.git_dir();
 
-    let (Header { path, host, extra }, run) = upload_pack(git_dir, recv, send).await?;
+    let (Header { path, host, extra }, run) = upload_pack(git_dir, &mut recv, &mut send).await?;
     info!(%path, ?host, ?extra, "upload-pack");
 
-    let status = run.await?;
+    let urn = Urn::try_from_id(path.strip_prefix("rad:git:").unwrap_or(&path)).ok();
+    let status = match transfer {
+        None => run.await,
+        Some(transfer) => {
+            if let Some(urn) = urn {
+                transfer.set_urn(urn);
+            }
+            let res = match transfer.check() {
+                Ok(()) => transfer.enforce(run).await,
+                Err(e) => {
+                    drop(run);
+                    Err(e)
+                },
+            };
+            match res {
+                Ok(status) => status,
+                Err(e) => {
+                    info!(err = %e, "refusing upload-pack");
+                    recv.close(CloseReason::QuotaExceeded);
+                    send.close(CloseReason::QuotaExceeded);
+                    return Ok(());
+                },
+            }
+        },
+    }?;
     // XXX: #![feature(exit_status_error)] ?
     // https://github.com/rust-lang/rust/issues/84908
     if !status.success() {
@@ -49,8 +49,12 @@ pub(in crate::net::protocol) async fn request_pull<S, G>(
     G: protocol::RequestPullGuard,
 {
     let remote_peer = stream.remote_peer_id();
-    let conn = stream.connection().clone();
-    let (recv, send) = stream.into_stream().split();
+    // Account both the request-pull stream itself and the streams opened for
+    // replication.
+    let meter = quic::Meter::new();
+    let mut transfer = state.bandwidth.transfer(remote_peer, meter.clone());
+    let conn = stream.connection().metered(meter.clone());
+    let (recv, send) = stream.into_stream().metered(meter).split();
     let recv = BufReader::with_capacity(request_pull::FRAMED_BUFSIZ, recv);
     let send = BufWriter::with_capacity(request_pull::FRAMED_BUFSIZ, send);
     let mut sink = send.into_sink();
@@ -65,15 +69,17 @@ pub(in crate::net::protocol) async fn request_pull<S, G>(
                 }
             },
             Ok(req) => {
+                transfer.set_urn(req.urn.clone());
                 let resp = encode(
-                    &handle_request(
-                        state,
-                        remote_peer,
-                        req,
-                        conn,
-                        &mut Reporter { sink: &mut sink },
-                    )
-                    .await,
+                    &transfer
+                        .drive(handle_request(
+                            &state,
+                            remote_peer,
+                            req,
+                            conn,
+                            &mut Reporter { sink: &mut sink },
+                        ))
+                        .await,
                 )
                 .unwrap_or_else(|e| {
                     tracing::error!(err = ?e, "error handling request");
@@ -113,7 +119,7 @@ where
 }
 
 async fn handle_request<'a, S, G, W>(
-    state: State<S, G>,
+    state: &State<S, G>,
     peer: PeerId,
     Request { urn }: Request,
     conn: quic::Connection,
@@ -124,6 +130,10 @@ where
     G: protocol::RequestPullGuard,
     W: AsyncWrite + Unpin,
 {
+    if let Err(err) = state.bandwidth.check(&peer, Some(&urn)) {
+        return error::quota(err).into();
+    }
+
     report.progress(progress::authorizing(&urn)).await;
     match state.request_pull.guard(&peer, &urn) {
         Ok(guard) => report.progress(progress::guard(guard)).await,
@@ -138,7 +148,7 @@ where
     {
         Ok(success) => {
             let tips = success.refs.iter().map(|Ref { oid, .. }| oid).copied();
-            gossip(&state, peer, &urn, tips).await;
+            gossip(state, peer, &urn, tips).await;
             success.into()
         },
         Err(err) => error::replication_error(err).into(),
@@ -51,6 +51,13 @@ pub(in crate::net::protocol) async fn incoming<S, G, I>(
             Some(stream) => {
                 tracing::info!("new ingress stream");
                 match stream {
+                    Ok(s) if state.drain.is_draining() => {
+                        tracing::debug!("draining, refusing ingress stream");
+                        match s {
+                            Left(bidi) => bidi.close(CloseReason::ServerShutdown),
+                            Right(uni) => uni.close(CloseReason::ServerShutdown),
+                        }
+                    },
                     Ok(s) => match s {
                         Left(bidi) => state
                             .spawner
@@ -84,13 +91,23 @@ mod incoming {
     {
         use upgrade::SomeUpgraded::*;
 
+        // Bidirectional streams may carry long-running work (serving git
+        // fetches, request-pull), which a graceful shutdown must wait for.
+        let _inflight = match state.drain.enter() {
+            Some(inflight) => inflight,
+            None => {
+                stream.close(CloseReason::ServerShutdown);
+                return;
+            },
+        };
+
         match upgrade::with_upgraded(stream).await {
             Err(upgrade::Error { stream, source }) => {
                 tracing::warn!(err = ?source, "invalid upgrade");
                 stream.close(CloseReason::InvalidUpgrade)
             },
 
-            Ok(Git(up)) => recv::git(&state.config.paths, up).await,
+            Ok(Git(up)) => recv::git(&state.config.paths, Some(&state.bandwidth), up).await,
             Ok(Gossip(up)) => recv::gossip(state, up).await,
             Ok(Membership(up)) => recv::membership(state, up).await,
             Ok(Interrogation(up)) => recv::interrogation(state, up).await,
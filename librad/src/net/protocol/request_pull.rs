@@ -6,8 +6,11 @@ use thiserror::Error;
 
 use crate::{
     git::{storage, storage::PoolError, Urn},
-    net::{quic, replication},
-    paths::Paths,
+    net::{
+        protocol::bandwidth,
+        quic,
+        replication::{self, Replication},
+    },
     PeerId,
 };
 
@@ -37,18 +40,21 @@ pub trait Guard {
 }
 
 /// State for serving request-pull calls.
+///
+/// Request-pulls are replicated using the given [`Replication`], and are thus
+/// subject to its limits and accounted for in its statistics.
 #[derive(Clone)]
 pub struct State<S, G> {
     storage: S,
-    paths: Paths,
+    repl: Replication,
     guard: G,
 }
 
 impl<S, G: Guard> State<S, G> {
-    pub fn new(storage: S, paths: Paths, guard: G) -> Self {
+    pub fn new(storage: S, repl: Replication, guard: G) -> Self {
         Self {
             storage,
-            paths,
+            repl,
             guard,
         }
     }
@@ -67,8 +73,6 @@ pub(in crate::net::protocol) mod error {
         Replication(#[from] replication::error::Replicate),
         #[error("internal error: could not get handle to storage")]
         Pool(#[from] PoolError),
-        #[error("internal error: could not intialise storage")]
-        Init(#[from] replication::error::Init),
         #[error("internal error: failed to look up symbolic-ref target")]
         Read(#[from] storage::read::Error),
     }
@@ -91,6 +95,12 @@ pub(in crate::net::protocol) mod error {
         }
     }
 
+    pub fn quota(e: bandwidth::Exceeded) -> Error {
+        Error {
+            message: e.to_string(),
+        }
+    }
+
     pub fn guard<E: std::error::Error>(e: E) -> Error {
         Error {
             message: e.to_string(),
@@ -112,9 +122,11 @@ where
         use crate::git::storage::ReadOnlyStorage as _;
         use link_replication::Updated;
 
-        let repl = replication::Replication::new(&self.paths, replication::Config::default())?;
         let storage = self.storage.get().await?;
-        let succ = repl.replicate(spawner, storage, conn, urn, None).await?;
+        let succ = self
+            .repl
+            .replicate(spawner, storage, conn, urn, None)
+            .await?;
 
         let storage = self.storage.get().await?;
         succ.updated_refs()
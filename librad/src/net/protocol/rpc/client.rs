@@ -46,12 +46,25 @@ where
     S: Signer + Clone,
 {
     pub fn new(config: Config<S>, spawner: Arc<Spawner>, endpoint: E) -> Result<Self, error::Init> {
+        let repl = Replication::new(&config.paths, config.replication)?;
+        Ok(Self::with_replication(config, spawner, endpoint, repl))
+    }
+
+    /// Create a client which runs replications using the given
+    /// [`Replication`], sharing its limits, [`Drain`] and statistics.
+    ///
+    /// [`Drain`]: crate::net::protocol::Drain
+    pub(crate) fn with_replication(
+        config: Config<S>,
+        spawner: Arc<Spawner>,
+        endpoint: E,
+        repl: Replication,
+    ) -> Self {
         let paths = config.paths.clone();
         let local_id = PeerId::from_signer(&config.signer);
         let user_store = config.storage();
-        let repl = Replication::new(&paths, config.replication)?;
 
-        Ok(Self {
+        Self {
             config,
             local_id,
             spawner,
@@ -59,7 +72,7 @@ where
             endpoint,
             repl,
             user_store,
-        })
+        }
     }
 }
 
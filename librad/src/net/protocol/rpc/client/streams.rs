@@ -63,7 +63,7 @@ mod incoming {
                 stream.close(CloseReason::InvalidUpgrade)
             },
 
-            Ok(Git(up)) => io::recv::git(&paths, up).await,
+            Ok(Git(up)) => io::recv::git(&paths, None, up).await,
             Ok(Gossip(up)) => deny_bidi(up.into_stream(), "gossip"),
             Ok(Membership(up)) => deny_bidi(up.into_stream(), "membership"),
             Ok(Interrogation(up)) => deny_bidi(up.into_stream(), "interrogation"),
@@ -11,6 +11,7 @@ use rand_pcg::Pcg64Mcg;
 use tracing::Instrument as _;
 
 use super::{
+    bandwidth::{self, Bandwidth},
     broadcast,
     cache,
     event,
@@ -18,6 +19,7 @@ use super::{
     membership,
     request_pull,
     tick,
+    Drain,
     Endpoint,
     ProtocolStorage,
     RequestPullGuard,
@@ -44,13 +46,15 @@ pub(super) struct State<S, G> {
     pub local_id: PeerId,
     pub endpoint: Endpoint,
     pub membership: membership::Hpv<Pcg64Mcg, SocketAddr>,
-    pub gossip: broadcast::State<Storage<S>, ()>,
+    pub gossip: broadcast::State<Storage<S>, broadcast::Stats>,
     pub request_pull: request_pull::State<Storage<S>, G>,
     pub phone: TinCans,
     pub config: StateConfig,
     pub caches: cache::Caches,
     pub spawner: Arc<Spawner>,
     pub limits: RateLimits,
+    pub drain: Drain,
+    pub bandwidth: Bandwidth,
 }
 
 impl<S, G> State<S, G> {
@@ -132,6 +136,10 @@ pub struct Quota {
     pub membership: rate_limit::Quota,
     /// See [`StorageQuota`].
     pub storage: StorageQuota,
+    /// Bytes transferred when serving git and request-pull streams.
+    ///
+    /// See [`bandwidth::Quota`].
+    pub bandwidth: bandwidth::Quota,
 }
 
 impl Default for Quota {
@@ -140,6 +148,7 @@ impl Default for Quota {
             gossip: GossipQuota::default(),
             membership: rate_limit::Quota::per_second(nonzero!(1u32)).allow_burst(nonzero!(10u32)),
             storage: StorageQuota::default(),
+            bandwidth: bandwidth::Quota::default(),
         }
     }
 }
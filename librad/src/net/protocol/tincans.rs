@@ -134,6 +134,26 @@ impl TinCans {
         rx.await.unwrap_or_default()
     }
 
+    pub async fn leave(&self) -> usize {
+        let (tx, rx) = replier();
+        if let Err(tincan::error::SendError(e)) = self.downstream.send(Downstream::Leave(tx)) {
+            match e {
+                Downstream::Leave(reply) => {
+                    reply
+                        .lock()
+                        .take()
+                        .expect("if chan send failed, there can't be another contender")
+                        .send(0)
+                        .ok();
+                },
+
+                _ => unreachable!(),
+            }
+        }
+
+        rx.await.unwrap_or_default()
+    }
+
     pub fn interrogate(&self, peer: PeerId, conn: quic::Connection) -> Interrogation {
         Interrogation {
             peer,
@@ -22,7 +22,7 @@ pub mod error;
 pub use error::{Error, Result};
 
 mod stream;
-pub use stream::{BidiStream, RecvStream, SendStream};
+pub use stream::{BidiStream, Meter, RecvStream, SendStream};
 
 const ALPN_PREFIX: &[u8] = b"rad";
 
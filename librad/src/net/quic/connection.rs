@@ -23,7 +23,7 @@ use futures::{
 use quinn::NewConnection;
 use thiserror::Error;
 
-use super::{BidiStream, Error, RecvStream, Result, SendStream};
+use super::{BidiStream, Error, Meter, RecvStream, Result, SendStream};
 use crate::{
     net::connection::{CloseReason, RemoteAddr, RemotePeer},
     PeerId,
@@ -71,10 +71,12 @@ fn incoming_streams(
                 send: SendStream {
                     conn: conn.clone(),
                     send,
+                    meter: None,
                 },
                 recv: RecvStream {
                     conn: conn.clone(),
                     recv,
+                    meter: None,
                 },
             })
         })
@@ -86,6 +88,7 @@ fn incoming_streams(
             Right(RecvStream {
                 conn: conn.clone(),
                 recv,
+                meter: None,
             })
         })
     };
@@ -144,6 +147,7 @@ pub struct Connection {
     conn: quinn::Connection,
     track: Option<Conntrack>,
     send_streams: Arc<Vec<Mutex<Option<SendStream>>>>,
+    meter: Option<Meter>,
 }
 
 impl Connection {
@@ -170,6 +174,7 @@ impl Connection {
                     .take(reserve_send_streams)
                     .collect(),
             ),
+            meter: None,
         };
         let incoming = incoming_streams(conn.clone(), bi_streams, uni_streams);
 
@@ -180,6 +185,25 @@ impl Connection {
         ConnectionId(self.conn.stable_id())
     }
 
+    /// A handle to the same connection, which accounts all bytes transferred
+    /// over streams subsequently opened through it to `meter`.
+    ///
+    /// Note that incoming streams are not metered. The streams handed out by
+    /// [`Connection::borrow_uni`] are shared between all handles to the
+    /// connection, and should thus not be used via a metered handle.
+    pub fn metered(&self, meter: Meter) -> Self {
+        Self {
+            meter: Some(meter),
+            ..self.clone()
+        }
+    }
+
+    /// The [`Meter`] of this handle, if it was obtained via
+    /// [`Connection::metered`].
+    pub fn meter(&self) -> Option<&Meter> {
+        self.meter.as_ref()
+    }
+
     pub async fn open_bidi(&self) -> Result<BidiStream> {
         let (send, recv) = self.conn.open_bi().await.map_err(|e| {
             self.close(CloseReason::ConnectionError);
@@ -192,10 +216,12 @@ impl Connection {
             recv: RecvStream {
                 conn: self.clone(),
                 recv,
+                meter: self.meter.clone(),
             },
             send: SendStream {
                 conn: self.clone(),
                 send,
+                meter: self.meter.clone(),
             },
         })
     }
@@ -210,6 +236,7 @@ impl Connection {
         Ok(SendStream {
             conn: self.clone(),
             send,
+            meter: self.meter.clone(),
         })
     }
 
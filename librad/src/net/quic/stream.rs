@@ -7,6 +7,10 @@ use std::{
     io,
     net::SocketAddr,
     pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
     task::{Context, Poll},
 };
 
@@ -19,6 +23,44 @@ use crate::{
     PeerId,
 };
 
+/// Counter of the bytes transferred over one or more streams.
+///
+/// Cloning a [`Meter`] yields a handle to the same counters.
+#[derive(Clone, Debug, Default)]
+pub struct Meter {
+    sent: Arc<AtomicU64>,
+    recvd: Arc<AtomicU64>,
+}
+
+impl Meter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bytes written to the metered streams.
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Relaxed)
+    }
+
+    /// Number of bytes read from the metered streams.
+    pub fn recvd(&self) -> u64 {
+        self.recvd.load(Relaxed)
+    }
+
+    /// Number of bytes transferred in either direction.
+    pub fn total(&self) -> u64 {
+        self.sent().saturating_add(self.recvd())
+    }
+
+    fn add_sent(&self, n: usize) {
+        self.sent.fetch_add(n as u64, Relaxed);
+    }
+
+    fn add_recvd(&self, n: usize) {
+        self.recvd.fetch_add(n as u64, Relaxed);
+    }
+}
+
 pub struct BidiStream {
     pub(super) conn: Connection,
     pub(super) recv: RecvStream,
@@ -40,6 +82,13 @@ impl BidiStream {
     pub fn connection(&self) -> &Connection {
         &self.conn
     }
+
+    /// Account all bytes transferred over this stream to `meter`.
+    pub fn metered(mut self, meter: Meter) -> Self {
+        self.recv.meter = Some(meter.clone());
+        self.send.meter = Some(meter);
+        self
+    }
 }
 
 impl RemotePeer for BidiStream {
@@ -92,6 +141,7 @@ impl AsyncWrite for BidiStream {
 pub struct RecvStream {
     pub(super) conn: Connection,
     pub(super) recv: quinn::RecvStream,
+    pub(super) meter: Option<Meter>,
 }
 
 impl RecvStream {
@@ -146,7 +196,12 @@ impl AsyncRead for RecvStream {
         if let Poll::Ready(ready) = &res {
             match ready {
                 Err(e) => this.on_stream_error(e),
-                Ok(_) => this.tickle(),
+                Ok(n) => {
+                    this.tickle();
+                    if let Some(meter) = this.meter.as_ref() {
+                        meter.add_recvd(*n)
+                    }
+                },
             }
         }
 
@@ -157,6 +212,7 @@ impl AsyncRead for RecvStream {
 pub struct SendStream {
     pub(super) conn: Connection,
     pub(super) send: quinn::SendStream,
+    pub(super) meter: Option<Meter>,
 }
 
 impl SendStream {
@@ -207,7 +263,12 @@ impl AsyncWrite for SendStream {
         if let Poll::Ready(ready) = &res {
             match ready {
                 Err(e) => this.on_stream_error(e),
-                Ok(_) => this.tickle(),
+                Ok(n) => {
+                    this.tickle();
+                    if let Some(meter) = this.meter.as_ref() {
+                        meter.add_sent(*n)
+                    }
+                },
             }
         }
 
@@ -3,29 +3,36 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use async_lock::Semaphore;
 use link_async::{timeout, Spawner};
 use link_replication::io::UserInfo;
-use tracing::debug;
+use tracing::{debug, info, warn};
 
 use crate::{
     git::{
-        identities::local::LocalIdentity,
-        storage::{read::ReadOnlyStorage as _, Storage},
+        identities::{self, local::LocalIdentity, SomeIdentity},
+        storage::{read::ReadOnlyStorage as _, stats::SizeCache, Storage},
+        tracking::{self, policy, UntrackAllArgs},
     },
-    identities::git::Urn,
-    net::{connection::RemotePeer as _, quic},
+    identities::{git::Urn, payload::ProjectStatus},
+    net::{connection::RemotePeer as _, protocol::Drain, quic},
     paths::Paths,
     PeerId,
 };
 
-pub use link_replication::FetchLimit;
+pub use link_replication::{FetchLimit, Quota};
 
 mod context;
 use context::Context;
 
+mod stats;
+pub use stats::{PhaseStats, Stats, DURATION_BUCKETS};
+
 pub mod error {
     use thiserror::Error;
 
@@ -43,6 +50,9 @@ pub mod error {
         #[error("timeout waiting for replication slot")]
         Timeout(#[from] link_async::Elapsed),
 
+        #[error("replication refused: shutting down")]
+        Draining,
+
         #[error(transparent)]
         Replicate(#[from] link_replication::Error),
     }
@@ -55,6 +65,11 @@ pub struct Config {
     pub limit: FetchLimit,
     pub slots: usize,
     pub wait_slot: Duration,
+    /// Stop tracking projects which were archived (see [`ProjectStatus`]) for
+    /// longer than this period, after replicating them.
+    ///
+    /// If `None`, archived projects remain tracked.
+    pub archived_grace_period: Option<Duration>,
 }
 
 impl Default for Config {
@@ -63,6 +78,7 @@ impl Default for Config {
             limit: FetchLimit::default(),
             slots: 4,
             wait_slot: Duration::from_secs(20),
+            archived_grace_period: None,
         }
     }
 }
@@ -73,6 +89,9 @@ pub struct Replication {
     slots: Arc<Semaphore>,
     odb: link_replication::io::Odb,
     rdb: link_git::refs::db::Refdb,
+    drain: Drain,
+    stats: Arc<stats::Recorder>,
+    sizes: SizeCache,
 }
 
 impl Replication {
@@ -86,9 +105,22 @@ impl Replication {
             slots,
             odb,
             rdb,
+            drain: Drain::default(),
+            stats: Default::default(),
+            sizes: Default::default(),
         })
     }
 
+    /// Register replications with the given [`Drain`], so a graceful
+    /// shutdown waits for them to complete.
+    pub(crate) fn with_drain(self, drain: Drain) -> Self {
+        Self { drain, ..self }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats.snapshot(self.odb.stats())
+    }
+
     pub async fn replicate<S>(
         &self,
         spawner: &Spawner,
@@ -100,15 +132,32 @@ impl Replication {
     where
         S: AsRef<Storage> + Send + 'static,
     {
-        let slot = timeout(self.config.wait_slot, self.slots.acquire_arc()).await?;
+        let inflight = self.drain.enter().ok_or(error::Replicate::Draining)?;
+        let slot = timeout(self.config.wait_slot, self.slots.acquire_arc())
+            .await
+            .map_err(|e| {
+                self.stats.record_slot_timeout();
+                e
+            })?;
+        let started = Instant::now();
         let limit = self.config.limit;
+        let archived_grace_period = self.config.archived_grace_period;
         let odb = self.odb.clone();
         let rdb = self.rdb.clone();
+        let stats = self.stats.clone();
+        let sizes = self.sizes.clone();
+        // Meter the connection, so the phases of the replication can report
+        // the bytes received
+        let conn = match conn.meter() {
+            Some(_) => conn,
+            None => conn.metered(quic::Meter::new()),
+        };
         let res = spawner
             .blocking(move || {
                 let store = store.as_ref();
                 let have_urn = store.has_urn(&urn)?;
                 let remote_id = conn.remote_peer_id();
+                let identity = urn.clone();
                 let info = UserInfo {
                     name: store.config()?.user_name()?,
                     peer_id: *store.peer_id(),
@@ -126,6 +175,8 @@ impl Replication {
                     store,
                     refdb,
                     net,
+                    metrics: &stats,
+                    sizes: &sizes,
                 };
                 let whoami = whoami.map(|id| link_replication::LocalIdentity {
                     tip: id.content_id.into(),
@@ -137,17 +188,65 @@ impl Replication {
                         .collect(),
                 });
 
-                if have_urn {
+                let res = if have_urn {
                     debug!("pull");
                     link_replication::pull(&mut cx, limit, remote_id, whoami)
                 } else {
                     debug!("clone");
                     link_replication::clone(&mut cx, limit, remote_id, whoami)
+                };
+                if let (Ok(_), Some(grace)) = (&res, archived_grace_period) {
+                    untrack_archived(store, &identity, grace);
                 }
+                res
             })
             .await
             .map_err(error::Replicate::Replicate);
+        self.stats.record(started.elapsed(), res.is_ok());
         drop(slot);
+        drop(inflight);
         res
     }
 }
+
+/// Untrack all peers of `urn` if it is a project which was archived more than
+/// `grace` ago.
+///
+/// Errors are logged, as they should not fail the replication.
+fn untrack_archived(store: &Storage, urn: &Urn, grace: Duration) {
+    let since = match identities::any::get(store, urn) {
+        Ok(Some(SomeIdentity::Project(_))) => match identities::project::verify(store, urn) {
+            Ok(Some(project)) => match project.status() {
+                ProjectStatus::Archived { since } => since,
+                _ => return,
+            },
+            Ok(None) => return,
+            Err(e) => {
+                warn!(urn = %urn, err = %e, "failed to verify project status");
+                return;
+            },
+        },
+        Ok(_) => return,
+        Err(e) => {
+            warn!(urn = %urn, err = %e, "failed to load identity");
+            return;
+        },
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    if now.saturating_sub(since) < grace.as_secs() as i64 {
+        return;
+    }
+    match tracking::untrack_all(store, urn, UntrackAllArgs::new(policy::UntrackAll::Any)) {
+        Ok(untracked) => info!(
+            urn = %urn,
+            since,
+            untracked = untracked.untracked.filter(Result::is_ok).count(),
+            "untracked archived project"
+        ),
+        Err(e) => warn!(urn = %urn, err = %e, "failed to untrack archived project"),
+    }
+}
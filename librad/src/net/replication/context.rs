@@ -5,7 +5,7 @@
 
 use std::{
     borrow::Cow,
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryFrom,
     ops::Deref,
     path::Path,
@@ -18,6 +18,7 @@ use git_ref_format::RefString;
 use link_git::protocol::Ref;
 use link_replication::{
     io,
+    metrics::{Measurement, Rejection},
     namespace,
     odb::Object,
     oid,
@@ -27,6 +28,7 @@ use link_replication::{
     Identities,
     LocalPeer,
     LsRefs,
+    Metrics,
     Namespace,
     Net,
     ObjectId,
@@ -37,6 +39,7 @@ use link_replication::{
     Sigrefs,
     Tracking,
     Update,
+    Usage,
     VerifiedIdentity,
 };
 use multihash::Multihash;
@@ -61,6 +64,8 @@ use crate::{
     PeerId,
 };
 
+use super::stats;
+
 pub mod error {
     use super::*;
     use thiserror::Error;
@@ -94,6 +99,9 @@ pub mod error {
 
         #[error(transparent)]
         Refs(#[from] git::refs::stored::Error),
+
+        #[error(transparent)]
+        Git(#[from] git2::Error),
     }
 
     #[derive(Debug, Error)]
@@ -125,6 +133,8 @@ pub struct Context<'a> {
     pub(super) store: &'a Storage,
     pub(super) refdb: io::Refdb<io::Odb>,
     pub(super) net: Network,
+    pub(super) metrics: &'a stats::Recorder,
+    pub(super) sizes: &'a git::storage::stats::SizeCache,
 }
 
 impl<'a> Context<'a> {
@@ -246,6 +256,19 @@ impl VerifiedIdentity for SomeVerifiedIdentity {
             BTreeSet::new()
         }
     }
+
+    fn revoked(&self) -> BTreeMap<PeerId, BTreeSet<Self::Oid>> {
+        let revocations = match self {
+            Self::Person(p) => p.revocations(),
+            Self::Project(p) => p.revocations(),
+        };
+
+        revocations
+            .revoked
+            .into_iter()
+            .map(|(key, valid)| (PeerId::from(key), valid))
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -392,7 +415,12 @@ impl SignedRefs for Context<'_> {
         signed_by: &PeerId,
         cutoff: usize,
     ) -> Result<Option<Sigrefs<Self::Oid>>, Self::Error> {
-        match git::refs::load_at(&self.store, treeish.into().into(), Some(signed_by))? {
+        match git::refs::load_at(
+            &self.store,
+            &self.urn,
+            treeish.into().into(),
+            Some(signed_by),
+        )? {
             None => Ok(None),
             Some(git::refs::Loaded { at, refs: signed }) => {
                 let refs = signed
@@ -413,6 +441,20 @@ impl SignedRefs for Context<'_> {
         }
     }
 
+    fn is_ancestor(
+        &self,
+        ancestor: impl Into<ObjectId>,
+        descendant: impl Into<ObjectId>,
+    ) -> Result<bool, Self::Error> {
+        let ancestor = git_ext::Oid::from(ancestor.into());
+        let descendant = git_ext::Oid::from(descendant.into());
+        Ok(git_ext::is_ancestor(
+            self.store.as_raw(),
+            *ancestor,
+            *descendant,
+        )?)
+    }
+
     fn update(&self) -> Result<Option<Self::Oid>, Self::Error> {
         use backoff::ExponentialBackoff;
         use git::refs::Updated::*;
@@ -633,6 +675,24 @@ impl Net for Context<'_> {
     ) -> Result<(), Self::Error> {
         self.net.run_fetch(max_pack_bytes, wants, haves).await
     }
+
+    fn bytes_received(&self) -> Option<u64> {
+        self.net.bytes_received()
+    }
+}
+
+impl Metrics for Context<'_> {
+    fn record_phase(&self, m: Measurement) {
+        self.metrics.record_phase(m)
+    }
+
+    fn record_refs(&self, updated: usize, rejected: usize) {
+        self.metrics.record_refs(updated, rejected)
+    }
+
+    fn record_rejection(&self, reason: Rejection) {
+        self.metrics.record_rejection(reason)
+    }
 }
 
 #[async_trait]
@@ -648,6 +708,22 @@ impl io::Connection for quic::Connection {
         let up = upgrade::upgrade(bi, upgrade::Git).await?;
         Ok(up.into_stream().split())
     }
+
+    fn bytes_received(&self) -> Option<u64> {
+        self.meter().map(quic::Meter::recvd)
+    }
+}
+
+impl Usage for Context<'_> {
+    type Error = git::storage::stats::Error;
+
+    fn urn_usage(&self) -> Result<u64, Self::Error> {
+        self.sizes.size(self.store, &self.urn, None)
+    }
+
+    fn remote_usage(&self, remote: &PeerId) -> Result<u64, Self::Error> {
+        self.sizes.size(self.store, &self.urn, Some(remote))
+    }
 }
 
 impl LocalPeer for Context<'_> {
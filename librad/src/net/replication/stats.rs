@@ -0,0 +1,154 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    time::Duration,
+};
+
+use link_replication::metrics::{Measurement, Phase, Rejection};
+use parking_lot::Mutex;
+
+/// Upper bounds, in seconds, of the buckets of [`Stats::duration_buckets`].
+pub const DURATION_BUCKETS: [f64; 10] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Cumulative replication statistics.
+pub struct Stats {
+    /// Number of replications which completed successfully.
+    pub succeeded: u64,
+    /// Number of replications which failed.
+    pub failed: u64,
+    /// Number of replications which were abandoned because no replication
+    /// slot became available in time.
+    pub slot_timeouts: u64,
+    /// Number of replications (successful or not) which completed within the
+    /// respective upper bound of [`DURATION_BUCKETS`].
+    ///
+    /// Like Prometheus histograms, the buckets are cumulative.
+    pub duration_buckets: [u64; DURATION_BUCKETS.len()],
+    /// Total time spent replicating.
+    pub duration_sum: Duration,
+    /// Statistics per replication [`Phase`].
+    pub phases: BTreeMap<Phase, PhaseStats>,
+    /// Number of refs updated by successful replications.
+    pub refs_updated: u64,
+    /// Number of ref updates rejected by successful replications.
+    pub refs_rejected: u64,
+    /// Number of refs which did not end up in the desired state, by reason.
+    pub rejections: BTreeMap<Rejection, u64>,
+    /// Statistics of the object database used for replication.
+    pub odb: link_replication::io::OdbStats,
+}
+
+/// Cumulative statistics of a single replication [`Phase`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhaseStats {
+    /// Number of times the phase was completed (successfully or not).
+    pub count: u64,
+    /// Number of times the phase failed, aborting the replication.
+    pub failures: u64,
+    /// Total time spent in the phase.
+    pub duration_sum: Duration,
+    /// Total bytes received from the network during the phase.
+    pub bytes_received: u64,
+}
+
+#[derive(Default)]
+pub(super) struct Recorder {
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    slot_timeouts: AtomicU64,
+    duration_buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    duration_sum_micros: AtomicU64,
+    phases: [PhaseRecorder; Phase::ALL.len()],
+    refs_updated: AtomicU64,
+    refs_rejected: AtomicU64,
+    rejections: Mutex<BTreeMap<Rejection, u64>>,
+}
+
+#[derive(Default)]
+struct PhaseRecorder {
+    count: AtomicU64,
+    failures: AtomicU64,
+    duration_sum_micros: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Recorder {
+    pub fn record_slot_timeout(&self) {
+        self.slot_timeouts.fetch_add(1, Relaxed);
+    }
+
+    pub fn record(&self, elapsed: Duration, success: bool) {
+        if success {
+            self.succeeded.fetch_add(1, Relaxed);
+        } else {
+            self.failed.fetch_add(1, Relaxed);
+        }
+
+        let secs = elapsed.as_secs_f64();
+        for (bound, count) in DURATION_BUCKETS.iter().zip(&self.duration_buckets) {
+            if secs <= *bound {
+                count.fetch_add(1, Relaxed);
+            }
+        }
+        self.duration_sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Relaxed);
+    }
+
+    pub fn record_phase(&self, m: Measurement) {
+        let phase = &self.phases[m.phase as usize];
+        phase.count.fetch_add(1, Relaxed);
+        if !m.success {
+            phase.failures.fetch_add(1, Relaxed);
+        }
+        phase
+            .duration_sum_micros
+            .fetch_add(m.elapsed.as_micros() as u64, Relaxed);
+        if let Some(bytes) = m.bytes_received {
+            phase.bytes_received.fetch_add(bytes, Relaxed);
+        }
+    }
+
+    pub fn record_refs(&self, updated: usize, rejected: usize) {
+        self.refs_updated.fetch_add(updated as u64, Relaxed);
+        self.refs_rejected.fetch_add(rejected as u64, Relaxed);
+    }
+
+    pub fn record_rejection(&self, reason: Rejection) {
+        *self.rejections.lock().entry(reason).or_default() += 1;
+    }
+
+    pub fn snapshot(&self, odb: link_replication::io::OdbStats) -> Stats {
+        let mut duration_buckets = [0; DURATION_BUCKETS.len()];
+        for (snap, count) in duration_buckets.iter_mut().zip(&self.duration_buckets) {
+            *snap = count.load(Relaxed);
+        }
+
+        Stats {
+            succeeded: self.succeeded.load(Relaxed),
+            failed: self.failed.load(Relaxed),
+            slot_timeouts: self.slot_timeouts.load(Relaxed),
+            duration_buckets,
+            duration_sum: Duration::from_micros(self.duration_sum_micros.load(Relaxed)),
+            phases: Phase::ALL
+                .iter()
+                .map(|phase| {
+                    let rec = &self.phases[*phase as usize];
+                    let stats = PhaseStats {
+                        count: rec.count.load(Relaxed),
+                        failures: rec.failures.load(Relaxed),
+                        duration_sum: Duration::from_micros(rec.duration_sum_micros.load(Relaxed)),
+                        bytes_received: rec.bytes_received.load(Relaxed),
+                    };
+                    (*phase, stats)
+                })
+                .collect(),
+            refs_updated: self.refs_updated.load(Relaxed),
+            refs_rejected: self.refs_rejected.load(Relaxed),
+            rejections: self.rejections.lock().clone(),
+            odb,
+        }
+    }
+}
@@ -26,19 +26,37 @@ use rustls::{
 };
 use time::{Date, OffsetDateTime};
 
-use crate::{net::x509, PeerId, Signer};
+use crate::{
+    identities::delegation::certificate::Certificate as DeviceCertificate,
+    net::x509,
+    PeerId,
+    Signer,
+};
 
 pub fn make_client_config<S>(signer: S) -> Result<rustls::ClientConfig, S::Error>
+where
+    S: Signer + Clone + Send + Sync + 'static,
+    S::Error: std::error::Error + Send + Sync + 'static,
+{
+    make_client_config_certified(signer, None)
+}
+
+/// Like [`make_client_config`], but presents the [`DeviceCertificate`] for the
+/// key of `signer` to the server (see [`x509::Certificate::acting_for`]).
+pub fn make_client_config_certified<S>(
+    signer: S,
+    device: Option<DeviceCertificate>,
+) -> Result<rustls::ClientConfig, S::Error>
 where
     S: Signer + Clone + Send + Sync + 'static,
     S::Error: std::error::Error + Send + Sync + 'static,
 {
     let peer_id = PeerId::from_signer(&signer);
-    let cert = x509::Certificate::generate(&signer)?;
+    let cert = x509::Certificate::generate_certified(&signer, device.clone())?;
 
     let mut cfg = rustls::ClientConfig::new();
     cfg.versions = vec![rustls::ProtocolVersion::TLSv1_3];
-    cfg.client_auth_cert_resolver = Arc::new(CertResolver::new(signer, cert));
+    cfg.client_auth_cert_resolver = Arc::new(CertResolver::new(signer, device, cert));
     cfg.dangerous()
         .set_certificate_verifier(Arc::new(RadServerCertVerifier::new(peer_id)));
 
@@ -46,16 +64,29 @@ where
 }
 
 pub fn make_server_config<S>(signer: S) -> Result<rustls::ServerConfig, S::Error>
+where
+    S: Signer + Clone + Send + Sync + 'static,
+    S::Error: std::error::Error + Send + Sync + 'static,
+{
+    make_server_config_certified(signer, None)
+}
+
+/// Like [`make_server_config`], but presents the [`DeviceCertificate`] for the
+/// key of `signer` to clients (see [`x509::Certificate::acting_for`]).
+pub fn make_server_config_certified<S>(
+    signer: S,
+    device: Option<DeviceCertificate>,
+) -> Result<rustls::ServerConfig, S::Error>
 where
     S: Signer + Clone + Send + Sync + 'static,
     S::Error: std::error::Error + Send + Sync + 'static,
 {
     let peer_id = PeerId::from_signer(&signer);
-    let cert = x509::Certificate::generate(&signer)?;
+    let cert = x509::Certificate::generate_certified(&signer, device.clone())?;
 
     let mut cfg = rustls::ServerConfig::new(Arc::new(RadClientCertVerifier::new(peer_id)));
     cfg.versions = vec![rustls::ProtocolVersion::TLSv1_3];
-    cfg.cert_resolver = Arc::new(CertResolver::new(signer, cert));
+    cfg.cert_resolver = Arc::new(CertResolver::new(signer, device, cert));
     // FIXME: session resumption is broken in rustls < 0.19 -- we can't get at
     // the client certs when resuming. Disable until we can upgrade (depends on
     // https://github.com/quinn-rs/quinn/pull/873)
@@ -71,11 +102,12 @@ struct Cert {
 
 struct CertResolver {
     signer: BoxedSigner,
+    device: Option<DeviceCertificate>,
     cert: RwLock<Cert>,
 }
 
 impl CertResolver {
-    fn new<S>(signer: S, cert: x509::Certificate) -> Self
+    fn new<S>(signer: S, device: Option<DeviceCertificate>, cert: x509::Certificate) -> Self
     where
         S: Signer + Clone + Send + Sync + 'static,
         S::Error: std::error::Error + Send + Sync + 'static,
@@ -85,6 +117,7 @@ impl CertResolver {
         let cert = rustls::Certificate(cert.to_der());
         Self {
             signer,
+            device,
             cert: RwLock::new(Cert { expires, cert }),
         }
     }
@@ -95,7 +128,7 @@ impl CertResolver {
         let read = self.cert.read().unwrap();
         if now >= read.expires {
             drop(read);
-            let fresh = x509::Certificate::generate(&self.signer)?;
+            let fresh = x509::Certificate::generate_certified(&self.signer, self.device.clone())?;
             let expires = x509::validity_time_as_date(&fresh.tbs_certificate.validity.not_after);
             let der = rustls::Certificate(fresh.to_der());
             {
@@ -166,7 +199,11 @@ impl ResolvesServerCert for CertResolver {
                         e
                     })
                     .ok()?;
-                if peer_id == PeerId::from_signer(&self.signer) {
+                let acting_for = self
+                    .device
+                    .as_ref()
+                    .map(|device| PeerId::from(device.issuer));
+                if peer_id == PeerId::from_signer(&self.signer) || Some(peer_id) == acting_for {
                     self.certified_key(client_hello.sigschemes())
                 } else {
                     tracing::warn!("sni doesn't match local peer id");
@@ -228,10 +265,13 @@ impl ServerCertVerifier for AccursedUnutterableUnsafeInsecureCertificateVerifier
         let cert = x509::Certificate::from_der(&presented_certs[0].0)
             .map_err(|e| TLSError::PeerIncompatibleError(e.to_string()))?;
 
-        // Both must be equal
-        if &peer_id_dns != cert.peer_id_ref() {
+        // Both must be equal, unless the key is certified to act for the peer
+        // named by the DNS name
+        if &peer_id_dns != cert.peer_id_ref() && peer_id_dns != cert.acting_for() {
             return Err(TLSError::PeerIncompatibleError(
-                "DNS name and subjectPublicKeyInfo must be equal".into(),
+                "DNS name and subjectPublicKeyInfo must be equal, or the device certificate \
+                 must be issued by the DNS name"
+                    .into(),
             ));
         }
 
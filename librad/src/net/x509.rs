@@ -6,11 +6,12 @@
 use std::{convert::TryFrom as _, ops::Deref, time::Duration};
 
 use futures::executor::block_on;
+use oid::ObjectIdentifier;
 use picky_asn1::{
     bit_string::BitString,
     date::{GeneralizedTime, UTCTime},
     restricted_string::IA5String,
-    wrapper::IntegerAsn1,
+    wrapper::{IntegerAsn1, ObjectIdentifierAsn1, OctetStringAsn1},
 };
 use picky_asn1_der::Asn1DerError;
 use picky_asn1_x509::{
@@ -29,10 +30,34 @@ use picky_asn1_x509::{
     Validity,
     Version,
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::{Date, OffsetDateTime};
 
-use crate::{keystore::sign::Signer, PeerId};
+use crate::{
+    identities::delegation::certificate::Certificate as DeviceCertificate,
+    keystore::sign::Signer,
+    PeerId,
+};
+
+/// Object identifier of the extension carrying a [`DeviceCertificate`].
+///
+/// Not registered, but from the private enterprise arc, so should not clash
+/// with any extension a TLS implementation cares about.
+const DEVICE_CERTIFICATE_OID: &str = "1.3.6.1.4.1.58419.1.1";
+
+/// DER layout of a non-critical X509 extension with an opaque value.
+#[derive(Serialize, Deserialize)]
+struct OpaqueExtension {
+    extn_id: ObjectIdentifierAsn1,
+    extn_value: OctetStringAsn1,
+}
+
+fn device_certificate_oid() -> ObjectIdentifierAsn1 {
+    ObjectIdentifier::try_from(DEVICE_CERTIFICATE_OID)
+        .expect("device certificate OID is valid")
+        .into()
+}
 
 #[derive(Debug, Error)]
 pub enum FromDerError {
@@ -51,20 +76,41 @@ pub enum FromDerError {
     #[error("subject public key and common name must be the same")]
     KeyMismatch,
 
+    #[error("the device certificate is not valid for the subject public key")]
+    InvalidDeviceCertificate,
+
     #[error(transparent)]
     Asn1(#[from] Asn1DerError),
 }
 
 /// Self-signed X509 certificate.
+///
+/// The certificate may carry a [`DeviceCertificate`] for the subject public
+/// key, in which case the peer acts on behalf of the issuer of the latter (see
+/// [`Certificate::acting_for`]).
 #[derive(Debug, PartialEq)]
 pub struct Certificate {
     peer_id: PeerId,
+    device: Option<DeviceCertificate>,
     cert: x509::Certificate,
 }
 
 impl Certificate {
     /// Generate a new self-signed [`Certificate`].
     pub fn generate<S>(signer: &S) -> Result<Self, S::Error>
+    where
+        S: Signer,
+        S::Error: std::error::Error,
+    {
+        Self::generate_certified(signer, None)
+    }
+
+    /// Generate a new self-signed [`Certificate`], embedding the given
+    /// [`DeviceCertificate`] for the key of `signer`.
+    pub fn generate_certified<S>(
+        signer: &S,
+        device: Option<DeviceCertificate>,
+    ) -> Result<Self, S::Error>
     where
         S: Signer,
         S::Error: std::error::Error,
@@ -82,16 +128,31 @@ impl Certificate {
             ),
         };
         let validity = valid_until(Duration::from_secs(7889400)); // 3 months
-        let extensions = Extensions(vec![
-            Extension::new_subject_alt_name(vec![GeneralName::DnsName(
-                peer_id.to_string().parse::<IA5String>().unwrap().into(),
-            )]),
-            Extension::new_extended_key_usage(ExtendedKeyUsage::new(vec![
-                oids::kp_server_auth(),
-                oids::kp_client_auth(),
-            ])),
-        ])
-        .into();
+        let extensions = {
+            // A certified device is also addressable by the peer it acts for
+            let names = std::iter::once(peer_id)
+                .chain(device.as_ref().map(|device| PeerId::from(device.issuer)))
+                .map(|name| {
+                    GeneralName::DnsName(name.to_string().parse::<IA5String>().unwrap().into())
+                })
+                .collect::<Vec<_>>();
+            let mut extensions = vec![
+                Extension::new_subject_alt_name(names),
+                Extension::new_extended_key_usage(ExtendedKeyUsage::new(vec![
+                    oids::kp_server_auth(),
+                    oids::kp_client_auth(),
+                ])),
+            ];
+            if let Some(device) = &device {
+                let opaque = OpaqueExtension {
+                    extn_id: device_certificate_oid(),
+                    extn_value: serde_json::to_vec(device).unwrap().into(),
+                };
+                let der = picky_asn1_der::to_vec(&opaque).unwrap();
+                extensions.push(picky_asn1_der::from_bytes(&der).unwrap());
+            }
+            Extensions(extensions).into()
+        };
 
         let tbs_certificate = TbsCertificate {
             version: Version::V3.into(),
@@ -116,7 +177,11 @@ impl Certificate {
             signature_value,
         };
 
-        Ok(Self { peer_id, cert })
+        Ok(Self {
+            peer_id,
+            device,
+            cert,
+        })
     }
 
     /// Serialise in DER format.
@@ -127,7 +192,9 @@ impl Certificate {
     /// Attempt to deserialise from DER format.
     ///
     /// Also validates that the subject public key is equal to the subject, and
-    /// both parse as the same [`PeerId`].
+    /// both parse as the same [`PeerId`]. If a [`DeviceCertificate`] is
+    /// present, it must be issued for the subject public key, carry a valid
+    /// signature, and be valid at the current time.
     pub fn from_der(der: &[u8]) -> Result<Self, FromDerError> {
         let cert: x509::Certificate = picky_asn1_der::from_bytes(der)?;
         let peer_id = {
@@ -159,13 +226,46 @@ impl Certificate {
             }
         }?;
 
-        Ok(Self { peer_id, cert })
+        let device = find_device_certificate(&cert)
+            .map(|device| {
+                let now = OffsetDateTime::now_utc().unix_timestamp();
+                if &device.device == peer_id.as_public_key()
+                    && device.verify_signature()
+                    && device.is_valid_at(now)
+                {
+                    Ok(device)
+                } else {
+                    Err(FromDerError::InvalidDeviceCertificate)
+                }
+            })
+            .transpose()?;
+
+        Ok(Self {
+            peer_id,
+            device,
+            cert,
+        })
     }
 
     pub fn peer_id(&self) -> PeerId {
         self.peer_id
     }
 
+    /// The [`DeviceCertificate`] embedded in this certificate, if any.
+    pub fn device_certificate(&self) -> Option<&DeviceCertificate> {
+        self.device.as_ref()
+    }
+
+    /// The [`PeerId`] the subject acts on behalf of.
+    ///
+    /// This is the issuer of the [`DeviceCertificate`] if one is present, and
+    /// [`Certificate::peer_id`] otherwise.
+    pub fn acting_for(&self) -> PeerId {
+        self.device
+            .as_ref()
+            .map_or(self.peer_id, |device| PeerId::from(device.issuer))
+    }
+
     pub fn peer_id_ref(&self) -> &PeerId {
         self.as_ref()
     }
@@ -185,6 +285,19 @@ impl AsRef<PeerId> for Certificate {
     }
 }
 
+fn find_device_certificate(cert: &x509::Certificate) -> Option<DeviceCertificate> {
+    let oid = device_certificate_oid();
+    cert.tbs_certificate
+        .extensions
+        .0
+         .0
+        .iter()
+        .filter_map(|ext| picky_asn1_der::to_vec(ext).ok())
+        .filter_map(|der| picky_asn1_der::from_bytes::<OpaqueExtension>(&der).ok())
+        .find(|opaque| opaque.extn_id == oid)
+        .and_then(|opaque| serde_json::from_slice(&opaque.extn_value.0).ok())
+}
+
 fn valid_until(d: Duration) -> Validity {
     let now = OffsetDateTime::now_utc();
     let until = now + d;
@@ -24,6 +24,7 @@ pub struct Paths {
     git_dir: PathBuf,
     git_includes_dir: PathBuf,
     cob_cache_dir: PathBuf,
+    identities_cache_dir: PathBuf,
     socket_dir: PathBuf,
     seeds_file: PathBuf,
     hooks_dir: PathBuf,
@@ -45,6 +46,7 @@ impl Paths {
             git_dir: data_dir.join("git"),
             git_includes_dir: config_dir.join("git-includes"),
             cob_cache_dir: cache_dir.join("cob-cache"),
+            identities_cache_dir: cache_dir.join("identities-cache"),
             socket_dir: socket_dir()?,
             seeds_file: config_dir.join("seeds"),
             hooks_dir: data_dir.join("hooks"),
@@ -61,6 +63,7 @@ impl Paths {
             git_dir: root.join("git"),
             git_includes_dir: root.join("git-includes"),
             cob_cache_dir: root.join("cob-cache"),
+            identities_cache_dir: root.join("identities-cache"),
             socket_dir: socket_dir()?,
             seeds_file: root.join("seeds"),
             hooks_dir: root.join("hooks"),
@@ -84,6 +87,10 @@ impl Paths {
         &self.cob_cache_dir
     }
 
+    pub fn identities_cache_dir(&self) -> &Path {
+        &self.identities_cache_dir
+    }
+
     pub fn hooks_dir(&self) -> &Path {
         &self.hooks_dir
     }
@@ -96,6 +103,7 @@ impl Paths {
             git_dir,
             git_includes_dir,
             cob_cache_dir,
+            identities_cache_dir,
             hooks_dir,
             socket_dir: _,
             seeds_file: _,
@@ -106,6 +114,7 @@ impl Paths {
             git_dir.as_path(),
             git_includes_dir.as_path(),
             cob_cache_dir.as_path(),
+            identities_cache_dir.as_path(),
             hooks_dir.as_path(),
         ]
         .into_iter()
@@ -132,6 +132,39 @@ fn when_disconnected_and_no_addr_hints() {
     assert!(matches!(res, Err(e) if e.to_string().starts_with("unable to obtain connection to")));
 }
 
+/// Each phase is counted once per replication, even though cloning enters
+/// some phases more than once.
+#[test]
+fn phases_counted_once() {
+    logging::init();
+
+    let net = testnet::run(disconnected_config()).unwrap();
+    net.enter(async {
+        let host = Host::init(&net.peers()[0]).await;
+        let leecher = Leecher(&net.peers()[1]);
+        let host_peer = host.peer.peer_id();
+        let host_addrs = host.peer.listen_addrs().to_vec();
+        let urn = host.project.project.urn();
+
+        leecher.clone_from(host, true).await.unwrap();
+        leecher
+            .0
+            .client()
+            .unwrap()
+            .replicate((host_peer, host_addrs), urn, None)
+            .await
+            .expect("error pulling from host");
+
+        let stats = leecher.0.replication_stats();
+        assert_eq!(stats.succeeded, 2);
+        for (phase, phase_stats) in &stats.phases {
+            if ["peek", "rad_refs"].contains(&phase.as_str()) {
+                assert_eq!(phase_stats.count, 2, "phase {}", phase.as_str());
+            }
+        }
+    })
+}
+
 struct Host<'a> {
     project: TestProject,
     peer: &'a RunningTestPeer,
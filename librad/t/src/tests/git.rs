@@ -3,10 +3,14 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+mod attestation;
+mod bundle;
+mod fsck;
 mod include;
 mod local;
 mod p2p;
 mod project;
+mod purge;
 mod refs;
 mod storage;
 mod tracking;
@@ -0,0 +1,122 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use either::Either::Right;
+
+use it_helpers::tmp;
+use librad::{
+    git::{
+        identities::{self, attestation},
+        storage::{ReadOnlyStorage as _, Storage},
+        types::{Namespace, Reference},
+    },
+    identities::{delegation, payload},
+    PeerId,
+    SecretKey,
+};
+use link_identities_test::helpers;
+
+#[test]
+fn vouch() -> anyhow::Result<()> {
+    let key = SecretKey::new();
+    let tmp = tmp::storage(key.clone());
+    let storage: &Storage = &tmp;
+    let whoami = helpers::dylan(storage, &key)?;
+    let colleague = identities::person::create(
+        storage,
+        payload::Person {
+            name: "cheyenne".into(),
+        },
+        delegation::Direct::new(key.public()),
+    )?;
+
+    let signed = attestation::vouch(storage, &whoami, &colleague.urn(), Some("colleague".into()))?;
+    assert_eq!(signed.attestation.attester, whoami.urn());
+    assert_eq!(signed.attestation.subject, colleague.urn());
+    assert_eq!(
+        attestation::get(storage, &whoami.urn(), &colleague.urn(), None::<PeerId>)?,
+        Some(signed.clone())
+    );
+    assert_eq!(
+        attestation::list(storage, &whoami.urn())?,
+        vec![signed.clone()]
+    );
+
+    let at = storage
+        .reference(&Reference::rad_attestation(
+            Namespace::from(&whoami.urn()),
+            None,
+            &colleague.urn(),
+        ))?
+        .expect("attestation ref exists")
+        .peel_to_commit()?
+        .id();
+    let mut forged = signed.clone();
+    forged.attestation.note = Some("best friend".into());
+    assert_matches!(
+        forged.verify(storage, at),
+        Err(attestation::Error::InvalidSignature { .. })
+    );
+
+    let proj = identities::project::create(
+        storage,
+        whoami.clone(),
+        payload::Project {
+            name: "reMarkable 3".into(),
+            description: None,
+            default_branch: None,
+        },
+        delegation::Indirect::try_from_iter(Some(Right(whoami.clone().into_inner().into_inner())))
+            .unwrap(),
+    )?;
+    assert!(attestation::vouched_peers(storage, &proj.urn())?.contains(&PeerId::from(key.public())));
+
+    assert!(attestation::withdraw(storage, &whoami, &colleague.urn())?);
+    assert!(attestation::list(storage, &whoami.urn())?.is_empty());
+    assert_eq!(
+        attestation::get(storage, &whoami.urn(), &colleague.urn(), None::<PeerId>)?,
+        None
+    );
+    assert!(attestation::vouched_peers(storage, &proj.urn())?.is_empty());
+    assert!(!attestation::withdraw(storage, &whoami, &colleague.urn())?);
+
+    Ok(())
+}
+
+#[test]
+fn withdraw_supersedes_replicas() -> anyhow::Result<()> {
+    let key = SecretKey::new();
+    let tmp = tmp::storage(key.clone());
+    let storage: &Storage = &tmp;
+    let whoami = helpers::dylan(storage, &key)?;
+    let colleague = identities::person::create(
+        storage,
+        payload::Person {
+            name: "cheyenne".into(),
+        },
+        delegation::Direct::new(key.public()),
+    )?;
+    attestation::vouch(storage, &whoami, &colleague.urn(), None)?;
+
+    // Pretend the attestation was replicated back to us by another peer
+    let peer = PeerId::from(SecretKey::new());
+    let namespace = Namespace::from(&whoami.urn());
+    let local = Reference::rad_attestation(namespace.clone(), None, &colleague.urn());
+    let remote = Reference::rad_attestation(namespace, peer, &colleague.urn());
+    let repo = git2::Repository::open(storage.path())?;
+    let at = repo.refname_to_id(&local.to_string())?;
+    repo.reference(&remote.to_string(), at, false, "replicated attestation")?;
+    assert_eq!(attestation::list(storage, &whoami.urn())?.len(), 1);
+
+    assert!(attestation::withdraw(storage, &whoami, &colleague.urn())?);
+    assert!(attestation::list(storage, &whoami.urn())?.is_empty());
+    assert!(attestation::vouched_peers(storage, &whoami.urn())?.is_empty());
+    // The stale copy itself is still intact
+    assert!(attestation::get(storage, &whoami.urn(), &colleague.urn(), peer)?.is_some());
+
+    // Vouching again supersedes the withdrawal
+    attestation::vouch(storage, &whoami, &colleague.urn(), None)?;
+    assert_eq!(attestation::list(storage, &whoami.urn())?.len(), 1);
+
+    Ok(())
+}
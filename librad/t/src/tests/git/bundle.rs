@@ -0,0 +1,156 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use it_helpers::{fixed::TestProject, tmp};
+use librad::{
+    git::{
+        bundle::{export, import, Error},
+        storage::ReadOnlyStorage as _,
+        tracking::is_tracked,
+        types::Namespace,
+        Urn,
+    },
+    reflike,
+    SecretKey,
+};
+
+#[test]
+fn round_trip() {
+    let exporter = tmp::storage(SecretKey::new());
+    let importer = tmp::storage(SecretKey::new());
+    let proj = TestProject::create(&exporter).unwrap();
+    let urn = proj.project.urn();
+
+    let out = tempfile::tempdir().unwrap();
+    let bundle = out.path().join("project.bundle");
+    let manifest = export(&exporter, &urn, &bundle).unwrap();
+    assert_eq!(manifest.peer, *exporter.peer_id());
+    assert!(manifest.delegates.contains(&proj.owner.urn()));
+
+    let imported = import(&importer, &bundle).unwrap();
+    assert_eq!(imported.tracked, vec![*exporter.peer_id()]);
+    assert!(is_tracked(&*importer, &urn, Some(*exporter.peer_id())).unwrap());
+    assert!(importer
+        .has_ref(&urn.clone().with_path(reflike!("refs/rad/id")))
+        .unwrap());
+    assert!(importer
+        .has_ref(
+            &urn.clone().with_path(
+                reflike!("refs/remotes")
+                    .join(exporter.peer_id())
+                    .join(reflike!("rad/signed_refs"))
+            )
+        )
+        .unwrap());
+}
+
+#[test]
+fn rejects_unsigned() {
+    let exporter = tmp::storage(SecretKey::new());
+    let importer = tmp::storage(SecretKey::new());
+    let proj = TestProject::create(&exporter).unwrap();
+    let urn = proj.project.urn();
+
+    // A branch which is not covered by `rad/signed_refs`
+    {
+        let repo = git2::Repository::open(exporter.path()).unwrap();
+        let ns = format!("refs/namespaces/{}/refs", Namespace::from(&urn));
+        let id = repo.refname_to_id(&format!("{}/rad/id", ns)).unwrap();
+        repo.reference(&format!("{}/heads/rogue", ns), id, false, "rogue")
+            .unwrap();
+    }
+
+    let out = tempfile::tempdir().unwrap();
+    let bundle = out.path().join("project.bundle");
+    export(&exporter, &urn, &bundle).unwrap();
+    assert_matches!(
+        import(&importer, &bundle),
+        Err(Error::Unsigned { peer, name }) if peer == *exporter.peer_id() && name == "heads/rogue"
+    );
+    assert!(!importer
+        .has_ref(&urn.clone().with_path(reflike!("refs/rad/id")))
+        .unwrap());
+}
+
+#[test]
+fn rejects_misfiled_identity() {
+    let exporter = tmp::storage(SecretKey::new());
+    let importer = tmp::storage(SecretKey::new());
+    let proj = TestProject::create(&exporter).unwrap();
+    let urn = proj.project.urn();
+
+    // Pass off the identity of the owner as the one of the project
+    {
+        let repo = git2::Repository::open(exporter.path()).unwrap();
+        let id = repo
+            .refname_to_id(&format!(
+                "refs/namespaces/{}/refs/rad/id",
+                Namespace::from(&proj.owner.urn())
+            ))
+            .unwrap();
+        repo.reference(
+            &format!("refs/namespaces/{}/refs/rad/id", Namespace::from(&urn)),
+            id,
+            true,
+            "misfile",
+        )
+        .unwrap();
+    }
+
+    let out = tempfile::tempdir().unwrap();
+    let bundle = out.path().join("project.bundle");
+    export(&exporter, &urn, &bundle).unwrap();
+    assert_matches!(
+        import(&importer, &bundle),
+        Err(Error::UrnMismatch { expected, actual })
+            if expected == urn && actual == proj.owner.urn()
+    );
+    assert!(!importer
+        .has_ref(&urn.clone().with_path(reflike!("refs/rad/id")))
+        .unwrap());
+}
+
+#[test]
+fn rejects_foreign_namespace() {
+    let exporter = tmp::storage(SecretKey::new());
+    let importer = tmp::storage(SecretKey::new());
+    let proj = TestProject::create(&exporter).unwrap();
+    let urn = proj.project.urn();
+
+    // Smuggle in a namespace which is not a delegate of the project
+    let foreign = Urn::new(git2::Oid::zero().into());
+    {
+        let repo = git2::Repository::open(exporter.path()).unwrap();
+        let ns = format!("refs/namespaces/{}/refs", Namespace::from(&urn));
+        let id = repo.refname_to_id(&format!("{}/rad/id", ns)).unwrap();
+        repo.reference(
+            &format!("{}/rad/ids/{}", ns, foreign.encode_id()),
+            id,
+            false,
+            "foreign",
+        )
+        .unwrap();
+        repo.reference(
+            &format!("refs/namespaces/{}/refs/rad/id", Namespace::from(&foreign)),
+            id,
+            false,
+            "foreign",
+        )
+        .unwrap();
+    }
+
+    let out = tempfile::tempdir().unwrap();
+    let bundle = out.path().join("project.bundle");
+    let manifest = export(&exporter, &urn, &bundle).unwrap();
+    assert!(manifest.delegates.contains(&foreign));
+    assert_matches!(
+        import(&importer, &bundle),
+        Err(Error::ForeignNamespace(ns)) if ns == Namespace::from(&foreign).to_string()
+    );
+    assert!(!importer
+        .has_ref(&urn.clone().with_path(reflike!("refs/rad/id")))
+        .unwrap());
+    assert!(!importer
+        .has_ref(&foreign.with_path(reflike!("refs/rad/id")))
+        .unwrap());
+}
@@ -0,0 +1,95 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use git_ref_format::{lit, name, Namespaced, Qualified};
+use it_helpers::git::create_commit;
+use librad::{
+    git::{
+        fsck::{check, repair, Problem},
+        storage::Storage,
+        tracking::{self, policy, track, Config},
+        Urn,
+    },
+    paths::Paths,
+    PeerId,
+    SecretKey,
+};
+
+#[test]
+fn repair_prunes_invalid() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let urn = Urn::new(git2::Oid::zero().into());
+        let remote = PeerId::from(SecretKey::new());
+
+        assert!(track(
+            &storage,
+            &urn,
+            Some(remote),
+            Config::default(),
+            policy::Track::Any,
+        )
+        .unwrap()
+        .is_ok());
+
+        // A namespace without `rad/id`
+        let branch = Namespaced::from(lit::refs_namespaces(
+            &urn,
+            Qualified::from(lit::refs_heads(name::MAIN)),
+        ))
+        .into_qualified();
+        // A remote-tracking branch in the same namespace
+        let remote_branch = format!(
+            "refs/namespaces/{}/refs/remotes/{}/heads/main",
+            urn.encode_id(),
+            remote
+        );
+        let repo = git2::Repository::open(paths.git_dir()).unwrap();
+        // A tracking entry which isn't a tracking config
+        let garbage = {
+            let oid = create_commit(&repo, branch.clone()).unwrap();
+            repo.reference(&remote_branch, oid, false, "remote")
+                .unwrap();
+
+            let blob = repo.blob(b"garbage").unwrap();
+            let other = Urn::new(blob.into());
+            let name = format!(
+                "refs/rad/remotes/{}/{}",
+                other.encode_id(),
+                PeerId::from(SecretKey::new())
+            );
+            repo.reference(&name, blob, false, "garbage").unwrap();
+            name
+        };
+
+        let report = check(&storage).unwrap();
+        assert_eq!(report.namespaces, 1);
+        assert_eq!(report.tracking, 2);
+        assert_matches!(
+            report.problems.as_slice(),
+            [
+                Problem::MissingIdentity { urn: missing },
+                Problem::InvalidTracking { name, .. }
+            ] if missing == &urn && name == &garbage
+        );
+
+        let repaired = repair(&storage, &report).unwrap();
+        assert_eq!(repaired.unverified, vec![urn.clone()]);
+        assert!(repaired.refs.contains(&garbage));
+        assert!(repaired.refs.contains(&remote_branch));
+
+        // Only the remote-tracking refs of the namespace are gone
+        assert!(repo.find_reference(branch.as_str()).is_ok());
+        assert!(repo.find_reference(&remote_branch).is_err());
+        assert!(tracking::is_tracked(&storage, &urn, Some(remote)).unwrap());
+
+        let report = check(&storage).unwrap();
+        assert_matches!(
+            report.problems.as_slice(),
+            [Problem::MissingIdentity { urn: missing }] if missing == &urn
+        );
+        assert_eq!(report.namespaces, 1);
+    }
+}
@@ -3,11 +3,11 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use either::Either::Left;
+use either::Either::{Left, Right};
 
 use it_helpers::tmp;
 use librad::{
-    git::identities,
+    git::identities::{self, diff::Change},
     identities::{delegation, payload, SomeIdentity},
     SecretKey,
 };
@@ -20,6 +20,32 @@ lazy_static! {
     ]);
 }
 
+#[test]
+fn create_multisig() -> anyhow::Result<()> {
+    let storage = tmp::storage(DYLAN.clone());
+    let whoami = helpers::dylan(&storage, &DYLAN)?;
+    let cheyenne = SecretKey::new();
+    let proj = identities::project::create(
+        &storage,
+        whoami.clone(),
+        payload::Project {
+            name: "reMarkable 3".into(),
+            description: Some("The next big thing in e-ink technology".into()),
+            default_branch: Some("eink".into()),
+        },
+        delegation::Indirect::try_from_iter(vec![
+            Left(cheyenne.public()),
+            Right(whoami.into_inner().into_inner()),
+        ])
+        .unwrap(),
+    )?;
+    assert_eq!(
+        Some(proj.content_id),
+        identities::project::verify(&storage, &proj.urn())?.map(|proj| proj.content_id)
+    );
+    Ok(())
+}
+
 #[test]
 fn create_anonymous() -> anyhow::Result<()> {
     let storage = tmp::storage(DYLAN.clone());
@@ -46,3 +72,62 @@ fn create_anonymous() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn diff_revisions() -> anyhow::Result<()> {
+    let storage = tmp::storage(DYLAN.clone());
+    let whoami = helpers::dylan(&storage, &DYLAN)?;
+    let cheyenne = SecretKey::new();
+    let old = identities::project::create(
+        &storage,
+        whoami,
+        payload::Project {
+            name: "reMarkable 3".into(),
+            description: Some("The next big thing in e-ink technology".into()),
+            default_branch: Some("eink".into()),
+        },
+        delegation::Indirect::try_from_iter(Some(Left(DYLAN.public()))).unwrap(),
+    )?;
+
+    let ci = payload::Ext {
+        namespace: "https://example.com/ci".parse()?,
+        val: serde_json::json!({ "enabled": true }),
+    };
+    let new = identities::project::update(
+        &storage,
+        &old.urn(),
+        None,
+        payload::ProjectPayload::new(payload::Project {
+            name: "reMarkable 3".into(),
+            description: Some("The biggest thing in e-ink technology".into()),
+            default_branch: None,
+        })
+        .with_ext(ci.clone())?,
+        delegation::Indirect::try_from_iter(vec![Left(DYLAN.public()), Left(cheyenne.public())])
+            .unwrap(),
+    )?;
+
+    let diff = identities::diff::project_revisions(
+        &storage.read_only(),
+        old.content_id.into(),
+        new.content_id.into(),
+    )?;
+    assert_eq!(
+        diff.subject.keys().collect::<Vec<_>>(),
+        vec!["default_branch", "description"]
+    );
+    assert_matches!(diff.subject["default_branch"], Change::Removed { .. });
+    assert_matches!(diff.subject["description"], Change::Modified { .. });
+    assert_matches!(
+        diff.ext.get(&ci.namespace),
+        Some(Change::Added { new }) if new == &ci.val
+    );
+    assert_eq!(
+        diff.delegations.added_keys,
+        Some(cheyenne.public()).into_iter().collect()
+    );
+    assert!(diff.delegations.removed_keys.is_empty());
+    assert!(diff.delegations.added_urns.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use git_ref_format::{lit, name, Namespaced, Qualified};
+use it_helpers::git::create_commit;
+use librad::{
+    git::{
+        purge::purge,
+        storage::{ReadOnlyStorage as _, Storage},
+        tracking::{is_tracked, policy, track, Config},
+        Urn,
+    },
+    paths::Paths,
+    PeerId,
+    SecretKey,
+};
+
+#[test]
+fn purge_removes_refs_and_tracking() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let remote_peer = PeerId::from(SecretKey::new());
+        let urn = Urn::new(git2::Oid::zero().into());
+
+        assert!(track(
+            &storage,
+            &urn,
+            Some(remote_peer),
+            Config::default(),
+            policy::Track::Any,
+        )
+        .unwrap()
+        .is_ok());
+
+        let branch = Namespaced::from(lit::refs_namespaces(
+            &urn,
+            Qualified::from(lit::refs_remotes(name::Component::from(&remote_peer)))
+                .join(name::HEADS)
+                .join(name::MAIN),
+        ));
+        {
+            let repo = git2::Repository::open(paths.git_dir()).unwrap();
+            create_commit(&repo, branch.clone().into_qualified()).unwrap();
+        }
+
+        let purged = purge(&storage, &paths, &urn).unwrap();
+        assert_eq!(
+            purged.refs,
+            vec![branch.clone().into_qualified().as_str().to_owned()]
+        );
+        assert_eq!(purged.tracking, 1);
+
+        assert!(storage
+            .reference(&branch.into_qualified().into_refstring())
+            .unwrap()
+            .is_none());
+        assert!(!is_tracked(&storage, &urn, Some(remote_peer)).unwrap());
+        assert!(purge(&storage, &paths, &urn).unwrap().is_empty())
+    }
+}
@@ -180,6 +180,68 @@ mod verifying_refs {
     }
 }
 
+mod certified_refs {
+    use std::collections::BTreeMap;
+
+    use librad::{
+        git::refs::{Refs, Remotes, Signed},
+        identities::delegation::certificate::{Certificate, Certificates},
+        PeerId,
+        SecretKey,
+    };
+
+    #[test]
+    fn signed_by_certified_device() {
+        let person = SecretKey::new();
+        let device = SecretKey::new();
+        let issuer = PeerId::from(person.public());
+
+        let refs = Refs {
+            categorised_refs: [(
+                "heads".to_owned(),
+                [(
+                    "main".to_owned(),
+                    "dcf932a7aae2a74e7c8a6166df2aa295b4221235".parse().unwrap(),
+                )]
+                .into_iter()
+                .collect(),
+            )]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>(),
+            remotes: Remotes::new(),
+        };
+        let json = serde_json::to_vec(&refs.sign(&device).unwrap()).unwrap();
+
+        // The certificate is not part of the signed refs, so older peers can
+        // still parse them
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            value.as_object().unwrap().keys().collect::<Vec<_>>(),
+            vec!["refs", "signature"]
+        );
+
+        assert!(Signed::from_json(&json, &issuer).is_err());
+        assert!(Signed::from_json_certified(&json, &issuer, &Certificates::default()).is_err());
+
+        let certs = Certificates::from_iter(Some(
+            Certificate::issue(&person, device.public(), 0, i64::MAX).unwrap(),
+        ));
+        assert!(Signed::from_json_certified(&json, &issuer, &certs).is_ok());
+
+        // Expired certificates are not honoured
+        let expired = Certificates::from_iter(Some(
+            Certificate::issue(&person, device.public(), 0, 1).unwrap(),
+        ));
+        assert!(Signed::from_json_certified(&json, &issuer, &expired).is_err());
+
+        // Nor are certificates issued by someone else
+        let other = Certificates::from_iter(Some(
+            Certificate::issue(&SecretKey::new(), device.public(), 0, i64::MAX).unwrap(),
+        ));
+        assert!(Signed::from_json_certified(&json, &issuer, &other).is_err());
+    }
+}
+
 mod computing_refs {
     use it_helpers::fixed::TestProject;
     use librad::{
@@ -4,4 +4,8 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod config;
+mod maintenance;
+mod migration;
+mod snapshot;
+mod stats;
 mod watch;
@@ -0,0 +1,71 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use it_helpers::{fixed::TestProject, tmp};
+use librad::{
+    git::{
+        identities,
+        storage::{
+            maintenance::{self, Report, Task},
+            Storage,
+        },
+    },
+    SecretKey,
+};
+
+fn packs(storage: &Storage) -> usize {
+    std::fs::read_dir(storage.path().join("objects").join("pack"))
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .path()
+                .extension()
+                .map_or(false, |ext| ext == "pack")
+        })
+        .count()
+}
+
+fn tasks(report: &Report) -> Vec<Task> {
+    report.tasks.iter().map(|(task, _)| *task).collect()
+}
+
+#[test]
+fn run_all_tasks() {
+    let store = tmp::storage(SecretKey::new());
+    let storage: &Storage = &store;
+    let TestProject { project, .. } = TestProject::create(storage).unwrap();
+    assert_eq!(packs(storage), 0);
+
+    let config = maintenance::Config {
+        tasks: vec![
+            Task::Gc,
+            Task::Repack,
+            Task::CommitGraph,
+            Task::MultiPackIndex,
+        ],
+        delta_islands: true,
+    };
+    let report = maintenance::run(storage, &config).unwrap();
+    assert_eq!(tasks(&report), config.tasks);
+    assert_eq!(packs(storage), 1);
+
+    let verified = identities::project::verify(storage, &project.urn())
+        .unwrap()
+        .expect("project should still verify");
+    assert_eq!(verified.urn(), project.urn());
+}
+
+#[test]
+fn default_config() {
+    let store = tmp::storage(SecretKey::new());
+    let storage: &Storage = &store;
+    TestProject::create(storage).unwrap();
+
+    let report = maintenance::run(storage, &maintenance::Config::default()).unwrap();
+    assert_eq!(
+        tasks(&report),
+        vec![Task::Gc, Task::CommitGraph, Task::MultiPackIndex]
+    );
+}
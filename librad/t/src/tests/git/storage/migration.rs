@@ -0,0 +1,89 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use librad::{
+    git::storage::{
+        error,
+        migration::{self, Migrated, VERSION},
+        Storage,
+    },
+    paths::Paths,
+    SecretKey,
+};
+
+fn set_version(storage: &Storage, version: i64) {
+    git2::Config::open(&storage.config_path())
+        .unwrap()
+        .set_i64("rad.version", version)
+        .unwrap()
+}
+
+fn log_all_ref_updates(storage: &Storage) -> String {
+    git2::Config::open(&storage.config_path())
+        .unwrap()
+        .get_string("core.logAllRefUpdates")
+        .unwrap()
+}
+
+#[test]
+fn init_is_current() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = Paths::from_root(&tmp).unwrap();
+    let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+
+    assert_eq!(storage.config().unwrap().version().unwrap(), VERSION);
+    assert_eq!(
+        migration::run(&storage).unwrap(),
+        Migrated {
+            from: VERSION,
+            to: VERSION
+        }
+    )
+}
+
+#[test]
+fn open_migrates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = Paths::from_root(&tmp).unwrap();
+    let key = SecretKey::new();
+    {
+        let storage = Storage::open(&paths, key.clone()).unwrap();
+        set_version(&storage, 0);
+        assert_eq!(storage.config().unwrap().version().unwrap(), 0);
+    }
+    let storage = Storage::open(&paths, key).unwrap();
+    assert_eq!(storage.config().unwrap().version().unwrap(), VERSION);
+}
+
+#[test]
+fn open_enables_reflogs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = Paths::from_root(&tmp).unwrap();
+    let key = SecretKey::new();
+    {
+        let storage = Storage::open(&paths, key.clone()).unwrap();
+        assert_eq!(log_all_ref_updates(&storage), "always");
+        set_version(&storage, 1);
+        git2::Config::open(&storage.config_path())
+            .unwrap()
+            .set_bool("core.logAllRefUpdates", true)
+            .unwrap();
+    }
+    let storage = Storage::open(&paths, key).unwrap();
+    assert_eq!(log_all_ref_updates(&storage), "always");
+}
+
+#[test]
+fn open_rejects_newer() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = Paths::from_root(&tmp).unwrap();
+    let key = SecretKey::new();
+    {
+        let storage = Storage::open(&paths, key.clone()).unwrap();
+        set_version(&storage, i64::from(VERSION) + 1);
+    }
+    assert_matches!(
+        Storage::open(&paths, key).map(|_| ()),
+        Err(error::Init::Migration(migration::Error::Unsupported { found })) if found == VERSION + 1
+    )
+}
@@ -0,0 +1,46 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use it_helpers::{fixed::TestProject, tmp};
+use librad::{
+    git::{identities, storage::ReadOnlyStorage as _},
+    git_ext::RefLike,
+    reflike,
+    SecretKey,
+};
+
+#[test]
+fn snapshot_is_stable() {
+    let store = tmp::storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+
+    let snapshot = store.read_only().snapshot(Some(&urn)).unwrap();
+    assert!(snapshot.refs() > 0);
+    assert!(snapshot.has_urn(&urn).unwrap());
+    // Delegates are resolved via `rad/ids/*`
+    assert_eq!(
+        identities::project::verify(&*snapshot, &urn)
+            .unwrap()
+            .map(|project| project.urn()),
+        Some(urn.clone())
+    );
+
+    // Updates after the snapshot was taken are not visible
+    let branch = urn.clone().with_path(reflike!("refs/heads/next"));
+    let id = store
+        .reference_oid(&urn.clone().with_path(reflike!("refs/rad/id")))
+        .unwrap();
+    {
+        let repo = git2::Repository::open(store.path()).unwrap();
+        repo.reference(RefLike::from(&branch).as_str(), *id, false, "test")
+            .unwrap();
+    }
+    assert!(store.has_ref(&branch).unwrap());
+    assert!(!snapshot.has_ref(&branch).unwrap());
+
+    // Only the namespace (and what it refers to) was captured
+    let other = store.read_only().snapshot(Some(&owner.urn())).unwrap();
+    assert!(other.has_urn(&owner.urn()).unwrap());
+    assert!(!other.has_urn(&urn).unwrap());
+}
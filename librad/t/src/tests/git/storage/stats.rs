@@ -0,0 +1,30 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeSet;
+
+use it_helpers::{fixed::TestProject, tmp};
+use librad::{git::storage::stats, SecretKey};
+
+#[test]
+fn project_stats() {
+    let store = tmp::storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+
+    let stats = stats::for_urn(&store, &project.urn()).unwrap();
+    assert_eq!(stats.urn, project.urn());
+    assert!(stats.refs > 0);
+    assert!(stats.objects > 0);
+    assert!(stats.size > 0);
+    assert!(stats.updated.is_some());
+    assert_eq!(stats.tracked, 0);
+    assert!(stats.cobs.is_empty());
+
+    let all = store
+        .stats()
+        .unwrap()
+        .into_iter()
+        .map(|stats| stats.urn)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(all, vec![project.urn(), owner.urn()].into_iter().collect())
+}
@@ -3,15 +3,17 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{collections::BTreeSet, convert::TryFrom as _};
+use std::{collections::BTreeSet, convert::TryFrom as _, fs};
 
-use it_helpers::{fixed::TestProject, tmp};
+use git_ref_format::{lit, name, Namespaced, Qualified};
+use it_helpers::{fixed::TestProject, git::create_commit, tmp};
 use librad::{
     git::{
-        storage::watch::{EventKind, NamespaceEvent},
+        storage::watch::{EventKind, NamespaceEvent, RefEvent},
         Urn,
     },
     git_ext::RefLike,
+    reflike,
     SecretKey,
 };
 use test_helpers::logging;
@@ -43,3 +45,55 @@ fn namespaces() {
 
     assert_eq!(expected, events)
 }
+
+#[test]
+fn refs() {
+    logging::init();
+
+    let store = tmp::storage(SecretKey::new());
+    let urn = Urn::new(git2::Oid::zero().into());
+    let repo = git2::Repository::open(store.path()).unwrap();
+    // Make sure the reflog directory is watched before the first write
+    fs::create_dir_all(
+        store
+            .path()
+            .join("logs/refs/namespaces")
+            .join(urn.encode_id())
+            .join("refs/heads"),
+    )
+    .unwrap();
+
+    let (watcher, events) = store.watch().refs().unwrap();
+
+    let branch = Namespaced::from(lit::refs_namespaces(
+        &urn,
+        Qualified::from(lit::refs_heads(name::MAIN)),
+    ))
+    .into_qualified();
+    let created = create_commit(&repo, branch.clone()).unwrap();
+    let updated = repo.blob(b"updated").unwrap();
+    repo.reference(branch.as_str(), updated, true, "update")
+        .unwrap();
+    repo.find_reference(branch.as_str())
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    let events = events.take(3).collect::<Vec<_>>();
+    drop(watcher);
+
+    let event = |old: Option<git2::Oid>, new: Option<git2::Oid>| RefEvent {
+        urn: urn.clone(),
+        name: reflike!("refs/heads/main"),
+        old: old.map(Into::into),
+        new: new.map(Into::into),
+    };
+    assert_eq!(
+        events,
+        vec![
+            event(None, Some(created)),
+            event(Some(created), Some(updated)),
+            event(Some(updated), None),
+        ]
+    )
+}
@@ -0,0 +1,62 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use futures::{stream, StreamExt as _};
+
+use librad::{
+    net::{
+        peer::{
+            subscription::{self, Event, Filter, Kind},
+            ProtocolEvent,
+        },
+        protocol::{event::upstream::Endpoint, RecvError},
+    },
+    PeerId,
+    SecretKey,
+};
+
+fn endpoint_down() -> ProtocolEvent {
+    ProtocolEvent::Endpoint(Endpoint::Down)
+}
+
+#[test]
+fn default_matches_all() {
+    assert!(Filter::default().matches(&endpoint_down()))
+}
+
+#[test]
+fn kinds() {
+    assert!(Filter::default()
+        .kind(Kind::Endpoint)
+        .matches(&endpoint_down()));
+    assert!(!Filter::default()
+        .kind(Kind::Gossip)
+        .matches(&endpoint_down()));
+}
+
+#[test]
+fn empty_kinds_match_nothing() {
+    assert!(!Filter::default().kinds(None).matches(&endpoint_down()))
+}
+
+#[test]
+fn peers_exclude_unrelated() {
+    let filter = Filter::default().peer(PeerId::from(SecretKey::new()));
+    assert!(!filter.matches(&endpoint_down()))
+}
+
+#[async_test]
+async fn lagged_marker() {
+    let events = stream::iter(vec![
+        Ok(endpoint_down()),
+        Err(RecvError::Lagged(3)),
+        Ok(endpoint_down()),
+        Err(RecvError::Closed),
+        Ok(endpoint_down()),
+    ]);
+    let filtered = subscription::filtered(events, Filter::default().kind(Kind::Gossip))
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(filtered.len(), 1);
+    assert_matches!(filtered[0], Event::Lagged { dropped: 3 });
+}
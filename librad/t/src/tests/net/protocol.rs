@@ -3,5 +3,7 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+mod bandwidth;
 mod broadcast;
+mod drain;
 mod gossip;
@@ -0,0 +1,94 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::time::{Duration, Instant};
+
+use librad::{
+    git::Urn,
+    git_ext,
+    net::protocol::bandwidth::{Bandwidth, Exceeded, Limit, Period, Quota},
+    PeerId,
+    SecretKey,
+};
+
+fn urn() -> Urn {
+    Urn::new(git_ext::Oid::from(git2::Oid::zero()))
+}
+
+#[test]
+fn unlimited_by_default() {
+    let bw = Bandwidth::new(Quota::default());
+    let peer = PeerId::from(SecretKey::new());
+    let now = Instant::now();
+
+    bw.record_at(now, peer, Some(&urn()), u64::MAX);
+    assert!(bw.check_at(now, &peer, Some(&urn())).is_ok())
+}
+
+#[test]
+fn peer_burst() {
+    let bw = Bandwidth::new(Quota {
+        per_peer: Limit {
+            daily: None,
+            burst: Some(100),
+        },
+        ..Quota::default()
+    });
+    let peer = PeerId::from(SecretKey::new());
+    let other = PeerId::from(SecretKey::new());
+    let now = Instant::now();
+
+    bw.record_at(now, peer, None, 99);
+    assert!(bw.check_at(now, &peer, None).is_ok());
+    bw.record_at(now, peer, None, 1);
+    assert!(matches!(
+        bw.check_at(now, &peer, None),
+        Err(Exceeded::Peer(p, Period::Burst)) if p == peer
+    ));
+    assert!(bw.check_at(now, &other, None).is_ok());
+
+    let later = now + bw.quota().burst_window;
+    assert!(bw.check_at(later, &peer, None).is_ok());
+}
+
+#[test]
+fn urn_daily() {
+    let bw = Bandwidth::new(Quota {
+        per_urn: Limit {
+            daily: Some(100),
+            burst: None,
+        },
+        ..Quota::default()
+    });
+    let now = Instant::now();
+
+    bw.record_at(now, PeerId::from(SecretKey::new()), Some(&urn()), 60);
+    bw.record_at(now, PeerId::from(SecretKey::new()), Some(&urn()), 60);
+    assert!(matches!(
+        bw.check_at(now, &PeerId::from(SecretKey::new()), Some(&urn())),
+        Err(Exceeded::Urn(_, Period::Daily))
+    ));
+
+    let tomorrow = now + Duration::from_secs(24 * 60 * 60);
+    assert!(bw
+        .check_at(tomorrow, &PeerId::from(SecretKey::new()), Some(&urn()))
+        .is_ok());
+}
+
+#[test]
+fn stats() {
+    let bw = Bandwidth::new(Quota::default());
+    let peer = PeerId::from(SecretKey::new());
+    let now = Instant::now();
+
+    bw.record_at(now, peer, Some(&urn()), 10);
+    bw.record_at(now, peer, None, 5);
+
+    let later = now + bw.quota().burst_window;
+    let stats = bw.stats_at(later);
+    assert_eq!(stats.global.total, 15);
+    assert_eq!(stats.global.daily, 15);
+    assert_eq!(stats.global.burst, 0);
+    assert_eq!(stats.peers[&peer].total, 15);
+    assert_eq!(stats.urns[&urn()].total, 10);
+}
@@ -0,0 +1,46 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::time::Duration;
+
+use librad::net::protocol::Drain;
+
+#[test]
+fn enter_refused_when_draining() {
+    let drain = Drain::new();
+    let inflight = drain.enter();
+    assert!(inflight.is_some());
+    assert_eq!(drain.inflight(), 1);
+
+    drain.start();
+    assert!(drain.enter().is_none());
+    assert_eq!(drain.inflight(), 1);
+
+    drop(inflight);
+    assert_eq!(drain.inflight(), 0);
+}
+
+#[tokio::test]
+async fn wait_for_inflight() {
+    let drain = Drain::new();
+    let inflight = drain.enter().unwrap();
+    drain.start();
+
+    let task = tokio::spawn({
+        let drain = drain.clone();
+        async move { drain.wait(Duration::from_secs(5)).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    drop(inflight);
+
+    assert!(task.await.unwrap().is_ok())
+}
+
+#[tokio::test]
+async fn wait_times_out() {
+    let drain = Drain::new();
+    let _inflight = drain.enter().unwrap();
+    drain.start();
+
+    assert!(drain.wait(Duration::from_millis(100)).await.is_err())
+}
@@ -5,10 +5,11 @@
 
 use std::{io, sync::Arc};
 
-use rustls::{ClientSession, ServerSession, Session};
+use rustls::{ClientSession, ServerSession, Session, TLSError};
 
 use librad::{
-    net::tls::{make_client_config, make_server_config},
+    identities::delegation::certificate::Certificate as DeviceCertificate,
+    net::tls::{make_client_config, make_server_config, make_server_config_certified},
     PeerId,
     SecretKey,
 };
@@ -27,16 +28,58 @@ fn test_can_handshake() {
     let server_config = Arc::new(make_server_config(server_key).unwrap());
     let mut server_session = ServerSession::new(&server_config);
 
-    do_handshake(&mut client_session, &mut server_session)
+    do_handshake(&mut client_session, &mut server_session).unwrap()
 }
 
-fn do_handshake(client: &mut ClientSession, server: &mut ServerSession) {
+#[test]
+fn test_can_handshake_certified_device() {
+    let client_key = SecretKey::new();
+    let person_key = SecretKey::new();
+    let device_key = SecretKey::new();
+
+    let device = DeviceCertificate::issue(&person_key, device_key.public(), 0, i64::MAX).unwrap();
+    let person_id = PeerId::from(&person_key).to_string();
+
+    let client_config = Arc::new(make_client_config(client_key).unwrap());
+    let sni = webpki::DNSNameRef::try_from_ascii_str(&person_id).unwrap();
+    let mut client_session = ClientSession::new(&client_config, sni);
+
+    let server_config = Arc::new(make_server_config_certified(device_key, Some(device)).unwrap());
+    let mut server_session = ServerSession::new(&server_config);
+
+    do_handshake(&mut client_session, &mut server_session).unwrap()
+}
+
+#[test]
+fn test_rejects_certificate_for_other_device() {
+    let client_key = SecretKey::new();
+    let person_key = SecretKey::new();
+    let other_key = SecretKey::new();
+    let device_key = SecretKey::new();
+
+    // Issued by the expected peer, but not for the key the server presents
+    let device = DeviceCertificate::issue(&person_key, other_key.public(), 0, i64::MAX).unwrap();
+    let person_id = PeerId::from(&person_key).to_string();
+
+    let client_config = Arc::new(make_client_config(client_key).unwrap());
+    let sni = webpki::DNSNameRef::try_from_ascii_str(&person_id).unwrap();
+    let mut client_session = ClientSession::new(&client_config, sni);
+
+    let server_config = Arc::new(make_server_config_certified(device_key, Some(device)).unwrap());
+    let mut server_session = ServerSession::new(&server_config);
+
+    assert!(do_handshake(&mut client_session, &mut server_session).is_err())
+}
+
+fn do_handshake(client: &mut ClientSession, server: &mut ServerSession) -> Result<(), TLSError> {
     while server.is_handshaking() || client.is_handshaking() {
         transfer(client, server);
-        server.process_new_packets().unwrap();
+        server.process_new_packets()?;
         transfer(server, client);
-        client.process_new_packets().unwrap();
+        client.process_new_packets()?;
     }
+
+    Ok(())
 }
 
 fn transfer(left: &mut dyn Session, right: &mut dyn Session) {
@@ -5,7 +5,12 @@
 
 use pretty_assertions::assert_eq;
 
-use librad::{net::x509::Certificate, PeerId, SecretKey};
+use librad::{
+    identities::delegation::certificate::Certificate as DeviceCertificate,
+    net::x509::{Certificate, FromDerError},
+    PeerId,
+    SecretKey,
+};
 
 lazy_static! {
     static ref KEY: SecretKey = SecretKey::from_seed([
@@ -29,3 +34,24 @@ fn get_peer_id() {
     assert_eq!(cert.peer_id_ref(), cert2.peer_id_ref());
     assert_eq!(&peer_id, cert.peer_id_ref());
 }
+
+#[test]
+fn device_certificate() {
+    let person = SecretKey::new();
+    let device = DeviceCertificate::issue(&person, KEY.public(), 0, i64::MAX).unwrap();
+    let cert = Certificate::generate_certified(&*KEY, Some(device.clone())).unwrap();
+    let cert2 = Certificate::from_der(&cert.to_der()).unwrap();
+
+    assert_eq!(cert, cert2);
+    assert_eq!(cert2.device_certificate(), Some(&device));
+    assert_eq!(cert2.peer_id(), PeerId::from(&*KEY));
+    assert_eq!(cert2.acting_for(), PeerId::from(person.public()));
+
+    // Certificates for other keys are rejected
+    let other = DeviceCertificate::issue(&person, person.public(), 0, i64::MAX).unwrap();
+    let cert = Certificate::generate_certified(&*KEY, Some(other)).unwrap();
+    assert_matches!(
+        Certificate::from_der(&cert.to_der()),
+        Err(FromDerError::InvalidDeviceCertificate)
+    );
+}
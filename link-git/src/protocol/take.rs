@@ -4,12 +4,40 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 use std::{
+    error,
     io,
     pin::Pin,
     task::{Context, Poll},
 };
 
 use futures_lite::io::{AsyncBufRead, AsyncRead};
+use thiserror::Error;
+
+/// The error returned by [`TryTake`] when the limit is exceeded, wrapped in an
+/// [`io::Error`].
+#[derive(Debug, Error)]
+#[error("max input size exceeded")]
+pub struct LimitExceeded;
+
+impl LimitExceeded {
+    /// Whether `e`, or any of its sources, is a [`LimitExceeded`] error.
+    pub fn is_cause_of(e: &(dyn error::Error + 'static)) -> bool {
+        let mut cur = Some(e);
+        while let Some(e) = cur {
+            if e.is::<Self>() {
+                return true;
+            }
+            // `io::Error::source` skips over the wrapped error itself
+            cur = match e.downcast_ref::<io::Error>() {
+                Some(io) => io
+                    .get_ref()
+                    .map(|inner| inner as &(dyn error::Error + 'static)),
+                None => e.source(),
+            };
+        }
+        false
+    }
+}
 
 /// Like [`futures_lite::io::Take`], but returns an error if and when the
 /// `limit` is exceeded.
@@ -38,10 +66,7 @@ where
         buf: &mut [u8],
     ) -> Poll<Result<usize, io::Error>> {
         if self.limit == 0 {
-            return Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::Other,
-                "max input size exceeded",
-            )));
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, LimitExceeded)));
         }
 
         let this = self.get_mut();
@@ -61,10 +86,7 @@ where
 {
     fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<&[u8], io::Error>> {
         if self.limit == 0 {
-            return Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::Other,
-                "max input size exceeded",
-            )));
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, LimitExceeded)));
         }
 
         Pin::new(&mut self.get_mut().inner).poll_fill_buf(cx)
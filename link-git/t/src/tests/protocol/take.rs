@@ -4,7 +4,7 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 use futures::{executor::block_on, io::Cursor, AsyncReadExt as _};
-use link_git::protocol::take::TryTake;
+use link_git::protocol::take::{LimitExceeded, TryTake};
 use std::io;
 
 #[test]
@@ -28,7 +28,8 @@ fn when_limit_exceeded() {
     let output =
         block_on(TryTake::new(Cursor::new(input), 10).read_to_end(&mut Vec::new())).unwrap_err();
 
-    assert_eq!(output.to_string(), "max input size exceeded")
+    assert_eq!(output.to_string(), "max input size exceeded");
+    assert!(LimitExceeded::is_cause_of(&output))
 }
 
 #[test]
@@ -10,6 +10,7 @@ use either::Either::{self, *};
 
 use crate::{generic, sealed};
 
+pub mod certificate;
 pub mod direct;
 pub mod indirect;
 
@@ -30,6 +31,16 @@ pub trait Delegations: sealed::Sealed {
     /// Nb.: "threshold" means that there must be `quorum_threshold() + 1` votes
     /// to form a quorum.
     fn quorum_threshold(&self) -> usize;
+
+    /// Given the set of keys which signed a revision, return the keys they
+    /// vote as, which are then passed to [`Delegations::eligible`].
+    ///
+    /// By default, every key votes as itself. See [`certificate::Certified`]
+    /// for an implementation which lets certified device keys vote as the key
+    /// which certified them.
+    fn votes<'a>(&'a self, signers: BTreeSet<&'a PublicKey>) -> BTreeSet<&'a PublicKey> {
+        signers
+    }
 }
 
 //// Forwarding impls for `Doc` and `Identity`
@@ -47,6 +58,10 @@ where
     fn quorum_threshold(&self) -> usize {
         self.delegations.quorum_threshold()
     }
+
+    fn votes<'a>(&'a self, signers: BTreeSet<&'a PublicKey>) -> BTreeSet<&'a PublicKey> {
+        self.delegations.votes(signers)
+    }
 }
 
 impl<T, R, C> Delegations for generic::Identity<T, R, C>
@@ -62,6 +77,10 @@ where
     fn quorum_threshold(&self) -> usize {
         self.doc.quorum_threshold()
     }
+
+    fn votes<'a>(&'a self, signers: BTreeSet<&'a PublicKey>) -> BTreeSet<&'a PublicKey> {
+        self.doc.votes(signers)
+    }
 }
 
 /// "Existentialised" delegations.
@@ -0,0 +1,289 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Time-limited certificates for device keys.
+//!
+//! A [`Certificate`] is a statement by a key of a person (the `issuer`) that
+//! another key (the `device`) may act on its behalf for a limited period of
+//! time. This allows each device to have its own key (and thus `PeerId`),
+//! without having to add it to the person's delegations, which would require a
+//! new identity revision signed by a quorum of the existing delegations.
+//!
+//! A signature made by a certified device key counts as a signature of the
+//! issuer, provided the certificate is valid at the time of verification,
+//! according to the clock of the verifier. The time a signature was made can
+//! not be used instead: it is only known from unauthenticated data, such as
+//! the commit time, which the holder of the device key is free to backdate
+//! into the validity period of an expired certificate. Consequently, once a
+//! certificate expires, the signatures made under it no longer count. A
+//! revision of an identity which relies on the vote of a device must thus be
+//! confirmed by a delegate before the certificate expires, or it will stop
+//! verifying.
+//!
+//! When verifying identity histories, [`Certified`] achieves this by mapping
+//! the votes of device keys to the keys which certified them.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter::FromIterator,
+};
+
+use canonical::{Cjson, CjsonError};
+use crypto::{PublicKey, Signer};
+use futures_lite::future::block_on;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::Delegations;
+use crate::{generic::Replaces, sealed};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("a certificate must expire after it becomes valid")]
+    Validity,
+
+    #[error("failed to sign certificate")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error(transparent)]
+    Cjson(#[from] CjsonError),
+}
+
+/// A certificate allowing `device` to act on behalf of `issuer` between
+/// `not_before` (inclusive) and `not_after` (exclusive), both in seconds since
+/// the Unix epoch.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    pub issuer: PublicKey,
+    pub device: PublicKey,
+    pub not_before: i64,
+    pub not_after: i64,
+    /// The signature of `issuer` over the canonical JSON form of the other
+    /// fields.
+    pub signature: crypto::Signature,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Unsigned<'a> {
+    issuer: &'a PublicKey,
+    device: &'a PublicKey,
+    not_before: i64,
+    not_after: i64,
+}
+
+impl Certificate {
+    /// Issue a [`Certificate`] for `device`, signed by `signer`.
+    pub fn issue<S>(
+        signer: &S,
+        device: PublicKey,
+        not_before: i64,
+        not_after: i64,
+    ) -> Result<Self, Error>
+    where
+        S: Signer,
+        S::Error: std::error::Error + Send + Sync + 'static,
+    {
+        if not_after <= not_before {
+            return Err(Error::Validity);
+        }
+        let issuer = PublicKey::from(signer.public_key());
+        let payload = Cjson(Unsigned {
+            issuer: &issuer,
+            device: &device,
+            not_before,
+            not_after,
+        })
+        .canonical_form()?;
+        let signature = block_on(signer.sign(&payload)).map_err(|e| Error::Sign(Box::new(e)))?;
+
+        Ok(Self {
+            issuer,
+            device,
+            not_before,
+            not_after,
+            signature: signature.into(),
+        })
+    }
+
+    /// `true` if the signature of the `issuer` is valid.
+    pub fn verify_signature(&self) -> bool {
+        Cjson(Unsigned {
+            issuer: &self.issuer,
+            device: &self.device,
+            not_before: self.not_before,
+            not_after: self.not_after,
+        })
+        .canonical_form()
+        .map_or(false, |payload| {
+            self.signature.verify(&payload, &self.issuer)
+        })
+    }
+
+    /// `true` if `timestamp` lies within the validity period.
+    ///
+    /// Note that this does not check the signature, see
+    /// [`Self::verify_signature`].
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        self.not_before <= timestamp && timestamp < self.not_after
+    }
+}
+
+/// A set of [`Certificate`]s with valid signatures, indexed by device key.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Certificates {
+    by_device: BTreeMap<PublicKey, BTreeSet<Cert>>,
+}
+
+// `Certificate` doesn't implement `Ord`, as `crypto::Signature` doesn't.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Cert {
+    issuer: PublicKey,
+    not_before: i64,
+    not_after: i64,
+}
+
+impl Certificates {
+    /// Add `cert` to the set.
+    ///
+    /// Returns `false` if the signature of `cert` is not valid, in which case
+    /// it is not added.
+    pub fn insert(&mut self, cert: Certificate) -> bool {
+        if !cert.verify_signature() {
+            return false;
+        }
+        self.by_device.entry(cert.device).or_default().insert(Cert {
+            issuer: cert.issuer,
+            not_before: cert.not_before,
+            not_after: cert.not_after,
+        });
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_device.is_empty()
+    }
+
+    /// The keys which certified `device` at `timestamp`, in ascending order.
+    pub fn issuers<'a>(
+        &'a self,
+        device: &PublicKey,
+        timestamp: i64,
+    ) -> impl Iterator<Item = &'a PublicKey> + 'a {
+        self.by_device
+            .get(device)
+            .into_iter()
+            .flatten()
+            .filter(move |cert| cert.not_before <= timestamp && timestamp < cert.not_after)
+            .map(|cert| &cert.issuer)
+    }
+
+    /// The devices certified by `issuer` at `timestamp`, in ascending order.
+    pub fn devices<'a>(
+        &'a self,
+        issuer: &'a PublicKey,
+        timestamp: i64,
+    ) -> impl Iterator<Item = &'a PublicKey> + 'a {
+        self.by_device
+            .iter()
+            .filter(move |(_, certs)| {
+                certs.iter().any(|cert| {
+                    &cert.issuer == issuer
+                        && cert.not_before <= timestamp
+                        && timestamp < cert.not_after
+                })
+            })
+            .map(|(device, _)| device)
+    }
+}
+
+impl FromIterator<Certificate> for Certificates {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Certificate>,
+    {
+        let mut certs = Self::default();
+        for cert in iter {
+            certs.insert(cert);
+        }
+        certs
+    }
+}
+
+/// [`Delegations`] of type `D`, for which signatures made by device keys
+/// certified at time `at` vote as the issuer of the [`Certificate`].
+///
+/// `at` is expected to be the current time, see the [module
+/// documentation](self).
+///
+/// A signer which is itself eligible under `D` always votes as itself. If a
+/// device was certified by more than one issuer, it only votes as the first of
+/// them which is eligible under `D`.
+#[derive(Clone, Debug)]
+pub struct Certified<'a, D> {
+    inner: D,
+    certs: &'a Certificates,
+    at: i64,
+}
+
+impl<'a, D> Certified<'a, D> {
+    pub fn new(inner: D, certs: &'a Certificates, at: i64) -> Self {
+        Self { inner, certs, at }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<'a, D> Delegations for Certified<'a, D>
+where
+    D: Delegations,
+{
+    type Error = D::Error;
+
+    fn eligible(&self, votes: BTreeSet<&PublicKey>) -> Result<BTreeSet<&PublicKey>, Self::Error> {
+        self.inner.eligible(votes)
+    }
+
+    fn quorum_threshold(&self) -> usize {
+        self.inner.quorum_threshold()
+    }
+
+    fn votes<'b>(&'b self, signers: BTreeSet<&'b PublicKey>) -> BTreeSet<&'b PublicKey> {
+        let is_eligible = |key: &PublicKey| {
+            self.inner
+                .eligible(Some(key).into_iter().collect())
+                .map_or(false, |eligible| !eligible.is_empty())
+        };
+        self.inner
+            .votes(signers)
+            .into_iter()
+            .map(|key| {
+                if is_eligible(key) {
+                    key
+                } else {
+                    self.certs
+                        .issuers(key, self.at)
+                        .find(|issuer| is_eligible(issuer))
+                        .unwrap_or(key)
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a, D> Replaces for Certified<'a, D>
+where
+    D: Replaces,
+{
+    type Revision = D::Revision;
+
+    fn replaces(&self) -> Option<&Self::Revision> {
+        self.inner.replaces()
+    }
+}
+
+impl<'a, D> sealed::Sealed for Certified<'a, D> {}
@@ -3,7 +3,10 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::collections::{btree_set, BTreeSet};
+use std::{
+    collections::{btree_set, BTreeSet},
+    num::NonZeroUsize,
+};
 
 use thiserror::Error;
 
@@ -18,8 +21,15 @@ use crate::{payload, sealed};
 ///
 /// Untrusted input must be deserialised via [`payload::PersonDelegations`],
 /// which ensures that duplicates in the source document translate to an error.
+///
+/// By default, a majority of the keys is required to form a quorum. This can be
+/// overridden by a [`payload::PersonQuorum`] in the payload of the document,
+/// which is applied when loading it.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Direct(NonEmptyOrderedSet<PublicKey>);
+pub struct Direct {
+    keys: NonEmptyOrderedSet<PublicKey>,
+    threshold: Option<NonZeroUsize>,
+}
 
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -30,28 +40,43 @@ pub enum Error {
 
 impl Direct {
     pub fn new(key: PublicKey) -> Self {
-        Self(NonEmptyOrderedSet::new(key))
+        Self::from(NonEmptyOrderedSet::new(key))
     }
 
     pub fn insert(mut self, key: PublicKey) -> Self {
-        self.0.insert(key);
+        self.keys.insert(key);
         self
     }
 
     pub fn try_from_iter(keys: impl Iterator<Item = PublicKey>) -> Result<Self, Error> {
         let keys = keys.collect::<BTreeSet<_>>();
         match NonEmptyOrderedSet::from_maybe_empty(keys) {
-            Some(keys) => Ok(Self(keys)),
+            Some(keys) => Ok(Self::from(keys)),
             None => Err(Error::EmptyKeys),
         }
     }
 
+    /// Require `threshold` signatures to form a quorum, instead of a majority
+    /// of the keys.
+    ///
+    /// A `threshold` greater than the number of keys requires all keys to
+    /// sign.
+    pub fn with_threshold(mut self, threshold: Option<NonZeroUsize>) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The number of signatures required to form a quorum, if overridden.
+    pub fn threshold(&self) -> Option<NonZeroUsize> {
+        self.threshold
+    }
+
     pub fn contains(&self, key: &PublicKey) -> bool {
-        self.0.contains(key)
+        self.keys.contains(key)
     }
 
     pub fn eligible(&self, votes: BTreeSet<&PublicKey>) -> BTreeSet<&PublicKey> {
-        self.0.iter().filter(|pk| votes.contains(pk)).collect()
+        self.keys.iter().filter(|pk| votes.contains(pk)).collect()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &PublicKey> {
@@ -67,7 +92,10 @@ impl Delegations for Direct {
     }
 
     fn quorum_threshold(&self) -> usize {
-        self.0.len() / 2
+        match self.threshold {
+            Some(threshold) => threshold.get().min(self.keys.len()) - 1,
+            None => self.keys.len() / 2,
+        }
     }
 }
 
@@ -75,26 +103,28 @@ impl sealed::Sealed for Direct {}
 
 impl From<payload::PersonDelegations> for Direct {
     fn from(payload: payload::PersonDelegations) -> Self {
-        Self(payload.into())
+        Self::from(NonEmptyOrderedSet::from(payload))
     }
 }
 
 impl From<Direct> for BTreeSet<PublicKey> {
     fn from(here: Direct) -> Self {
-        here.0.into_inner()
+        here.keys.into_inner()
     }
 }
 
 impl From<Direct> for NonEmptyOrderedSet<PublicKey> {
     fn from(here: Direct) -> Self {
-        here.0
+        here.keys
     }
 }
 
-#[cfg(test)]
 impl From<NonEmptyOrderedSet<PublicKey>> for Direct {
-    fn from(set: NonEmptyOrderedSet<PublicKey>) -> Self {
-        Self(set)
+    fn from(keys: NonEmptyOrderedSet<PublicKey>) -> Self {
+        Self {
+            keys,
+            threshold: None,
+        }
     }
 }
 
@@ -103,7 +133,7 @@ impl<'a> IntoIterator for &'a Direct {
     type IntoIter = btree_set::Iter<'a, PublicKey>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        self.keys.iter()
     }
 }
 
@@ -112,7 +142,7 @@ impl IntoIterator for Direct {
     type IntoIter = btree_set::IntoIter<PublicKey>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.keys.into_iter()
     }
 }
 
@@ -121,6 +151,6 @@ impl Extend<PublicKey> for Direct {
     where
         I: IntoIterator<Item = PublicKey>,
     {
-        self.0.extend(iter)
+        self.keys.extend(iter)
     }
 }
@@ -291,6 +291,19 @@ impl<T, S> Verifying<T, S> {
     }
 }
 
+impl<T> Verifying<T, Verified> {
+    /// Mark `t` as [`Verified`], without actually verifying it.
+    ///
+    /// Only to be used for identities which are known to have passed
+    /// verification before, eg. because the result was cached.
+    pub(crate) fn assume_verified(t: T) -> Self {
+        Verifying {
+            inner: t,
+            state: PhantomData,
+        }
+    }
+}
+
 impl<T> From<T> for Verifying<T, Untrusted> {
     fn from(t: T) -> Self {
         Self::from_untrusted(t)
@@ -374,7 +387,7 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Signed> {
     {
         let eligible = self
             .doc
-            .eligible(self.signatures.keys().collect())
+            .eligible(self.doc.votes(self.signatures.keys().collect()))
             .map_err(error::Verify::eligibility)?
             .len();
 
@@ -386,6 +399,45 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Signed> {
     }
 }
 
+impl<T, R, C> Verifying<Identity<T, R, C>, Signed> {
+    /// Attempt to transition the [`Signed`] initial revision of an [`Identity`]
+    /// to the [`Quorum`] state.
+    ///
+    /// Unlike [`Self::quorum`], a single eligible signature is sufficient, so
+    /// that an identity with multiple delegations can be initialised by one of
+    /// them. The other delegations attest to the identity by signing it
+    /// subsequently, at which point the regular quorum rules apply.
+    ///
+    /// # Errors
+    ///
+    /// If `self` is not the initial revision, or none of the signatures is
+    /// eligible.
+    pub fn genesis(self) -> Result<Verifying<Identity<T, R, C>, Quorum>, error::Verify<R, C>>
+    where
+        T: Delegations + Replaces<Revision = R>,
+        T::Error: std::error::Error + Send + Sync + 'static,
+
+        R: Clone + Debug + Display,
+        C: Debug + Display,
+    {
+        if let Some(replaces) = self.doc.replaces() {
+            return Err(error::Verify::MissingParent(replaces.clone()));
+        }
+
+        let eligible = self
+            .doc
+            .eligible(self.doc.votes(self.signatures.keys().collect()))
+            .map_err(error::Verify::eligibility)?
+            .len();
+
+        if eligible > 0 {
+            Ok(self.coerce())
+        } else {
+            Err(error::Verify::Quorum)
+        }
+    }
+}
+
 impl<T, R, C> Verifying<Identity<T, R, C>, Quorum> {
     /// Attempt to transition a [`Quorum`] [`Identity`] to the [`Verified`]
     /// state.
@@ -440,7 +492,7 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Quorum> {
                 } else {
                     let votes = parent
                         .doc
-                        .eligible(self.signatures.keys().collect())
+                        .eligible(self.doc.votes(self.signatures.keys().collect()))
                         .map_err(error::Verify::eligibility)?
                         .len();
 
@@ -3,7 +3,13 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{convert::TryFrom, fmt::Debug, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use canonical::Cjson;
 use crypto::{PublicKey, Signer};
@@ -13,13 +19,18 @@ use git_ext as ext;
 use multihash::Multihash;
 
 use crate::{
-    delegation::{self, Delegations},
-    generic::{self, Signed, Verified},
+    delegation::{
+        self,
+        certificate::{Certificates, Certified},
+        Delegations,
+    },
+    generic::{self, Signed, Untrusted, Verified},
     payload::{self, PersonPayload, ProjectPayload, SomePayload},
     sign::{Signature, Signatures},
     urn,
 };
 
+pub mod cache;
 pub mod error;
 pub mod iter;
 
@@ -92,6 +103,88 @@ impl SomeIdentity {
     }
 }
 
+impl Person {
+    /// The keys revoked by this person, see [`payload::PersonRevocations`].
+    ///
+    /// The revocations are validated when the [`Person`] is loaded, so a
+    /// malformed extension is treated as absent here. Only keys of the person
+    /// itself can be revoked.
+    pub fn revocations(&self) -> payload::PersonRevocations {
+        self.payload().revocations().unwrap_or_default()
+    }
+
+    /// The extensions of this person which do not conform to their registered
+    /// schema, see [`payload::schema`].
+    pub fn invalid_exts(&self) -> Vec<payload::schema::Invalid> {
+        payload::schema::check(self.payload())
+    }
+}
+
+impl Project {
+    /// The lifecycle status of this project, see [`payload::ProjectStatus`].
+    ///
+    /// An unknown or malformed status is treated as absent, ie. the project
+    /// is considered [`payload::ProjectStatus::Active`].
+    pub fn status(&self) -> payload::ProjectStatus<Revision> {
+        self.payload().status().unwrap_or_default()
+    }
+
+    /// The keys revoked by the indirect delegations of this project, see
+    /// [`Person::revocations`].
+    ///
+    /// A key revoked by one delegate, but which is still delegated to by the
+    /// project or another delegate, is not considered revoked.
+    pub fn revocations(&self) -> payload::PersonRevocations {
+        let mut delegated = BTreeSet::new();
+        let mut revocations = payload::PersonRevocations::default();
+        for delegation in self.delegations().iter() {
+            match delegation {
+                Either::Left(key) => {
+                    delegated.insert(*key);
+                },
+                Either::Right(person) => {
+                    delegated.extend(person.delegations().iter().copied());
+                    revocations.merge(person.revocations());
+                },
+            }
+        }
+        revocations
+            .revoked
+            .retain(|key, _| !delegated.contains(key));
+        revocations
+    }
+
+    /// The extensions of this project which do not conform to their registered
+    /// schema, see [`payload::schema`].
+    pub fn invalid_exts(&self) -> Vec<payload::schema::Invalid> {
+        payload::schema::check(self.payload())
+    }
+}
+
+impl payload::PersonRevocations {
+    /// Whether `commit`, signed by `key`, is to be rejected.
+    ///
+    /// This is the case if `key` was revoked, and `commit` is neither one of
+    /// the commits which remain valid, nor an ancestor of one of them.
+    pub fn rejects(
+        &self,
+        repo: &git2::Repository,
+        key: &PublicKey,
+        commit: git2::Oid,
+    ) -> Result<bool, git2::Error> {
+        let valid = match self.valid(key) {
+            None => return Ok(false),
+            Some(valid) => valid,
+        };
+        for oid in valid {
+            if ext::is_ancestor(repo, commit, **oid)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
 pub type SignedPerson = SignedIdentity<PersonDoc>;
 pub type SignedProject = SignedIdentity<ProjectDoc>;
 
@@ -102,9 +195,41 @@ pub type VerificationError = generic::error::Verify<Revision, ContentId>;
 
 pub type IndirectDelegation = delegation::Indirect<PersonPayload, Revision, ContentId>;
 
+/// The point at which an identity history ceases to be linear, see
+/// [`Identities::fork_point`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fork {
+    /// The revision replaced by both `left` and `right`, or `None` if both are
+    /// initial revisions.
+    pub base: Option<Revision>,
+    /// The commit introducing the first of the diverging revisions.
+    pub left: ContentId,
+    /// The commit introducing the second of the diverging revisions.
+    pub right: ContentId,
+}
+
+impl Display for Fork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.base {
+            Some(base) => write!(
+                f,
+                "{} and {} both replace revision {}",
+                self.left, self.right, base
+            ),
+            None => write!(
+                f,
+                "{} and {} are both initial revisions",
+                self.left, self.right
+            ),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Identities<'a, T> {
     repo: &'a git2::Repository,
+    cache: Option<&'a dyn cache::Cache>,
+    certificates: Option<&'a Certificates>,
     _marker: PhantomData<T>,
 }
 
@@ -112,6 +237,8 @@ impl<'a, T: 'a> From<&'a git2::Repository> for Identities<'a, T> {
     fn from(repo: &'a git2::Repository) -> Self {
         Self {
             repo,
+            cache: None,
+            certificates: None,
             _marker: PhantomData,
         }
     }
@@ -119,11 +246,39 @@ impl<'a, T: 'a> From<&'a git2::Repository> for Identities<'a, T> {
 
 impl<'a, T: 'a> From<&Identities<'a, T>> for Identities<'a, T> {
     fn from(other: &Identities<'a, T>) -> Self {
-        Identities::from(other.repo)
+        Self {
+            repo: other.repo,
+            cache: other.cache,
+            certificates: other.certificates,
+            _marker: PhantomData,
+        }
     }
 }
 
 impl<'a, T: 'a> Identities<'a, T> {
+    /// Use `cache` to avoid re-verifying histories which have been verified
+    /// before, see [`cache`].
+    pub fn with_cache(self, cache: &'a dyn cache::Cache) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// Accept signatures made by device keys certified in `certificates` as
+    /// signatures of the issuing keys, see [`delegation::certificate`].
+    ///
+    /// A certificate must be valid at the time of verification, regardless of
+    /// when the revision was signed. Note that the verification [`cache`] is
+    /// bypassed if `certificates` is not empty, as its entries do not take
+    /// certificates into account.
+    pub fn with_certificates(self, certificates: &'a Certificates) -> Self {
+        Self {
+            certificates: Some(certificates),
+            ..self
+        }
+    }
+
     /// Convenience to specialise `T` to [`Person`].
     pub fn as_person(&self) -> Identities<'_, Person> {
         self.coerce()
@@ -147,6 +302,8 @@ impl<'a, T: 'a> Identities<'a, T> {
     pub fn coerce<U>(&self) -> Identities<'_, U> {
         Identities {
             repo: self.repo,
+            cache: self.cache,
+            certificates: self.certificates,
             _marker: PhantomData,
         }
     }
@@ -208,19 +365,87 @@ impl<'a, T: 'a> Identities<'a, T> {
 
         Identity<Doc>: TryFrom<ByOid<'a>, Error = error::Load>,
     {
-        let mut progeny = Iter::<'_, Identity<Doc>>::new(self.repo, head)
+        let cache = match self.cache {
+            Some(cache) if self.certificates.map_or(true, Certificates::is_empty) => cache,
+            _ => return self.fold_verify_uncached(head),
+        };
+
+        match cache.get(head) {
+            Ok(Some(entry)) => match self.cached_fold(&entry) {
+                Ok(folded) => return Ok(folded),
+                Err(e) => {
+                    tracing::warn!(head = %head, err = %e, "failed to load cached verification")
+                },
+            },
+            Ok(None) => {},
+            Err(e) => tracing::warn!(head = %head, err = %e, "failed to read verification cache"),
+        }
+
+        let folded = self.fold_verify_uncached(head)?;
+        let entry = cache::Entry {
+            head: folded.head.content_id,
+            parent: folded.parent.as_ref().map(|parent| parent.content_id),
+        };
+        if let Err(e) = cache.put(head, &entry) {
+            tracing::warn!(head = %head, err = %e, "failed to write verification cache");
+        }
+
+        Ok(folded)
+    }
+
+    fn cached_fold<Doc>(
+        &self,
+        entry: &cache::Entry,
+    ) -> Result<generic::Folded<Doc, Revision, ContentId>, error::Load>
+    where
+        Identity<Doc>: TryFrom<ByOid<'a>, Error = error::Load>,
+    {
+        let load = |oid: ContentId| {
+            Identity::<Doc>::try_from(self.by_oid(oid.into())).map(Verifying::assume_verified)
+        };
+        Ok(generic::Folded {
+            head: load(entry.head)?,
+            parent: entry.parent.map(load).transpose()?,
+        })
+    }
+
+    fn fold_verify_uncached<Doc>(
+        &self,
+        head: git2::Oid,
+    ) -> Result<generic::Folded<Doc, Revision, ContentId>, VerificationError>
+    where
+        Doc: Delegations + generic::Replaces<Revision = Revision>,
+        <Doc as Delegations>::Error: std::error::Error + Send + Sync + 'static,
+
+        Identity<Doc>: TryFrom<ByOid<'a>, Error = error::Load>,
+    {
+        let progeny = Iter::<'_, Identity<Doc>>::new(self.repo, head)
             .map_err(generic::error::Verify::history)?;
 
-        // TODO(kim): should we skip non-quorum commits at the beginning?
-        let root = progeny
-            .next()
-            .ok_or(generic::error::Verify::EmptyHistory)?
-            .map_err(generic::error::Verify::history)?
-            .signed()?
-            .quorum()?
-            .verified(None)?;
+        let certs = match self.certificates {
+            Some(certs) if !certs.is_empty() => certs,
+            _ => return fold_verify(progeny),
+        };
 
-        root.verify(progeny)
+        // Not the commit time, which is not authenticated
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let progeny = progeny.map(|item| {
+            let identity = item?.into_inner();
+            Ok::<_, error::Load>(Verifying::from(
+                identity.map(|doc| Certified::new(doc, certs, now)),
+            ))
+        });
+        let uncertify = |verified: VerifiedIdentity<Certified<Doc>>| {
+            Verifying::assume_verified(verified.into_inner().map(Certified::into_inner))
+        };
+        let folded = fold_verify(progeny)?;
+        Ok(generic::Folded {
+            head: uncertify(folded.head),
+            parent: folded.parent.map(uncertify),
+        })
     }
 
     //// Helpers ////
@@ -258,6 +483,93 @@ where
     T::Error: std::error::Error + 'static,
     Identity<T>: TryFrom<ByOid<'a>, Error = error::Load>,
 {
+    /// Find the point at which the history with head commit `head` forks.
+    ///
+    /// The history is linear if every revision reachable from `head` is
+    /// replaced by at most one other revision, ie. the revisions form a chain.
+    /// Merge commits are permitted as long as they don't join diverging
+    /// revisions: collecting signatures for the same revision, or approving
+    /// the direct successor of a revision (see [`Self::update_from`]), keeps
+    /// the history linear.
+    ///
+    /// If the history is linear, `None` is returned.
+    pub fn fork_point(&self, head: git2::Oid) -> Result<Option<Fork>, error::Load> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        revwalk.push(head)?;
+
+        let mut successors: BTreeMap<Option<Revision>, (Revision, ContentId)> = BTreeMap::new();
+        for oid in revwalk {
+            let id = Identity::<T>::try_from(self.by_oid(oid?))?;
+            let base = id.doc.replaces().copied();
+            match successors.get(&base) {
+                Some((revision, _)) if *revision == id.revision => continue,
+                Some((_, left)) => {
+                    return Ok(Some(Fork {
+                        base,
+                        left: *left,
+                        right: id.content_id,
+                    }))
+                },
+                None => {
+                    successors.insert(base, (id.revision, id.content_id));
+                },
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Replay the history of `head` as a linear sequence of commits.
+    ///
+    /// The revisions leading up to `head` -- the revision it replaces, the
+    /// revision that one replaces, and so on -- are committed in order, each
+    /// carrying the signatures it was approved with. Revisions which are not
+    /// ancestors of `head`, such as competing or unapproved proposals, are
+    /// dropped.
+    ///
+    /// Since signatures are made over revisions rather than commits, the
+    /// result verifies to the same revision as `head`.
+    pub fn linearise(&self, head: VerifiedIdentity<T>) -> Result<Identity<T>, error::Store>
+    where
+        T::Error: Send + Sync,
+    {
+        // The latest well-signed commit of each revision on the first-parent
+        // path, which is what verification considers
+        let mut by_revision = BTreeMap::new();
+        for id in Iter::<'_, Identity<T>>::new(self.repo, *head.content_id)? {
+            if let Ok(id) = id?.signed() {
+                let id = id.into_inner();
+                by_revision.insert(id.revision, id);
+            }
+        }
+        let head = head.into_inner();
+        let mut chain = Vec::new();
+        let mut next = head.doc.replaces().copied();
+        chain.push(head);
+        while let Some(revision) = next.take() {
+            let id = by_revision
+                .remove(&revision)
+                .ok_or(error::Load::MissingDoc)?;
+            next = id.doc.replaces().copied();
+            chain.push(id);
+        }
+
+        let mut tip: Option<Identity<T>> = None;
+        for id in chain.into_iter().rev() {
+            let parents = tip.iter().collect::<Vec<_>>();
+            let content_id = self.commit(
+                &format!("Linearised revision {} from {}", id.revision, id.content_id),
+                &id.signatures,
+                id.revision,
+                &parents,
+            )?;
+            tip = Some(Identity { content_id, ..id });
+        }
+
+        Ok(tip.expect("chain contains at least `head`"))
+    }
+
     /// Sign and commit some identity.
     pub fn create_from<S>(
         &self,
@@ -470,6 +782,18 @@ impl<'a> Identities<'a, Person> {
         Ok(self.verify_generic(head)?)
     }
 
+    /// Verify the person history with head commit `head`, requiring it to be
+    /// linear.
+    ///
+    /// Like [`Self::verify`], but fails with the [`Fork`] if the history is not
+    /// linear, see [`Identities::fork_point`].
+    pub fn verify_linear(&self, head: git2::Oid) -> Result<VerifiedPerson, error::VerifyPerson> {
+        match self.fork_point(head)? {
+            Some(fork) => Err(error::VerifyPerson::NonLinear(fork)),
+            None => self.verify(head),
+        }
+    }
+
     /// Create a new [`Person`] from a payload and delegations.
     ///
     /// The returned [`Person`] (and the underlying commit) will not have any
@@ -503,7 +827,7 @@ impl<'a> Identities<'a, Person> {
             content_id,
             root,
             revision,
-            doc: doc.second(delegation::Direct::from),
+            doc: load::person_doc(doc)?,
             signatures,
         })
     }
@@ -514,11 +838,13 @@ impl<'a> Identities<'a, Person> {
         payload: PersonPayload,
         delegations: delegation::Direct,
     ) -> Result<(Doc<PersonPayload, payload::PersonDelegations>, Revision), error::Store> {
+        payload::schema::validate(&payload)?;
+        let delegations = person_delegations(&payload, delegations, &BTreeSet::new())?;
         let doc = Doc {
             version: 0,
             replaces: None,
             payload,
-            delegations: payload::PersonDelegations::from(delegations),
+            delegations,
         };
         let root: Revision = self.repo.blob(&Cjson(&doc).canonical_form()?)?.into();
         Ok((doc, root))
@@ -548,14 +874,27 @@ impl<'a> Identities<'a, Person> {
         if payload.is_none() && delegations.is_none() {
             return Ok(base.into_inner());
         }
+        if let Some(payload) = &payload {
+            payload::schema::validate(payload)?;
+        }
 
+        let payload = payload.unwrap_or_else(|| base.payload().clone());
+        let previous = base
+            .delegations()
+            .iter()
+            .copied()
+            .chain(base.revocations().revoked.into_keys())
+            .collect();
+        let delegations = person_delegations(
+            &payload,
+            delegations.unwrap_or_else(|| base.delegations().clone()),
+            &previous,
+        )?;
         let doc = Doc {
             version: 0,
             replaces: Some(base.revision),
-            payload: payload.unwrap_or_else(|| base.payload().clone()),
-            delegations: payload::PersonDelegations::from(
-                delegations.unwrap_or_else(|| base.delegations().clone()),
-            ),
+            payload,
+            delegations,
         };
 
         let revision = {
@@ -584,7 +923,7 @@ impl<'a> Identities<'a, Person> {
             content_id,
             root: base.root,
             revision,
-            doc: doc.second(delegation::Direct::from),
+            doc: load::person_doc(doc)?,
             signatures,
         })
     }
@@ -626,10 +965,33 @@ impl<'a> Identities<'a, Project> {
             })
             .transpose()?;
 
-        Ok(generic::Verifying::from(head)
-            .signed()?
-            .quorum()?
-            .verified(parent.as_ref())?)
+        let head = generic::Verifying::from(head).signed()?;
+        let head = match parent {
+            None => head.genesis()?,
+            Some(_) => head.quorum()?,
+        };
+        Ok(head.verified(parent.as_ref())?)
+    }
+
+    /// Verify the project history with head commit `head`, requiring it to be
+    /// linear.
+    ///
+    /// Like [`Self::verify`], but fails with the [`Fork`] if the history is not
+    /// linear, see [`Identities::fork_point`]. Note that only the history of
+    /// the project itself is considered, not those of its indirect delegations.
+    pub fn verify_linear<F, E>(
+        &self,
+        head: git2::Oid,
+        find_latest_head: F,
+    ) -> Result<VerifiedProject, error::VerifyProject>
+    where
+        F: Fn(Urn) -> Result<git2::Oid, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match self.fork_point(head)? {
+            Some(fork) => Err(error::VerifyProject::NonLinear(fork)),
+            None => self.verify(head, find_latest_head),
+        }
     }
 
     /// Create a new [`Project`] from a payload and delegations.
@@ -683,6 +1045,7 @@ impl<'a> Identities<'a, Project> {
         ),
         error::Store,
     > {
+        payload::schema::validate(&payload)?;
         let doc = Doc {
             version: 0,
             replaces: None,
@@ -717,6 +1080,9 @@ impl<'a> Identities<'a, Project> {
         if payload.is_none() && delegations.is_none() {
             return Ok(base.into_inner());
         }
+        if let Some(payload) = &payload {
+            payload::schema::validate(payload)?;
+        }
 
         // FIXME: reorder stuff to avoid cloning
 
@@ -846,6 +1212,50 @@ impl<'a> Identities<'a, Project> {
     }
 }
 
+/// The [`payload::PersonDelegations`] to store for `delegations`, which keep
+/// listing the keys revoked by `payload`, see [`payload::PersonRevocations`].
+///
+/// A revoked key must be among `delegations`, or among the `previous`
+/// delegations of the person.
+fn person_delegations(
+    payload: &payload::PersonPayload,
+    delegations: delegation::Direct,
+    previous: &BTreeSet<PublicKey>,
+) -> Result<payload::PersonDelegations, error::Load> {
+    let mut delegations = payload::PersonDelegations::from(delegations);
+    for key in payload.revocations()?.revoked.into_keys() {
+        if !delegations.contains(&key) && !previous.contains(&key) {
+            return Err(error::Load::RevokedNotDelegated(key));
+        }
+        delegations.extend(Some(key));
+    }
+    Ok(delegations)
+}
+
+fn fold_verify<Doc, E>(
+    mut progeny: impl Iterator<Item = Result<Verifying<Identity<Doc>, Untrusted>, E>>,
+) -> Result<generic::Folded<Doc, Revision, ContentId>, VerificationError>
+where
+    Doc: Delegations + generic::Replaces<Revision = Revision>,
+    <Doc as Delegations>::Error: std::error::Error + Send + Sync + 'static,
+
+    E: std::error::Error + Send + Sync + 'static,
+{
+    // TODO(kim): should we skip non-quorum commits at the beginning?
+    //
+    // The initial revision only needs to be signed by one of its
+    // delegations, see `Verifying::genesis`.
+    let root = progeny
+        .next()
+        .ok_or(generic::error::Verify::EmptyHistory)?
+        .map_err(generic::error::Verify::history)?
+        .signed()?
+        .genesis()?
+        .verified(None)?;
+
+    root.verify(progeny)
+}
+
 pub fn sign<S>(signer: &S, rev: git_ext::Oid) -> Result<Signature, S::Error>
 where
     S: Signer,
@@ -0,0 +1,117 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Caching of identity verification results.
+//!
+//! Verifying an identity walks its entire history, checking the signatures
+//! of every revision. Since the history is content-addressed, the outcome of
+//! this walk is fully determined by the head commit it starts from, and can
+//! thus be cached by the head's oid.
+//!
+//! The [`Entry`] records the most recent verified revision, and the parent
+//! revision it was verified against. The latter is needed to verify projects,
+//! whose indirect delegations are resolved to their latest heads on every
+//! verification -- only the walk of the project's own history is cached.
+
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use super::ContentId;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Decode(#[from] minicbor::decode::Error),
+
+    #[error(transparent)]
+    Encode(#[from] minicbor::encode::Error<io::Error>),
+}
+
+/// The cached result of verifying the history of an identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, minicbor::Encode, minicbor::Decode)]
+pub struct Entry {
+    /// The most recent revision which passed verification.
+    #[n(0)]
+    pub head: ContentId,
+    /// The revision `head` was verified against, `None` if `head` is the
+    /// initial revision.
+    #[n(1)]
+    pub parent: Option<ContentId>,
+}
+
+/// Storage for verification results, keyed by the head commit the
+/// verification started from.
+///
+/// Failures to read from or write to the cache are not fatal, verification
+/// just proceeds as if there was no cached result.
+pub trait Cache {
+    fn get(&self, head: git2::Oid) -> Result<Option<Entry>, Error>;
+    fn put(&self, head: git2::Oid, entry: &Entry) -> Result<(), Error>;
+}
+
+/// A [`Cache`] which stores its entries on the file system, in the same
+/// fashion as the collaborative objects cache:
+///
+/// ```ignore
+/// <cache dir>
+/// |- v1
+/// |  |- <head 1 oid>
+/// |  |- <head 2 oid>
+/// |  ...
+/// ```
+///
+/// Each file contains the CBOR encoding of an [`Entry`]. Entries are written
+/// to a temporary file which is then renamed, so the cache is safe to be used
+/// by concurrent processes.
+#[derive(Clone, Debug)]
+pub struct FileSystemCache {
+    dir: PathBuf,
+}
+
+impl FileSystemCache {
+    /// Create a [`FileSystemCache`] in `dir`.
+    ///
+    /// The directory is created when the first entry is written.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().join("v1"),
+        }
+    }
+
+    fn entry_path(&self, head: git2::Oid) -> PathBuf {
+        self.dir.join(head.to_string())
+    }
+}
+
+impl Cache for FileSystemCache {
+    fn get(&self, head: git2::Oid) -> Result<Option<Entry>, Error> {
+        let bytes = match fs::read(self.entry_path(head)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(minicbor::decode(&bytes)?))
+    }
+
+    fn put(&self, head: git2::Oid, entry: &Entry) -> Result<(), Error> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = tempfile::NamedTempFile::new_in(&self.dir)?;
+        {
+            let out = tmp.as_file();
+            minicbor::encode(entry, out)?;
+            out.sync_all()?;
+        }
+        tmp.persist(self.entry_path(head))
+            .map_err(|e| Error::Io(e.error))?;
+        Ok(())
+    }
+}
@@ -6,12 +6,14 @@
 use std::{fmt::Debug, path::PathBuf};
 
 use canonical::CjsonError;
+use crypto::PublicKey;
 use thiserror::Error;
 
 use super::Urn;
 use crate::{
     delegation::indirect::error::FromIter as DelegationsFromIterError,
     generic,
+    payload,
     sign,
     ContentId,
     Revision,
@@ -38,6 +40,12 @@ pub enum Load {
     #[error(transparent)]
     Delegation(#[from] DelegationsFromIterError<Revision>),
 
+    #[error("revoked key {0} is not a delegation of the person")]
+    RevokedNotDelegated(PublicKey),
+
+    #[error("all delegations of the person are revoked")]
+    AllRevoked,
+
     #[error(transparent)]
     Signatures(#[from] self::Signatures),
 
@@ -66,6 +74,9 @@ pub enum Store {
     #[error(transparent)]
     Cjson(#[from] CjsonError),
 
+    #[error(transparent)]
+    InvalidExt(#[from] payload::schema::Invalid),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
@@ -117,6 +128,9 @@ pub enum VerifyProject {
     #[error(transparent)]
     VerifyPerson(#[from] self::VerifyPerson),
 
+    #[error("non-linear history: {0}")]
+    NonLinear(super::Fork),
+
     #[error(transparent)]
     Delegation(#[from] DelegationsFromIterError<Revision>),
 
@@ -138,9 +152,15 @@ pub enum VerifyPerson {
         latest_head: ContentId,
     },
 
+    #[error("non-linear history: {0}")]
+    NonLinear(super::Fork),
+
     #[error(transparent)]
     Verification(#[from] generic::error::Verify<Revision, ContentId>),
 
+    #[error(transparent)]
+    Load(#[from] self::Load),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
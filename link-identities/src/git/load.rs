@@ -3,9 +3,10 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{convert::TryFrom, path::PathBuf};
+use std::{collections::BTreeSet, convert::TryFrom, path::PathBuf};
 
 use canonical::Cjson;
+use data::NonEmpty;
 use either::Either;
 use git_ext::{self as ext, is_not_found_err};
 use std_ext::result::ResultExt as _;
@@ -16,6 +17,7 @@ use crate::{
     payload::{
         PersonDelegations,
         PersonPayload,
+        PersonQuorum,
         ProjectDelegations,
         ProjectPayload,
         SomeDelegations,
@@ -25,7 +27,7 @@ use crate::{
     urn::Urn,
 };
 
-use super::{error, ContentId, Doc, Identity, Person, Project, Revision, SomeIdentity};
+use super::{error, ContentId, Doc, Identity, Person, PersonDoc, Project, Revision, SomeIdentity};
 
 pub type ByOid<'a> = (&'a git2::Repository, git2::Oid);
 
@@ -86,12 +88,42 @@ struct Any<'a, Doc> {
 type AnyPerson<'a> = Any<'a, Doc<PersonPayload, PersonDelegations>>;
 type AnyProject<'a> = Any<'a, Doc<ProjectPayload, ProjectDelegations<Revision>>>;
 
-impl<'a> From<AnyPerson<'a>> for Person {
-    fn from(any: AnyPerson<'a>) -> Self {
-        any.identity.map(|doc| doc.second(delegation::Direct::from))
+impl<'a> TryFrom<AnyPerson<'a>> for Person {
+    type Error = error::Load;
+
+    fn try_from(any: AnyPerson<'a>) -> Result<Self, Self::Error> {
+        any.identity.map(person_doc).transpose()
     }
 }
 
+/// Convert the [`PersonDelegations`] of `doc` to [`delegation::Direct`],
+/// applying the [`PersonQuorum`] extension of the payload, if any.
+///
+/// Keys revoked by the [`PersonRevocations`] extension must be among the
+/// [`PersonDelegations`], and are removed from the resulting
+/// [`delegation::Direct`].
+///
+/// [`PersonRevocations`]: crate::payload::PersonRevocations
+pub(super) fn person_doc(
+    doc: Doc<PersonPayload, PersonDelegations>,
+) -> Result<PersonDoc, error::Load> {
+    let revocations = doc.payload.revocations()?;
+    let threshold = doc
+        .payload
+        .get_ext::<PersonQuorum>()?
+        .map(|quorum| quorum.threshold);
+    doc.try_second(|delegations| {
+        let mut keys = BTreeSet::from(delegations);
+        for key in revocations.revoked.keys() {
+            if !keys.remove(key) {
+                return Err(error::Load::RevokedNotDelegated(*key));
+            }
+        }
+        let keys = NonEmpty::from_maybe_empty(keys).ok_or(error::Load::AllRevoked)?;
+        Ok(delegation::Direct::from(keys).with_threshold(threshold))
+    })
+}
+
 impl<'a> TryFrom<AnyProject<'a>> for Project {
     type Error = error::Load;
 
@@ -104,6 +136,9 @@ impl<'a> TryFrom<AnyProject<'a>> for Project {
 
         identity
             .map(|doc| {
+                if let Err(e) = doc.payload.status::<Revision>() {
+                    tracing::warn!(err = %e, "ignoring unknown or malformed project status");
+                }
                 doc.try_second(|delegations| {
                     let delegations = delegations
                         .into_iter()
@@ -204,7 +239,7 @@ impl<'a> TryFrom<ByOid<'a>> for SomeIdentity {
 
         match doc {
             SomeDoc::Person(person) => {
-                let person = Person::from(Any {
+                let person = Person::try_from(Any {
                     repo,
                     tree,
                     identity: Identity {
@@ -214,7 +249,7 @@ impl<'a> TryFrom<ByOid<'a>> for SomeIdentity {
                         doc: person,
                         signatures,
                     },
-                });
+                })?;
                 Ok(SomeIdentity::Person(person))
             },
 
@@ -240,7 +275,7 @@ impl<'a> TryFrom<ByOid<'a>> for Person {
     type Error = error::Load;
 
     fn try_from(git: ByOid<'a>) -> Result<Self, Self::Error> {
-        Ok(Person::from(Any::try_from(git)?))
+        Person::try_from(AnyPerson::try_from(git)?)
     }
 }
 
@@ -267,7 +302,8 @@ fn resolve_inlined_person(
         .into_blob()
         .map_err(|obj| error::Load::NotABlob(path, obj.kind()))?;
 
-    Ok(Cjson::<InlinedPerson>::from_slice(blob.content())?
+    Cjson::<InlinedPerson>::from_slice(blob.content())?
         .into_inner()
-        .map(|doc| doc.second(delegation::Direct::from)))
+        .map(person_doc)
+        .transpose()
 }
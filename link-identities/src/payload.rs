@@ -9,6 +9,7 @@ use std::{
     fmt::{self, Debug},
     iter::FromIterator,
     marker::PhantomData,
+    num::NonZeroUsize,
     ops::{Deref, DerefMut, RangeBounds},
 };
 
@@ -27,6 +28,8 @@ use crate::{
     urn::{HasProtocol, Urn},
 };
 
+pub mod schema;
+
 lazy_static! {
     /// Base [`Url`] for [`Person`]
     static ref PERSON_NAMESPACE_BASE: Url =
@@ -39,6 +42,18 @@ lazy_static! {
         base
     };
 
+    /// Versioned [`Url`] for [`PersonQuorum`], version 1
+    static ref PERSON_QUORUM_NAMESPACE_V1: Url =
+        Url::parse("https://radicle.xyz/link/identities/quorum/v1").unwrap();
+
+    /// Versioned [`Url`] for [`PersonRevocations`], version 1
+    static ref PERSON_REVOCATIONS_NAMESPACE_V1: Url =
+        Url::parse("https://radicle.xyz/link/identities/revocations/v1").unwrap();
+
+    /// Versioned [`Url`] for [`ProjectStatus`], version 1
+    static ref PROJECT_STATUS_NAMESPACE_V1: Url =
+        Url::parse("https://radicle.xyz/link/identities/status/v1").unwrap();
+
     /// Base [`Url`] for [`Project`]
     static ref PROJECT_NAMESPACE_BASE: Url =
         Url::parse("https://radicle.xyz/link/identities/project").unwrap();
@@ -60,6 +75,84 @@ pub struct Person {
 
 impl sealed::Sealed for Person {}
 
+/// Extension of a [`PersonPayload`], which overrides the number of
+/// [`PersonDelegations`] required to sign a revision of the document.
+///
+/// If absent, a majority of the delegations is required. Like any other change
+/// to the document, setting or lowering the threshold must be signed by a
+/// quorum of the previous revision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PersonQuorum {
+    pub threshold: NonZeroUsize,
+}
+
+impl HasNamespace for PersonQuorum {
+    fn namespace() -> &'static Url {
+        &PERSON_QUORUM_NAMESPACE_V1
+    }
+}
+
+/// Extension of a [`PersonPayload`], which lists keys that are no longer to be
+/// trusted.
+///
+/// Each revoked key maps to the commits signed by it which remain valid, eg.
+/// the last `rad/signed_refs` published by the compromised device. Commits
+/// signed by a revoked key are rejected, unless they are one of those commits
+/// or an ancestor of one of them. Unlike the (unauthenticated) commit time,
+/// the ancestry of a commit can not be forged by the holder of the revoked
+/// key.
+///
+/// A person can only revoke keys of its own [`PersonDelegations`]. Revoked keys
+/// remain listed there, but are no longer eligible to sign revisions of the
+/// document.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PersonRevocations {
+    pub revoked: BTreeMap<PublicKey, BTreeSet<git_ext::Oid>>,
+}
+
+impl PersonRevocations {
+    /// Revoke `key`, retaining the validity of the commits `valid` and their
+    /// ancestors.
+    ///
+    /// If `key` was already revoked, only the commits retained by both
+    /// revocations stay valid.
+    pub fn revoke<I>(&mut self, key: PublicKey, valid: I)
+    where
+        I: IntoIterator<Item = git_ext::Oid>,
+    {
+        let valid = valid.into_iter().collect::<BTreeSet<_>>();
+        match self.revoked.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(valid);
+            },
+            Entry::Occupied(mut entry) => entry.get_mut().retain(|oid| valid.contains(oid)),
+        }
+    }
+
+    /// Whether `key` was revoked.
+    pub fn is_revoked(&self, key: &PublicKey) -> bool {
+        self.revoked.contains_key(key)
+    }
+
+    /// The commits signed by `key` which remain valid, if it was revoked.
+    pub fn valid(&self, key: &PublicKey) -> Option<&BTreeSet<git_ext::Oid>> {
+        self.revoked.get(key)
+    }
+
+    /// Combine the revocations of `self` and `other`.
+    pub fn merge(&mut self, other: Self) {
+        for (key, valid) in other.revoked {
+            self.revoke(key, valid)
+        }
+    }
+}
+
+impl HasNamespace for PersonRevocations {
+    fn namespace() -> &'static Url {
+        &PERSON_REVOCATIONS_NAMESPACE_V1
+    }
+}
+
 /// Structure `radicle-link` expects to be part of a [`Payload`] describing a
 /// project identity.
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -71,6 +164,46 @@ pub struct Project {
 
 impl sealed::Sealed for Project {}
 
+/// Extension of a [`ProjectPayload`], which describes the lifecycle status of
+/// the project.
+///
+/// If absent, the project is [`ProjectStatus::Active`]. A status which is not
+/// known to this version (or otherwise malformed) is treated as absent when
+/// loading the project.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(
+    tag = "status",
+    rename_all = "camelCase",
+    bound(
+        serialize = "Urn<R>: serde::Serialize",
+        deserialize = "Urn<R>: serde::Deserialize<'de>"
+    )
+)]
+pub enum ProjectStatus<R> {
+    /// The project is maintained.
+    Active,
+    /// The project is still available, but its use is discouraged as of
+    /// `since` (in seconds since the Unix epoch).
+    Deprecated { since: i64 },
+    /// The project is no longer maintained, as of `since` (in seconds since
+    /// the Unix epoch).
+    Archived { since: i64 },
+    /// The project was superseded by the project `to`.
+    Moved { to: Urn<R> },
+}
+
+impl<R> Default for ProjectStatus<R> {
+    fn default() -> Self {
+        Self::Active
+    }
+}
+
+impl<R> HasNamespace for ProjectStatus<R> {
+    fn namespace() -> &'static Url {
+        &PROJECT_STATUS_NAMESPACE_V1
+    }
+}
+
 /// Namespace attached to a member type of the [`Payload`] "open" coproduct.
 ///
 /// This is morally a constant -- we cannot, however, construct a [`Url`] in
@@ -136,8 +269,27 @@ impl Subject for Project {
 }
 
 pub type PersonPayload = Payload<Person>;
+
+impl PersonPayload {
+    /// The [`PersonRevocations`] extension of this payload, or an empty set of
+    /// revocations if absent.
+    pub fn revocations(&self) -> Result<PersonRevocations, serde_json::Error> {
+        Ok(self.get_ext()?.unwrap_or_default())
+    }
+}
 pub type ProjectPayload = Payload<Project>;
 
+impl ProjectPayload {
+    /// The [`ProjectStatus`] extension of this payload, or
+    /// [`ProjectStatus::Active`] if absent.
+    pub fn status<R>(&self) -> Result<ProjectStatus<R>, serde_json::Error>
+    where
+        Urn<R>: serde::de::DeserializeOwned,
+    {
+        Ok(self.get_ext()?.unwrap_or_default())
+    }
+}
+
 /// [`Payload`] for which the type is not known statically.
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
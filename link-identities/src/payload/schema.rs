@@ -0,0 +1,148 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Validation of [`Payload`] extensions against JSON schemas.
+//!
+//! Applications which define an extension namespace can [`register`] a [JSON
+//! schema] for it. Payloads are then validated against the registered schemas
+//! when an identity is created or updated via [`crate::git::Identities`], and
+//! [`check`] can be used to flag extensions of identities received from other
+//! peers which do not conform.
+//!
+//! Extensions whose namespace has no registered schema are not validated.
+//!
+//! [JSON schema]: https://json-schema.org
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{PoisonError, RwLock},
+};
+
+use jsonschema::JSONSchema;
+use thiserror::Error;
+use url::Url;
+
+use super::{Payload, Subject};
+
+lazy_static! {
+    static ref GLOBAL: RwLock<Registry> = RwLock::new(Registry::default());
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("invalid schema for extension {namespace}: {reason}")]
+    Schema { namespace: Url, reason: String },
+}
+
+/// An extension which does not conform to the schema registered for its
+/// namespace.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("extension {namespace} does not conform to its schema: {}", .reasons.join(", "))]
+pub struct Invalid {
+    pub namespace: Url,
+    pub reasons: Vec<String>,
+}
+
+/// A set of JSON schemas, by extension namespace.
+#[derive(Default)]
+pub struct Registry {
+    schemas: BTreeMap<Url, JSONSchema>,
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.schemas.keys()).finish()
+    }
+}
+
+impl Registry {
+    /// Register `schema` for the extension `namespace`, replacing any
+    /// previously registered schema.
+    pub fn register(&mut self, namespace: Url, schema: &serde_json::Value) -> Result<(), Error> {
+        let compiled = JSONSchema::compile(schema).map_err(|e| Error::Schema {
+            namespace: namespace.clone(),
+            reason: e.to_string(),
+        })?;
+        self.schemas.insert(namespace, compiled);
+        Ok(())
+    }
+
+    /// Remove the schema for the extension `namespace`.
+    ///
+    /// Returns `true` if a schema was registered.
+    pub fn unregister(&mut self, namespace: &Url) -> bool {
+        self.schemas.remove(namespace).is_some()
+    }
+
+    /// Validate the extensions of `payload`, returning all which do not conform
+    /// to their schema.
+    pub fn check<T>(&self, payload: &Payload<T>) -> Vec<Invalid>
+    where
+        T: Subject,
+    {
+        payload
+            .exts()
+            .filter_map(|(namespace, val)| {
+                let schema = self.schemas.get(namespace)?;
+                schema.validate(val).err().map(|errors| Invalid {
+                    namespace: namespace.clone(),
+                    reasons: errors.map(|e| e.to_string()).collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Validate the extensions of `payload`, failing with the first which does
+    /// not conform to its schema.
+    pub fn validate<T>(&self, payload: &Payload<T>) -> Result<(), Invalid>
+    where
+        T: Subject,
+    {
+        match self.check(payload).into_iter().next() {
+            None => Ok(()),
+            Some(invalid) => Err(invalid),
+        }
+    }
+}
+
+/// Register `schema` for the extension `namespace` in the process-wide
+/// [`Registry`].
+pub fn register(namespace: Url, schema: &serde_json::Value) -> Result<(), Error> {
+    GLOBAL
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(namespace, schema)
+}
+
+/// Remove the schema for the extension `namespace` from the process-wide
+/// [`Registry`].
+pub fn unregister(namespace: &Url) -> bool {
+    GLOBAL
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .unregister(namespace)
+}
+
+/// [`Registry::check`] against the process-wide [`Registry`].
+pub fn check<T>(payload: &Payload<T>) -> Vec<Invalid>
+where
+    T: Subject,
+{
+    GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .check(payload)
+}
+
+/// [`Registry::validate`] against the process-wide [`Registry`].
+pub fn validate<T>(payload: &Payload<T>) -> Result<(), Invalid>
+where
+    T: Subject,
+{
+    GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .validate(payload)
+}
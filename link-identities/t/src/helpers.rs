@@ -97,6 +97,14 @@ impl<'a> Device<'a> {
         Ok(Self { cur, ..self })
     }
 
+    pub fn update_payload(self, payload: payload::PersonPayload) -> anyhow::Result<Self> {
+        let cur = self
+            .git
+            .update(Verifying::from(self.cur).signed()?, payload, None, self.key)?;
+
+        Ok(Self { cur, ..self })
+    }
+
     pub fn update_from(self, other: &Device<'a>) -> anyhow::Result<Self> {
         let cur = self.git.update_from(
             Verifying::from(self.cur).signed()?,
@@ -148,6 +156,11 @@ pub struct Project<'a> {
 
 impl<'a> Project<'a> {
     pub fn new(dev: Device<'a>) -> anyhow::Result<Self> {
+        let delegations = IndirectDelegation::try_from_iter(Some(Right(dev.cur.clone())))?;
+        Self::new_with(dev, delegations)
+    }
+
+    pub fn new_with(dev: Device<'a>, delegations: IndirectDelegation) -> anyhow::Result<Self> {
         let cur = dev.git.as_project().create(
             payload::Project {
                 name: "haskell-emoji".into(),
@@ -155,7 +168,7 @@ impl<'a> Project<'a> {
                 default_branch: Some("\u{1F32F}".into()),
             }
             .into(),
-            IndirectDelegation::try_from_iter(Some(Right(dev.cur.clone())))?,
+            delegations,
             dev.key,
         )?;
 
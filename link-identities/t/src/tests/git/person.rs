@@ -3,13 +3,28 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::num::NonZeroUsize;
+
 use it_helpers::tmp;
 use link_crypto::SecretKey;
 use link_identities::{
-    delegation::Direct,
-    git::{error, VerificationError},
+    delegation::{
+        certificate::{Certificate, Certificates},
+        Direct,
+    },
+    git::{
+        cache::{Cache as _, FileSystemCache},
+        error,
+        Fork,
+        Person,
+        VerificationError,
+        Verifying,
+    },
+    payload::{self, Ext, PersonQuorum, PersonRevocations},
     Identities,
 };
+use serde_json::json;
+use url::Url;
 
 use crate::helpers::Device;
 
@@ -109,3 +124,330 @@ fn revoke_a_trois() -> anyhow::Result<()> {
         desktop.assert_verifies()
     }
 }
+
+#[test]
+fn quorum_override() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?.update(
+            Direct::new(DESKTOP.public())
+                .insert(LAPTOP.public())
+                .insert(PALMTOP.public()),
+        )?;
+        let laptop = Device::create_from(&*LAPTOP, &desktop)?;
+        let desktop = desktop.update_from(&laptop)?;
+        desktop.assert_verifies()?;
+
+        // Lowering the threshold requires the current quorum
+        let mut payload = desktop.current().payload().clone();
+        payload.set_ext(PersonQuorum {
+            threshold: NonZeroUsize::new(1).unwrap(),
+        })?;
+        let desktop = desktop.update_payload(payload)?;
+        assert_matches!(
+            desktop.verify(),
+            Err(error::VerifyPerson::Verification(
+                VerificationError::ParentQuorum
+            ))
+        );
+        let laptop = Device::create_from(&*LAPTOP, &desktop)?;
+        laptop.assert_verifies()?;
+        let desktop = desktop.update_from(&laptop)?;
+        desktop.assert_verifies()?;
+        assert_eq!(
+            desktop.current().delegations().threshold(),
+            NonZeroUsize::new(1)
+        );
+
+        // Now a single device is enough
+        let desktop = desktop.update(Direct::new(DESKTOP.public()).insert(LAPTOP.public()))?;
+        desktop.assert_verifies()
+    }
+}
+
+#[test]
+fn linearise() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?
+            .update(Direct::new(DESKTOP.public()).insert(LAPTOP.public()))?;
+        let laptop = Device::create_from(&*LAPTOP, &desktop)?;
+        let desktop = desktop.update_from(&laptop)?;
+        desktop.assert_verifies()?;
+        let base = desktop.current().revision;
+
+        // Competing proposals
+        let desktop = desktop.update_payload(payload::PersonPayload::new(payload::Person {
+            name: "desktop".into(),
+        }))?;
+        let laptop = laptop.update_payload(payload::PersonPayload::new(payload::Person {
+            name: "laptop".into(),
+        }))?;
+
+        // Joined by a merge commit, as another implementation might do
+        let merge = {
+            let ours = repo.find_commit(*desktop.current().content_id)?;
+            let theirs = repo.find_commit(*laptop.current().content_id)?;
+            let author = repo.signature()?;
+            repo.commit(
+                None,
+                &author,
+                &author,
+                ours.message().unwrap(),
+                &ours.tree()?,
+                &[&ours, &theirs],
+            )?
+        };
+
+        let git = Identities::<Person>::from(&*repo);
+        assert_matches!(
+            git.fork_point(merge)?,
+            Some(Fork { base: Some(at), .. }) if at == base
+        );
+        assert_matches!(
+            git.verify_linear(merge),
+            Err(error::VerifyPerson::NonLinear(_))
+        );
+
+        let verified = git.verify(merge)?;
+        assert_eq!(verified.revision, base);
+
+        let linear = git.linearise(verified)?;
+        assert_eq!(git.fork_point(*linear.content_id)?, None);
+        assert_eq!(git.verify_linear(*linear.content_id)?.revision, base);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn revocations() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?;
+        let first = desktop.current().content_id;
+        let desktop = desktop.update(
+            Direct::new(DESKTOP.public())
+                .insert(LAPTOP.public())
+                .insert(PALMTOP.public()),
+        )?;
+        let laptop = Device::create_from(&*LAPTOP, &desktop)?;
+        let desktop = desktop.update_from(&laptop)?;
+        desktop.assert_verifies()?;
+        let signed_by_laptop = laptop.current().content_id;
+
+        // Only own keys can be revoked
+        let stranger = SecretKey::new();
+        let mut revocations = PersonRevocations::default();
+        revocations.revoke(stranger.public(), None);
+        let mut payload = desktop.current().payload().clone();
+        payload.set_ext(revocations)?;
+        let err = desktop.clone().update_payload(payload).unwrap_err();
+        assert_matches!(
+            err.downcast_ref::<error::Store>(),
+            Some(error::Store::Load(error::Load::RevokedNotDelegated(key))) if *key == stranger.public()
+        );
+
+        let mut revocations = PersonRevocations::default();
+        revocations.revoke(LAPTOP.public(), Some(signed_by_laptop));
+        // Only the commits retained by both revocations stay valid
+        revocations.revoke(LAPTOP.public(), None);
+        assert_eq!(
+            revocations.valid(&LAPTOP.public()),
+            Some(&Default::default())
+        );
+
+        let mut revocations = PersonRevocations::default();
+        revocations.revoke(LAPTOP.public(), Some(signed_by_laptop));
+        let mut payload = desktop.current().payload().clone();
+        payload.set_ext(revocations)?;
+        let desktop = desktop.update_payload(payload)?;
+        let palmtop = Device::create_from(&*PALMTOP, &desktop)?;
+        let desktop = desktop.update_from(&palmtop)?;
+        desktop.assert_verifies()?;
+
+        let verified = desktop.verify()?;
+        assert!(!verified.delegations().contains(&LAPTOP.public()));
+        let revocations = verified.revocations();
+        assert!(revocations.is_revoked(&LAPTOP.public()));
+        assert!(!revocations.is_revoked(&DESKTOP.public()));
+
+        // Commits covered by the revocation remain valid, later ones don't
+        let head = *verified.content_id;
+        assert!(!revocations.rejects(&repo, &LAPTOP.public(), *signed_by_laptop)?);
+        assert!(!revocations.rejects(&repo, &LAPTOP.public(), *first)?);
+        assert!(revocations.rejects(&repo, &LAPTOP.public(), head)?);
+        assert!(!revocations.rejects(&repo, &DESKTOP.public(), head)?);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn ext_schema() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let namespace: Url = "https://example.com/link/tests/avatar".parse()?;
+        let avatar = |val| Ext {
+            namespace: namespace.clone(),
+            val,
+        };
+
+        // Without a schema, anything goes
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?;
+        let mut payload = desktop.current().payload().clone();
+        payload.set_ext(avatar(json!({ "size": "large" })))?;
+        let desktop = desktop.update_payload(payload)?;
+        desktop.assert_verifies()?;
+
+        payload::schema::register(
+            namespace.clone(),
+            &json!({
+                "type": "object",
+                "properties": { "size": { "type": "integer" } },
+                "required": ["size"],
+            }),
+        )?;
+
+        // Existing revisions are flagged
+        let invalid = desktop.verify()?.invalid_exts();
+        assert_eq!(
+            invalid.iter().map(|i| &i.namespace).collect::<Vec<_>>(),
+            vec![&namespace]
+        );
+
+        // New revisions must conform
+        let mut payload = desktop.current().payload().clone();
+        payload.set_ext(avatar(json!({ "size": "small" })))?;
+        assert_matches!(
+            Identities::<Person>::from(&*repo).update(
+                Verifying::from(desktop.current().clone()).signed()?,
+                payload.clone(),
+                None,
+                &*DESKTOP,
+            ),
+            Err(error::Store::InvalidExt(payload::schema::Invalid { namespace: ns, .. }))
+                if ns == namespace
+        );
+        payload.set_ext(avatar(json!({ "size": 64 })))?;
+        let desktop = desktop.update_payload(payload)?;
+        assert!(desktop.verify()?.invalid_exts().is_empty());
+
+        payload::schema::unregister(&namespace);
+        Ok(())
+    }
+}
+
+#[test]
+fn cached_verification() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    let dir = tempfile::tempdir()?;
+    {
+        let cache = FileSystemCache::new(dir.path());
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?;
+        let root = desktop.current().content_id;
+        // Not confirmed by the laptop, so verification stops at the root
+        let desktop = desktop.update(Direct::new(DESKTOP.public()).insert(LAPTOP.public()))?;
+        let head = *desktop.current().content_id;
+
+        let git = Identities::<Person>::from(&*repo).with_cache(&cache);
+        assert_eq!(cache.get(head)?, None);
+        let verified = git.verify(head)?;
+        assert_eq!(verified.content_id, root);
+
+        let entry = cache
+            .get(head)?
+            .expect("verification result should be cached");
+        assert_eq!(entry.head, root);
+        assert_eq!(entry.parent, None);
+        assert_eq!(git.verify(head)?, verified);
+
+        Ok(())
+    }
+}
+
+#[test]
+fn certified_device() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?;
+        let git = Identities::<Person>::from(&*repo);
+        // The laptop is not a delegate, but signs an update
+        let update = git.update(
+            Verifying::from(desktop.current().clone()).signed()?,
+            payload::PersonPayload::new(payload::Person {
+                name: "laptop".into(),
+            }),
+            None,
+            &*LAPTOP,
+        )?;
+        let head = *update.content_id;
+        assert_matches!(
+            git.verify(head),
+            Err(error::VerifyPerson::Verification(
+                VerificationError::ParentQuorum
+            ))
+        );
+
+        // Certified by the desktop, the laptop votes on its behalf
+        let certs = Certificates::from_iter(Some(Certificate::issue(
+            &*DESKTOP,
+            LAPTOP.public(),
+            0,
+            i64::MAX,
+        )?));
+        let verified = Identities::<Person>::from(&*repo)
+            .with_certificates(&certs)
+            .verify(head)?;
+        assert_eq!(verified.content_id, update.content_id);
+
+        // But not if the certificate expired
+        let expired =
+            Certificates::from_iter(Some(Certificate::issue(&*DESKTOP, LAPTOP.public(), 0, 1)?));
+        assert_matches!(
+            Identities::<Person>::from(&*repo)
+                .with_certificates(&expired)
+                .verify(head),
+            Err(error::VerifyPerson::Verification(
+                VerificationError::ParentQuorum
+            ))
+        );
+
+        // Backdating the commit into the validity period of an expired
+        // certificate doesn't help either
+        let backdated = {
+            let time = git2::Time::new(1_000, 0);
+            let sig = git2::Signature::new("laptop", "laptop@example.com", &time)?;
+            repo.find_commit(head)?
+                .amend(None, Some(&sig), Some(&sig), None, None, None)?
+        };
+        let expired = Certificates::from_iter(Some(Certificate::issue(
+            &*DESKTOP,
+            LAPTOP.public(),
+            0,
+            2_000,
+        )?));
+        assert_matches!(
+            Identities::<Person>::from(&*repo)
+                .with_certificates(&expired)
+                .verify(backdated),
+            Err(error::VerifyPerson::Verification(
+                VerificationError::ParentQuorum
+            ))
+        );
+
+        // Nor if it wasn't issued by a delegate
+        let self_issued = Certificates::from_iter(Some(Certificate::issue(
+            &*LAPTOP,
+            LAPTOP.public(),
+            0,
+            i64::MAX,
+        )?));
+        assert!(Identities::<Person>::from(&*repo)
+            .with_certificates(&self_issued)
+            .verify(head)
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -20,6 +20,8 @@ use librad::{
     SecretKey,
 };
 use radicle_std_ext::Void;
+use serde_json::json;
+use url::Url;
 
 use crate::helpers::{Device, Project};
 
@@ -82,6 +84,37 @@ fn update() -> anyhow::Result<()> {
     }
 }
 
+#[test]
+fn create_multisig() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let cheyenne = Device::new(&*CHEYENNE_DESKTOP, Identities::from(&*repo))?;
+        let dylan = Device::new(&*DYLAN, Identities::from(&*repo))?;
+
+        let heads = current_heads_from(vec![&cheyenne, &dylan]);
+
+        // Cheyenne initialises the project on behalf of both
+        let project = {
+            let delegations = IndirectDelegation::try_from_iter(vec![
+                Right(cheyenne.current().clone()),
+                Right(dylan.current().clone()),
+            ])?;
+            Project::new_with(cheyenne, delegations)
+        }?;
+        project.assert_no_quorum()?;
+        project.assert_verifies(lookup(&heads))?;
+
+        // Dylan joins
+        let project = Project::create_from(dylan, &project)?;
+        project.assert_verifies(lookup(&heads))?;
+
+        // Subsequent revisions require the regular quorum
+        project
+            .change_description("sig all the things")?
+            .assert_no_quorum()
+    }
+}
+
 #[test]
 fn update_payload() -> anyhow::Result<()> {
     let repo = tmp::repo()?;
@@ -103,6 +136,28 @@ fn update_payload() -> anyhow::Result<()> {
     }
 }
 
+#[test]
+fn unknown_status() -> anyhow::Result<()> {
+    let repo = tmp::repo()?;
+    {
+        let cheyenne = Device::new(&*CHEYENNE_DESKTOP, Identities::from(&*repo))?;
+        let heads = current_heads_from(vec![&cheyenne]);
+
+        // A status introduced by a later version
+        let project = Project::new(cheyenne)?;
+        let mut update = project.current().payload().clone();
+        update.set_ext(payload::Ext {
+            namespace: "https://radicle.xyz/link/identities/status/v1".parse::<Url>()?,
+            val: json!({ "status": "frozen" }),
+        })?;
+        let project = project.update(update, None)?;
+        project.assert_verifies(lookup(&heads))?;
+        assert_eq!(project.current().status(), payload::ProjectStatus::Active);
+
+        Ok(())
+    }
+}
+
 /// Revoke by just removing a delegation at the top-level
 #[test]
 fn revoke() -> anyhow::Result<()> {
@@ -6,14 +6,18 @@
 use std::fmt::Debug;
 
 use link_crypto::SecretKey;
-use link_identities::payload::{
-    Ext,
-    Person,
-    PersonDelegations,
-    PersonPayload,
-    Project,
-    ProjectDelegations,
-    ProjectPayload,
+use link_identities::{
+    payload::{
+        Ext,
+        Person,
+        PersonDelegations,
+        PersonPayload,
+        Project,
+        ProjectDelegations,
+        ProjectPayload,
+        ProjectStatus,
+    },
+    urn::Urn,
 };
 use pretty_assertions::assert_eq;
 use proptest::prelude::*;
@@ -122,6 +126,55 @@ nom is a parser combinators library written in Rust.";
     roundtrip::cjson(payload)
 }
 
+#[test]
+fn project_status() {
+    let mut payload = ProjectPayload::new(Project {
+        name: "nom".into(),
+        description: None,
+        default_branch: None,
+    });
+    assert_eq!(payload.status::<Oid>().unwrap(), ProjectStatus::Active);
+
+    let to = Urn::new(Oid::from(git2::Oid::zero()));
+    payload
+        .set_ext(ProjectStatus::Moved { to: to.clone() })
+        .unwrap();
+    let json_pretty = format!(
+        r#"{{
+  "https://radicle.xyz/link/identities/project/v1": {{
+    "name": "nom",
+    "description": null,
+    "default_branch": null
+  }},
+  "https://radicle.xyz/link/identities/status/v1": {{
+    "status": "moved",
+    "to": "{}"
+  }}
+}}"#,
+        to
+    );
+    assert_eq!(serde_json::to_string_pretty(&payload).unwrap(), json_pretty);
+    assert_eq!(payload.status().unwrap(), ProjectStatus::Moved { to });
+
+    payload
+        .set_ext(ProjectStatus::<Oid>::Archived { since: 1_000 })
+        .unwrap();
+    assert_eq!(
+        payload.status::<Oid>().unwrap(),
+        ProjectStatus::Archived { since: 1_000 }
+    );
+
+    payload
+        .set_ext(ProjectStatus::<Oid>::Deprecated { since: 1_000 })
+        .unwrap();
+    assert_eq!(
+        payload.status::<Oid>().unwrap(),
+        ProjectStatus::Deprecated { since: 1_000 }
+    );
+
+    roundtrip::cjson(payload)
+}
+
 fn duplicate_delegation<T>()
 where
     T: serde::Serialize + serde::de::DeserializeOwned,
@@ -158,3 +158,27 @@ pub enum OwnRad<T: Debug + Send + Sync + 'static> {
     #[error("ref transaction failure")]
     Tx(#[source] Error),
 }
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Quota {
+    #[error("storage quota of {quota} bytes exceeded: {used} bytes in use")]
+    Urn { used: u64, quota: u64 },
+
+    #[error("storage quota of {quota} bytes exceeded by {remote}: {used} bytes in use")]
+    Remote {
+        remote: PeerId,
+        used: u64,
+        quota: u64,
+    },
+
+    #[error("fetch exceeded the remaining storage quota of {remaining} bytes")]
+    Fetch {
+        remaining: u64,
+        #[source]
+        source: Error,
+    },
+
+    #[error("failed to determine storage usage")]
+    Usage(#[source] Error),
+}
@@ -3,7 +3,11 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{collections::BTreeMap, fmt::Debug, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    marker::PhantomData,
+};
 
 use itertools::Itertools;
 
@@ -12,7 +16,9 @@ use crate::{
     error,
     fetch,
     ids,
+    metrics::{Phase, Phases},
     peek,
+    quota,
     refs,
     sigrefs::{self, Refs},
     state::FetchState,
@@ -23,6 +29,7 @@ use crate::{
     Identities,
     LocalIdentity,
     LocalPeer,
+    Metrics,
     Net,
     Odb,
     PeerId,
@@ -34,10 +41,12 @@ use crate::{
     SymrefTarget,
     Tracking,
     Update,
+    Usage,
     VerifiedIdentity,
 };
 
 pub(crate) fn pull<U, C>(
+    phases: &mut Phases,
     state: &mut FetchState<U>,
     cx: &mut C,
     limit: FetchLimit,
@@ -49,11 +58,13 @@ where
     U: ids::Urn + Clone + Debug + Ord,
     C: Identities<Urn = U>
         + LocalPeer
+        + Metrics
         + Net
         + Refdb
         + Odb
         + SignedRefs<Oid = <C as Identities>::Oid>
-        + Tracking<Urn = U>,
+        + Tracking<Urn = U>
+        + Usage,
     <C as Identities>::Oid: Debug + PartialEq + Send + Sync + 'static,
     for<'a> &'a C: RefScan,
 {
@@ -62,6 +73,13 @@ where
     let scx = state.as_shim(cx);
     let local_id = *LocalPeer::id(&scx);
     let delegates = VerifiedIdentity::delegate_ids(&anchor);
+    let revoked = VerifiedIdentity::revoked(&anchor)
+        .into_iter()
+        .map(|(id, valid)| {
+            let valid = valid.iter().map(|oid| oid.as_ref().to_owned());
+            (id, valid.collect::<BTreeSet<_>>())
+        })
+        .collect::<BTreeMap<_, _>>();
     let delegates_sans_local = delegates
         .iter()
         .filter(|id| *id != &local_id)
@@ -101,8 +119,10 @@ where
         limit: limit.peek,
     };
     debug!(?peek);
+    phases.enter(&*cx, Phase::Peek);
     state.step(cx, &peek)?;
 
+    phases.enter(&*cx, Phase::RadRefs);
     info!("loading sigrefs");
     let signed_refs = sigrefs::combined(
         &state.as_shim(cx),
@@ -115,6 +135,7 @@ where
                 .copied()
                 .collect(),
             cutoff: 2,
+            revoked: &revoked,
         },
     )?;
     debug!(?signed_refs);
@@ -198,7 +219,18 @@ where
         Refdb::update(cx, tips)?
     };
 
-    let signed_refs = signed_refs.flattened();
+    let mut signed_refs = signed_refs.flattened();
+    let mut budget = if limit.quota.is_unlimited() {
+        quota::Budget {
+            limit: limit.data,
+            bound: false,
+            remaining: None,
+            exceeded: Vec::new(),
+        }
+    } else {
+        info!("checking storage quota");
+        quota::budget(&*cx, &limit, &mut signed_refs, state.updates_mut())?
+    };
     // Clear rad tips so far. Fetch will ask the remote to advertise
     // all rad refs from the transitive trackings, so we can inspect
     // the state afterwards to see if we got any.
@@ -208,11 +240,20 @@ where
         local_id,
         remote_id,
         signed_refs,
-        limit: limit.data,
+        limit: budget.limit,
     };
+    phases.enter(&*cx, Phase::Fetch);
     info!("fetching data");
     debug!(?fetch);
-    state.step(cx, &fetch)?;
+    let received = cx.bytes_received();
+    state.step(cx, &fetch).map_err(|e| budget.fetch_error(e))?;
+    // The transitive fetch must not use up the quota a second time
+    if let Some(received) = received
+        .zip(cx.bytes_received())
+        .map(|(before, after)| after.saturating_sub(before))
+    {
+        budget.consume(received, limit.data);
+    }
 
     let mut signed_refs = fetch.signed_refs;
 
@@ -227,6 +268,7 @@ where
                 .copied()
                 .collect(),
             cutoff: 0,
+            revoked: &revoked,
         };
         let trans_sigrefs = sigrefs::combined(&state.as_shim(cx), selector)?;
         let trans_ids = state.id_tips().keys().copied().collect();
@@ -240,16 +282,19 @@ where
                 .into_iter()
                 .filter_map(|(id, policy)| matches!(policy, DataPolicy::Deny).then(|| id))
                 .collect(),
-            limit: limit.data,
+            limit: budget.limit,
         };
         info!("fetching transitively tracked data");
         debug!(?trans_fetch);
-        state.step(cx, &trans_fetch)?;
+        state
+            .step(cx, &trans_fetch)
+            .map_err(|e| budget.fetch_error(e))?;
         signed_refs
             .refs
             .append(&mut trans_fetch.signed_refs.flattened().refs);
     }
 
+    phases.enter(&*cx, Phase::RefUpdate);
     info!("updating tips");
     applied.append(&mut Refdb::update(cx, state.updates_mut().drain(..))?);
     for u in &applied.updated {
@@ -259,6 +304,7 @@ where
     info!("updating signed refs");
     SignedRefs::update(cx)?;
 
+    phases.enter(&*cx, Phase::Validation);
     let mut warnings = Vec::new();
     debug!(?signed_refs);
     info!("validating signed trees");
@@ -300,6 +346,7 @@ where
         tracked: newly_tracked,
         requires_confirmation,
         validation: warnings,
+        quota: budget.exceeded,
         _marker: PhantomData,
     })
 }
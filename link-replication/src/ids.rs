@@ -3,7 +3,10 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{collections::BTreeSet, fmt::Debug};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+};
 
 use link_crypto::PeerId;
 use link_git::protocol::{oid, ObjectId};
@@ -33,6 +36,11 @@ pub trait VerifiedIdentity: Sized {
     /// Set of all URNs this identity delegates to (ie. indirect delegations).
     /// Possibly empty.
     fn delegate_urns(&self) -> BTreeSet<Self::Urn>;
+
+    /// Keys revoked by this identity, directly or through its indirect
+    /// delegations, along with the commits signed by them which remain
+    /// valid. Possibly empty.
+    fn revoked(&self) -> BTreeMap<PeerId, BTreeSet<Self::Oid>>;
 }
 
 pub trait Urn: Sized {
@@ -7,7 +7,7 @@ mod net;
 pub use net::{Connection, Network};
 
 mod odb;
-pub use odb::Odb;
+pub use odb::{Odb, Stats as OdbStats};
 
 mod refdb;
 pub use refdb::{Refdb, UserInfo};
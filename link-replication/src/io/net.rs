@@ -22,6 +22,11 @@ pub trait Connection {
     type Error: std::error::Error + Send + Sync + 'static;
 
     async fn open_stream(&self) -> Result<(Self::Read, Self::Write), Self::Error>;
+
+    /// Total number of bytes received over this connection, if known.
+    fn bytes_received(&self) -> Option<u64> {
+        None
+    }
 }
 
 pub struct Network<U, D, B, C> {
@@ -162,6 +167,10 @@ where
 
         Ok(())
     }
+
+    fn bytes_received(&self) -> Option<u64> {
+        self.conn.bytes_received()
+    }
 }
 
 fn io_other<E>(e: E) -> io::Error
@@ -29,6 +29,19 @@ impl Odb {
 
         Ok(Self(Arc::new(odb::Odb { loose, packed })))
     }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            index: self.0.packed.index.stats(),
+            window: self.0.packed.data.stats(),
+        }
+    }
+}
+
+/// Statistics of the packfile index and data caches of an [`Odb`].
+pub struct Stats {
+    pub index: index::StatsView,
+    pub window: window::StatsView,
 }
 
 impl Thickener for Odb {
@@ -28,6 +28,10 @@ pub mod refs;
 
 mod eval;
 
+pub mod metrics;
+pub use metrics::Metrics;
+use metrics::{Phase, Phases};
+
 mod ids;
 pub use ids::{AnyIdentity, Identities, LocalIdentity, Urn, VerifiedIdentity};
 
@@ -36,6 +40,9 @@ pub use odb::Odb;
 
 mod prepare;
 
+mod quota;
+pub use quota::{Quota, Usage};
+
 mod refdb;
 pub use refdb::{Applied, Policy, RefScan, Refdb, SymrefTarget, Update, Updated};
 
@@ -71,6 +78,7 @@ pub trait LocalPeer {
 pub struct FetchLimit {
     pub peek: u64,
     pub data: u64,
+    pub quota: Quota,
 }
 
 impl Default for FetchLimit {
@@ -78,6 +86,7 @@ impl Default for FetchLimit {
         Self {
             peek: 1024 * 1024 * 5,
             data: 1024 * 1024 * 1024 * 5,
+            quota: Quota::default(),
         }
     }
 }
@@ -92,11 +101,13 @@ pub fn pull<C>(
 where
     C: Identities
         + LocalPeer
+        + Metrics
         + Net
         + Refdb
         + Odb
         + SignedRefs<Oid = <C as Identities>::Oid>
-        + Tracking<Urn = <C as Identities>::Urn>,
+        + Tracking<Urn = <C as Identities>::Urn>
+        + Usage,
     <C as Identities>::Oid: Debug + PartialEq + Send + Sync + 'static,
     <C as Identities>::Urn: Clone + Debug + Ord,
     for<'a> &'a C: RefScan,
@@ -105,14 +116,18 @@ where
         return Err("cannot replicate from self".into());
     }
     let anchor = ids::current(cx)?.ok_or("pull: missing `rad/id`")?;
-    eval::pull(
+    let mut phases = Phases::default();
+    let res = eval::pull(
+        &mut phases,
         &mut FetchState::default(),
         cx,
         limit,
         anchor,
         remote_id,
         whoami,
-    )
+    );
+    phases.finish(&*cx, &res);
+    res
 }
 
 #[tracing::instrument(skip(cx, limit, whoami), fields(local_id = %LocalPeer::id(cx)))]
@@ -125,11 +140,13 @@ pub fn clone<C>(
 where
     C: Identities
         + LocalPeer
+        + Metrics
         + Net
         + Refdb
         + Odb
         + SignedRefs<Oid = <C as Identities>::Oid>
-        + Tracking<Urn = <C as Identities>::Urn>,
+        + Tracking<Urn = <C as Identities>::Urn>
+        + Usage,
     <C as Identities>::Oid: Debug + PartialEq + Send + Sync + 'static,
     <C as Identities>::Urn: Clone + Debug + Ord,
     for<'a> &'a C: RefScan,
@@ -138,7 +155,35 @@ where
     if LocalPeer::id(cx) == &remote_id {
         return Err("cannot replicate from self".into());
     }
+    let mut phases = Phases::default();
+    let res = clone_inner(&mut phases, cx, limit, remote_id, whoami);
+    phases.finish(&*cx, &res);
+    res
+}
+
+fn clone_inner<C>(
+    phases: &mut Phases,
+    cx: &mut C,
+    limit: FetchLimit,
+    remote_id: PeerId,
+    whoami: Option<LocalIdentity>,
+) -> Result<Success<<C as Identities>::Urn>, Error>
+where
+    C: Identities
+        + LocalPeer
+        + Metrics
+        + Net
+        + Refdb
+        + Odb
+        + SignedRefs<Oid = <C as Identities>::Oid>
+        + Tracking<Urn = <C as Identities>::Urn>
+        + Usage,
+    <C as Identities>::Oid: Debug + PartialEq + Send + Sync + 'static,
+    <C as Identities>::Urn: Clone + Debug + Ord,
+    for<'a> &'a C: RefScan,
+{
     let mut state = FetchState::default();
+    phases.enter(&*cx, Phase::Peek);
     state.step(
         cx,
         &peek::ForClone {
@@ -146,6 +191,7 @@ where
             limit: limit.peek,
         },
     )?;
+    phases.enter(&*cx, Phase::RadRefs);
     let anchor = Identities::verify(
         cx,
         state
@@ -154,5 +200,5 @@ where
             .expect("BUG: peek step must ensure we got a rad/id ref"),
         state.lookup_delegations(&remote_id),
     )?;
-    eval::pull(&mut state, cx, limit, anchor, remote_id, whoami)
+    eval::pull(phases, &mut state, cx, limit, anchor, remote_id, whoami)
 }
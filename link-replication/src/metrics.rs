@@ -0,0 +1,223 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use crate::{error, Applied, Error, Net, Success};
+
+/// The phases of a replication run.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Phase {
+    /// Fetching the identity and signed refs of the remote and tracked peers.
+    Peek,
+    /// Verifying identities, loading signed refs, and setting up the local
+    /// `rad/` hierarchy.
+    RadRefs,
+    /// Fetching the data, incl. transitively tracked data.
+    Fetch,
+    /// Updating the local refs.
+    RefUpdate,
+    /// Post-validation of the local signed trees.
+    Validation,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Self::Peek,
+        Self::RadRefs,
+        Self::Fetch,
+        Self::RefUpdate,
+        Self::Validation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Peek => "peek",
+            Self::RadRefs => "rad_refs",
+            Self::Fetch => "fetch",
+            Self::RefUpdate => "ref_update",
+            Self::Validation => "validation",
+        }
+    }
+}
+
+/// Reasons for refs not ending up in the desired state.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Rejection {
+    /// A ref update was rejected, eg. because it was not a fast-forward.
+    Update,
+    /// See [`error::Validation::Unexpected`].
+    Unexpected,
+    /// See [`error::Validation::Malformed`].
+    Malformed,
+    /// See [`error::Validation::Missing`].
+    Missing,
+    /// See [`error::Validation::MissingRadId`].
+    MissingRadId,
+    /// See [`error::Validation::MissingSigRefs`].
+    MissingSigRefs,
+    /// See [`error::Validation::MismatchedTips`].
+    MismatchedTips,
+    /// See [`error::Validation::NoData`].
+    NoData,
+    /// See [`error::Quota::Remote`].
+    Quota,
+}
+
+impl Rejection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Update => "update",
+            Self::Unexpected => "unexpected",
+            Self::Malformed => "malformed",
+            Self::Missing => "missing",
+            Self::MissingRadId => "missing_rad_id",
+            Self::MissingSigRefs => "missing_sigrefs",
+            Self::MismatchedTips => "mismatched_tips",
+            Self::NoData => "no_data",
+            Self::Quota => "quota",
+        }
+    }
+}
+
+impl From<&error::Validation> for Rejection {
+    fn from(e: &error::Validation) -> Self {
+        use error::Validation::*;
+
+        match e {
+            Unexpected(_) => Self::Unexpected,
+            Malformed { .. } => Self::Malformed,
+            Missing { .. } => Self::Missing,
+            MissingRadId(_) => Self::MissingRadId,
+            MissingSigRefs(_) => Self::MissingSigRefs,
+            MismatchedTips { .. } => Self::MismatchedTips,
+            NoData(_) => Self::NoData,
+        }
+    }
+}
+
+/// Measurement of a single [`Phase`].
+#[derive(Clone, Copy, Debug)]
+pub struct Measurement {
+    pub phase: Phase,
+    /// Wall-clock time the phase took.
+    pub elapsed: Duration,
+    /// Bytes received from the network during the phase, if known.
+    pub bytes_received: Option<u64>,
+    /// `false` if the phase failed, aborting the replication.
+    pub success: bool,
+}
+
+pub trait Metrics {
+    /// Record that a [`Phase`] has completed (successfully or not).
+    fn record_phase(&self, m: Measurement);
+
+    /// Record the number of refs updated and rejected by a (successful)
+    /// replication run.
+    fn record_refs(&self, updated: usize, rejected: usize);
+
+    /// Record why a ref did not end up in the desired state.
+    fn record_rejection(&self, reason: Rejection);
+}
+
+impl Metrics for () {
+    fn record_phase(&self, _: Measurement) {}
+    fn record_refs(&self, _: usize, _: usize) {}
+    fn record_rejection(&self, _: Rejection) {}
+}
+
+/// Tracks the [`Phase`]s of a replication run.
+///
+/// A phase may be entered more than once during a run, eg. a clone peeks at
+/// the remote's identity before peeking at the tracked peers. The time and
+/// bytes spent are summed up, and each phase is recorded at most once per run
+/// when the run [`finish`]es.
+///
+/// [`finish`]: Phases::finish
+#[derive(Default)]
+pub(crate) struct Phases {
+    current: Option<Running>,
+    completed: BTreeMap<Phase, Measurement>,
+}
+
+struct Running {
+    phase: Phase,
+    started: Instant,
+    bytes: Option<u64>,
+}
+
+impl Phases {
+    /// Enter `phase`, completing the current phase (if any) successfully.
+    pub fn enter<C>(&mut self, cx: &C, phase: Phase)
+    where
+        C: Net + Metrics,
+    {
+        self.complete(cx, true);
+        self.current = Some(Running {
+            phase,
+            started: Instant::now(),
+            bytes: cx.bytes_received(),
+        })
+    }
+
+    /// Complete the current phase (if any), with the outcome of the
+    /// replication run, and record all phases of the run.
+    pub fn finish<C, U>(&mut self, cx: &C, res: &Result<Success<U>, Error>)
+    where
+        C: Net + Metrics,
+    {
+        self.complete(cx, res.is_ok());
+        for (_, m) in std::mem::take(&mut self.completed) {
+            cx.record_phase(m)
+        }
+        if let Ok(success) = res {
+            let Applied { rejected, updated } = &success.applied;
+            cx.record_refs(updated.len(), rejected.len());
+            for _ in rejected {
+                cx.record_rejection(Rejection::Update)
+            }
+            for warning in &success.validation {
+                cx.record_rejection(warning.into())
+            }
+            for _ in &success.quota {
+                cx.record_rejection(Rejection::Quota)
+            }
+        }
+    }
+
+    fn complete<C>(&mut self, cx: &C, success: bool)
+    where
+        C: Net + Metrics,
+    {
+        if let Some(Running {
+            phase,
+            started,
+            bytes,
+        }) = self.current.take()
+        {
+            let elapsed = started.elapsed();
+            let bytes_received = bytes
+                .zip(cx.bytes_received())
+                .map(|(before, after)| after.saturating_sub(before));
+            self.completed
+                .entry(phase)
+                .and_modify(|m| {
+                    m.elapsed += elapsed;
+                    m.bytes_received = match (m.bytes_received, bytes_received) {
+                        (Some(a), Some(b)) => Some(a.saturating_add(b)),
+                        (a, b) => a.or(b),
+                    };
+                    m.success &= success;
+                })
+                .or_insert(Measurement {
+                    phase,
+                    elapsed,
+                    bytes_received,
+                    success,
+                });
+        }
+    }
+}
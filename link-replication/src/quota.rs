@@ -0,0 +1,148 @@
+// Copyright © 2022 The Radicle Link Contributors
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use link_crypto::PeerId;
+use link_git::protocol::take::LimitExceeded;
+
+use crate::{error, sigrefs, FetchLimit, Update};
+
+/// Bounds on the storage space a single [`crate::Urn`] may occupy, in bytes.
+///
+/// Unlike the fetch limits, which bound the size of a single packfile, quotas
+/// bound the accumulated size of the data in the namespace. `None` means no
+/// quota is enforced.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Quota {
+    /// Maximum size of all data in the namespace.
+    pub urn: Option<u64>,
+    /// Maximum size of the data of a single remote peer in the namespace.
+    pub remote: Option<u64>,
+}
+
+impl Quota {
+    pub fn is_unlimited(&self) -> bool {
+        self.urn.is_none() && self.remote.is_none()
+    }
+}
+
+/// Storage space accounting, for enforcing a [`Quota`].
+///
+/// Implementations are free to approximate the size, eg. by using the
+/// uncompressed size of the objects reachable from the refs in question.
+/// Methods are only invoked if the corresponding [`Quota`] is set.
+pub trait Usage {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Size in bytes of the data in the current namespace.
+    fn urn_usage(&self) -> Result<u64, Self::Error>;
+
+    /// Size in bytes of the data of `remote` in the current namespace.
+    fn remote_usage(&self, remote: &PeerId) -> Result<u64, Self::Error>;
+}
+
+/// The outcome of checking the [`Quota`] before fetching data.
+pub(crate) struct Budget {
+    /// Maximum number of bytes the packfile may have.
+    pub limit: u64,
+    /// Whether `limit` is determined by the remaining [`Quota::urn`].
+    pub bound: bool,
+    /// The remaining [`Quota::urn`], if set.
+    pub remaining: Option<u64>,
+    /// Remotes excluded from the fetch, because they exceed [`Quota::remote`].
+    pub exceeded: Vec<error::Quota>,
+}
+
+impl Budget {
+    /// Account for `received` bytes written by a fetch, adjusting `limit` for
+    /// the next fetch of the same pull.
+    ///
+    /// `data` is the packfile size limit regardless of any [`Quota`].
+    pub fn consume(&mut self, received: u64, data: u64) {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(received);
+            self.limit = (*remaining).min(data);
+            self.bound = *remaining < data;
+        }
+    }
+
+    /// Attribute a failure of a fetch limited by this [`Budget`].
+    ///
+    /// Only a packfile exceeding the size limit is attributed to the
+    /// [`Quota`], any other error is returned as is.
+    pub fn fetch_error(&self, e: error::Error) -> error::Error {
+        if self.bound && LimitExceeded::is_cause_of(&*e) {
+            Box::new(error::Quota::Fetch {
+                remaining: self.limit,
+                source: e,
+            })
+        } else {
+            e
+        }
+    }
+}
+
+/// Check the [`Quota`] of `limit` against the current [`Usage`].
+///
+/// Remotes exceeding [`Quota::remote`] are removed from `signed_refs`, and
+/// pending `updates` of their refs are discarded. If [`Quota::urn`] is
+/// exceeded already, an error is returned. Otherwise, the packfile size is
+/// limited to the remaining quota.
+///
+/// Note that quotas are checked before fetching, so a remote may exceed its
+/// quota by the size of one fetch.
+pub(crate) fn budget<C, Oid>(
+    cx: &C,
+    limit: &FetchLimit,
+    signed_refs: &mut sigrefs::Flattened<Oid>,
+    updates: &mut Vec<Update<'static>>,
+) -> Result<Budget, error::Quota>
+where
+    C: Usage,
+{
+    let usage = |e: C::Error| error::Quota::Usage(Box::new(e));
+
+    let mut exceeded = Vec::new();
+    if let Some(quota) = limit.quota.remote {
+        let mut over = Vec::new();
+        for remote in signed_refs.refs.keys() {
+            let used = cx.remote_usage(remote).map_err(usage)?;
+            if used >= quota {
+                warn!(%remote, used, quota, "remote exceeds storage quota");
+                over.push(*remote);
+                exceeded.push(error::Quota::Remote {
+                    remote: *remote,
+                    used,
+                    quota,
+                });
+            }
+        }
+        for remote in over {
+            signed_refs.refs.remove(&remote);
+            signed_refs.remotes.remove(&remote);
+            let prefix = format!("refs/remotes/{}/", remote);
+            updates.retain(|up| !up.refname().as_str().starts_with(&prefix));
+        }
+    }
+
+    match limit.quota.urn {
+        None => Ok(Budget {
+            limit: limit.data,
+            bound: false,
+            remaining: None,
+            exceeded,
+        }),
+        Some(quota) => {
+            let used = cx.urn_usage().map_err(usage)?;
+            if used >= quota {
+                return Err(error::Quota::Urn { used, quota });
+            }
+            let remaining = quota - used;
+            Ok(Budget {
+                limit: remaining.min(limit.data),
+                bound: remaining < limit.data,
+                remaining: Some(remaining),
+                exceeded,
+            })
+        },
+    }
+}
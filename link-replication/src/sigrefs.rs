@@ -45,6 +45,18 @@ pub trait SignedRefs {
         cutoff: usize,
     ) -> Result<Option<Sigrefs<Self::Oid>>, Self::Error>;
 
+    /// Whether `ancestor` is the same commit as `descendant`, or one of its
+    /// ancestors.
+    ///
+    /// Commits not present locally must yield `false` rather than an error, as
+    /// the holder of a revoked key could otherwise block replication by
+    /// publishing unrelated history.
+    fn is_ancestor(
+        &self,
+        ancestor: impl Into<ObjectId>,
+        descendant: impl Into<ObjectId>,
+    ) -> Result<bool, Self::Error>;
+
     /// Compute and update the sigrefs for the local peer.
     ///
     /// A `None` return value denotes a no-op (ie. the sigrefs were already
@@ -148,28 +160,63 @@ pub struct Select<'a> {
     pub must: &'a BTreeSet<PeerId>,
     pub may: &'a BTreeSet<PeerId>,
     pub cutoff: usize,
+    /// Revoked keys, and the commits signed by them which remain valid.
+    ///
+    /// Sigrefs of a revoked key are ignored, unless they are one of those
+    /// commits or an ancestor of one of them.
+    pub revoked: &'a BTreeMap<PeerId, BTreeSet<ObjectId>>,
 }
 
 pub fn combined<S>(
     s: &S,
-    Select { must, may, cutoff }: Select,
+    Select {
+        must,
+        may,
+        cutoff,
+        revoked,
+    }: Select,
 ) -> Result<Combined<S::Oid>, error::Combine<S::Error>>
 where
     S: SignedRefs,
 {
-    let must = must.iter().map(|id| {
-        SignedRefs::load(s, id, cutoff)
-            .map_err(error::Combine::from)
-            .and_then(|sr| match sr {
-                None => Err(error::Combine::NotFound(*id)),
-                Some(sr) => Ok((id, sr)),
-            })
+    let is_valid = |id: &PeerId, sr: &Sigrefs<S::Oid>| -> Result<bool, S::Error> {
+        match revoked.get(id) {
+            None => Ok(true),
+            Some(valid) => {
+                for anchor in valid {
+                    if s.is_ancestor(sr.at.as_ref().to_owned(), *anchor)? {
+                        return Ok(true);
+                    }
+                }
+                warn!(
+                    "ignoring sigrefs of {} at {}, not covered by the revocation of its key",
+                    id,
+                    sr.at.as_ref()
+                );
+                Ok(false)
+            },
+        }
+    };
+    let must = must.iter().filter_map(|id| {
+        match SignedRefs::load(s, id, cutoff).map_err(error::Combine::from) {
+            Ok(None) => Some(Err(error::Combine::NotFound(*id))),
+            Ok(Some(sr)) => match is_valid(id, &sr) {
+                Ok(true) => Some(Ok((id, sr))),
+                Ok(false) => None,
+                Err(e) => Some(Err(e.into())),
+            },
+            Err(e) => Some(Err(e)),
+        }
     });
     let may = may
         .iter()
         .filter_map(|id| match SignedRefs::load(s, id, cutoff) {
             Ok(None) => None,
-            Ok(Some(sr)) => Some(Ok((id, sr))),
+            Ok(Some(sr)) => match is_valid(id, &sr) {
+                Ok(true) => Some(Ok((id, sr))),
+                Ok(false) => None,
+                Err(e) => Some(Err(e.into())),
+            },
             Err(e) => Some(Err(e.into())),
         });
 
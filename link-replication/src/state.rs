@@ -258,6 +258,14 @@ where
         self.inner.load_at(treeish, of, cutoff)
     }
 
+    fn is_ancestor(
+        &self,
+        ancestor: impl Into<ObjectId>,
+        descendant: impl Into<ObjectId>,
+    ) -> Result<bool, Self::Error> {
+        self.inner.is_ancestor(ancestor, descendant)
+    }
+
     fn update(&self) -> Result<Option<Self::Oid>, Self::Error> {
         self.inner.update()
     }
@@ -15,6 +15,7 @@ pub struct Success<Urn> {
     pub tracked: Vec<Either<PeerId, Urn>>,
     pub requires_confirmation: bool,
     pub validation: Vec<error::Validation>,
+    pub quota: Vec<error::Quota>,
     pub(crate) _marker: PhantomData<Urn>,
 }
 
@@ -78,4 +79,10 @@ where
     pub fn validation_errors(&self) -> &[error::Validation] {
         &self.validation
     }
+
+    /// Remotes which were not fetched, because they exceed their storage
+    /// quota.
+    pub fn quota_exceeded(&self) -> &[error::Quota] {
+        &self.quota
+    }
 }
@@ -45,6 +45,11 @@ pub trait Net {
         wants: NonEmptyVec<ObjectId>,
         haves: Vec<ObjectId>,
     ) -> Result<(), Self::Error>;
+
+    /// Total number of bytes received from the network so far, if known.
+    fn bytes_received(&self) -> Option<u64> {
+        None
+    }
 }
 
 pub trait Negotiation<T = Self> {